@@ -0,0 +1,217 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::BTreeMap,
+    fmt, fs,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use super::error::{Error, ParseError};
+use super::solution::{Part, Runner};
+use super::utils::{column_of, parse_field};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator which counts allocations. Install it with `#[global_allocator]`
+/// to have [`bench()`] report them, otherwise they are always 0.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchStats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    /// Allocations made by a single run
+    pub allocations: u64,
+}
+
+impl BenchStats {
+    /// Summarises the timings of several runs, which needn't be sorted
+    pub fn from_timings(timings: &[Duration], allocations: u64) -> Self {
+        assert!(!timings.is_empty(), "no timings to summarise");
+
+        let mut timings = timings.to_vec();
+        timings.sort();
+        let p95_index = (timings.len() * 95).div_ceil(100) - 1;
+
+        BenchStats {
+            min: timings[0],
+            median: timings[timings.len() / 2],
+            p95: timings[p95_index],
+            allocations,
+        }
+    }
+
+    /// How much slower this median is than `baseline`'s, as a percentage
+    pub fn slowdown(&self, baseline: &BenchStats) -> f64 {
+        let baseline = baseline.median.as_secs_f64();
+        if baseline == 0.0 {
+            return 0.0;
+        }
+        (self.median.as_secs_f64() - baseline) / baseline * 100.0
+    }
+}
+
+/// Runs one part `iterations` times against `input`, which is parsed afresh each run
+pub fn bench(
+    solution: &dyn Runner,
+    part: Part,
+    input: &str,
+    iterations: usize,
+) -> Result<BenchStats, ParseError> {
+    let mut timings = Vec::with_capacity(iterations);
+    let mut allocations = 0;
+
+    for _ in 0..iterations.max(1) {
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        solution.run(part, input)?;
+        timings.push(start.elapsed());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+    }
+
+    Ok(BenchStats::from_timings(&timings, allocations))
+}
+
+/// Benchmark results saved from an earlier run, to compare new results against.
+/// Stored as one line per day and part: `day part min_ns median_ns p95_ns allocations`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Baseline {
+    pub stats: BTreeMap<(u8, Part), BenchStats>,
+}
+
+impl Baseline {
+    pub fn load(baseline_file: &str) -> Result<Baseline, Error> {
+        let contents =
+            fs::read_to_string(baseline_file).map_err(|why| Error::io(baseline_file, why))?;
+        Baseline::parse(&contents).map_err(|error| Error::parse(baseline_file, error))
+    }
+
+    pub fn parse(input: &str) -> Result<Baseline, ParseError> {
+        let mut stats = BTreeMap::new();
+
+        for (line_index, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [day, part, min, median, p95, allocations] = fields[..] else {
+                return Err(ParseError::new(
+                    line_index,
+                    0,
+                    "\"<day> <part> <min_ns> <median_ns> <p95_ns> <allocations>\"",
+                    line,
+                ));
+            };
+
+            let day = parse_field(line_index, line, day, "a day")?;
+            let part = Part::try_from(parse_field::<u8>(line_index, line, part, "a part")?)
+                .map_err(|_| {
+                    ParseError::new(line_index, column_of(line, part), "part 1 or 2", part)
+                })?;
+            let nanos = |field| {
+                parse_field(line_index, line, field, "a time in nanoseconds")
+                    .map(Duration::from_nanos)
+            };
+
+            stats.insert(
+                (day, part),
+                BenchStats {
+                    min: nanos(min)?,
+                    median: nanos(median)?,
+                    p95: nanos(p95)?,
+                    allocations: parse_field(line_index, line, allocations, "an allocation count")?,
+                },
+            );
+        }
+
+        Ok(Baseline { stats })
+    }
+
+    pub fn save(&self, baseline_file: &str) -> Result<(), Error> {
+        fs::write(baseline_file, self.to_string()).map_err(|why| Error::io(baseline_file, why))
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# day part min_ns median_ns p95_ns allocations")?;
+        for ((day, part), stats) in &self.stats {
+            writeln!(
+                f,
+                "{} {} {} {} {} {}",
+                day,
+                part,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.p95.as_nanos(),
+                stats.allocations
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_stats_from_timings() {
+        let timings: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        let stats = BenchStats::from_timings(&timings, 3);
+
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_millis(11), stats.median);
+        assert_eq!(Duration::from_millis(19), stats.p95);
+        assert_eq!(3, stats.allocations);
+    }
+
+    #[test]
+    fn test_bench_stats_slowdown() {
+        let baseline = BenchStats::from_timings(&[Duration::from_millis(10)], 0);
+        let stats = BenchStats::from_timings(&[Duration::from_millis(12)], 0);
+
+        assert!((stats.slowdown(&baseline) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let mut baseline = Baseline::default();
+        baseline.stats.insert(
+            (6, Part::Two),
+            BenchStats::from_timings(&[Duration::from_nanos(1500)], 42),
+        );
+
+        assert_eq!(baseline, Baseline::parse(&baseline.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_baseline_parse_invalid() {
+        assert_eq!(
+            Err(ParseError::new(0, 2, "a part", "x")),
+            Baseline::parse("6 x 1 2 3 4")
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{env, fs, io, path::PathBuf, time::UNIX_EPOCH};
+
+use super::Params;
+use super::solution::Part;
+
+/// Where answers are cached unless told otherwise
+pub const DEFAULT_CACHE_DIR: &str = ".cache/answers";
+
+/// Answers saved on disk, one file each, by day, part, parameters, input and build. Any
+/// change to the code makes a new build, which misses everything the old one cached, so
+/// an answer is only ever reused for exactly the same work.
+pub struct Cache {
+    dir: PathBuf,
+    /// `None` if the running executable couldn't be identified, in which case nothing is
+    /// read or saved
+    build: Option<u64>,
+}
+
+impl Cache {
+    /// The cache kept in `dir`, which is made when the first answer is saved
+    pub fn new(dir: impl Into<PathBuf>) -> Cache {
+        Cache {
+            dir: dir.into(),
+            build: build_id(),
+        }
+    }
+
+    fn file(&self, day: u8, part: Part, params: &Params, input: &str) -> Option<PathBuf> {
+        let mut hash = Fnv::default();
+        hash.write(&self.build?.to_le_bytes());
+        hash.write(&[day, part as u8]);
+        hash.write(params.to_string().as_bytes());
+        // So that parameters and input can't run into each other
+        hash.write(&[0]);
+        hash.write(input.as_bytes());
+        Some(
+            self.dir
+                .join(format!("day{:02}_part{}_{:016x}.txt", day, part, hash.0)),
+        )
+    }
+
+    /// The answer saved for this day, part, parameters and input, if there is one
+    pub fn get(&self, day: u8, part: Part, params: &Params, input: &str) -> Option<String> {
+        fs::read_to_string(self.file(day, part, params, input)?).ok()
+    }
+
+    /// Saves an answer for [`get`](Cache::get) to find next time
+    pub fn put(
+        &self,
+        day: u8,
+        part: Part,
+        params: &Params,
+        input: &str,
+        answer: &str,
+    ) -> io::Result<()> {
+        let Some(file) = self.file(day, part, params, input) else {
+            return Ok(());
+        };
+        fs::create_dir_all(&self.dir)?;
+        // Written aside and moved into place, so that a run stopped part way through
+        // can't leave half an answer behind
+        let partial = file.with_extension("partial");
+        fs::write(&partial, answer)?;
+        fs::rename(partial, file)
+    }
+
+    /// Removes every cached answer, from any build, returning how many there were
+    pub fn clear(&self) -> io::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension().is_some_and(|extension| extension == "txt") {
+                removed += 1;
+            }
+            fs::remove_file(file)?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Identifies the build that's running by its executable's size and when it was built.
+/// Cargo only relinks when something has changed, so these change exactly when the code
+/// does.
+fn build_id() -> Option<u64> {
+    let metadata = fs::metadata(env::current_exe().ok()?).ok()?;
+    let built = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let mut hash = Fnv::default();
+    hash.write(env!("CARGO_PKG_VERSION").as_bytes());
+    hash.write(&metadata.len().to_le_bytes());
+    hash.write(&built.as_nanos().to_le_bytes());
+    Some(hash.0)
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hasher is the same from one Rust
+/// release to the next, so cache files keep their names
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv() {
+        let mut hash = Fnv::default();
+        hash.write(b"foobar");
+        assert_eq!(0x85944171f73967e8, hash.0);
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = env::temp_dir().join(format!("aoc_cache_{}", std::process::id()));
+        let cache = Cache::new(&dir);
+        let params = Params::new();
+        let mut blinks = Params::new();
+        blinks.insert(String::from("part1_blinks"), toml::Value::Integer(6));
+
+        assert_eq!(None, cache.get(11, Part::One, &params, "125 17\n"));
+        cache
+            .put(11, Part::One, &params, "125 17\n", "55312")
+            .unwrap();
+        assert_eq!(
+            Some(String::from("55312")),
+            cache.get(11, Part::One, &params, "125 17\n")
+        );
+
+        // Anything else being different is a different answer
+        assert_eq!(None, cache.get(11, Part::Two, &params, "125 17\n"));
+        assert_eq!(None, cache.get(11, Part::One, &blinks, "125 17\n"));
+        assert_eq!(None, cache.get(11, Part::One, &params, "125 18\n"));
+        assert_eq!(None, cache.get(10, Part::One, &params, "125 17\n"));
+
+        assert_eq!(1, cache.clear().unwrap());
+        assert_eq!(None, cache.get(11, Part::One, &params, "125 17\n"));
+        assert_eq!(0, cache.clear().unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
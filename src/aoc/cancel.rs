@@ -0,0 +1,92 @@
+use std::{
+    error, fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Tells a solution to give up, either when its time budget runs out or when someone
+/// holding a clone calls [`Cancel::cancel`]. Solutions call [`Cancel::check`] in their
+/// hot loops and pass on the [`TimedOut`] it returns.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel {
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Returned by a solution which gave up before finding its answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out")
+    }
+}
+
+impl error::Error for TimedOut {}
+
+impl Cancel {
+    /// Never runs out of time, though it can still be cancelled
+    pub fn never() -> Self {
+        Cancel::default()
+    }
+
+    /// Runs out of time `budget` from now
+    pub fn after(budget: Duration) -> Self {
+        Cancel {
+            deadline: Instant::now().checked_add(budget),
+            cancelled: Arc::default(),
+        }
+    }
+
+    /// Runs out of time after `budget`, or never if there isn't one
+    pub fn within(budget: Option<Duration>) -> Self {
+        budget.map_or_else(Cancel::never, Cancel::after)
+    }
+
+    /// Makes this and every clone of it give up at their next check
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// `Err(TimedOut)` once it's time to give up, for use with `?`
+    pub fn check(&self) -> Result<(), TimedOut> {
+        if self.is_cancelled() {
+            Err(TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let cancel = Cancel::never();
+        let clone = cancel.clone();
+        assert_eq!(Ok(()), clone.check());
+
+        cancel.cancel();
+        assert_eq!(Err(TimedOut), clone.check());
+    }
+
+    #[test]
+    fn test_cancel_after() {
+        assert_eq!(Err(TimedOut), Cancel::after(Duration::ZERO).check());
+        assert_eq!(Ok(()), Cancel::after(Duration::from_secs(60)).check());
+        assert_eq!(Ok(()), Cancel::within(None).check());
+    }
+}
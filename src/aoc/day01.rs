@@ -1,57 +1,102 @@
 // https://adventofcode.com/2024/day/1
 
-use super::utils::get_lines;
-
-use std::iter::zip;
-
-struct Input {
-    left_list: Vec<u32>,
-    right_list: Vec<u32>,
+//! Day 1: Historian Hysteria. Comparing two lists of location IDs.
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::{parse_field, parse_input_file, validate_lines};
+
+use std::{fmt::Display, iter::zip};
+
+/// The two lists of location IDs, each sorted
+pub struct Input {
+    /// The left list, in ascending order
+    pub left_list: Vec<u32>,
+    /// The right list, in ascending order
+    pub right_list: Vec<u32>,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
+/// Reads the two columns of location IDs, skipping blank lines, and sorts each list
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut left_list: Vec<u32> = Vec::new();
     let mut right_list: Vec<u32> = Vec::new();
 
-    for line in lines {
+    for (line_index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
         let line_parts: Vec<&str> = line.split_whitespace().take(2).collect();
         if let [left, right] = &line_parts[..] {
-            left_list.push(left.parse::<u32>().unwrap());
-            right_list.push(right.parse::<u32>().unwrap());
+            left_list.push(parse_field(line_index, line, left, "a location ID")?);
+            right_list.push(parse_field(line_index, line, right, "a location ID")?);
+        } else {
+            return Err(ParseError::new(
+                line_index,
+                line.len(),
+                "two location IDs",
+                "",
+            ));
         }
     }
 
     left_list.sort();
     right_list.sort();
 
-    Input {
+    Ok(Input {
         left_list,
         right_list,
-    }
+    })
 }
 
-fn get_total_distance(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+/// Every line which isn't a pair of location IDs
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_lines(input, parse_input)
+}
 
+/// `size` pairs of location IDs
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            format!(
+                "{}   {}\n",
+                rng.between(10000, 99999),
+                rng.between(10000, 99999)
+            )
+        })
+        .collect()
+}
+
+pub fn get_total_distance(input_file: &str) -> Result<u32, Error> {
+    Ok(total_distance(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part one: the sum of the distances between the smallest remaining IDs in each list
+pub fn total_distance(input: &Input) -> u32 {
     let mut total_distance = 0;
 
-    let iter = zip(input.left_list, input.right_list);
+    let iter = zip(&input.left_list, &input.right_list);
 
     for (left, right) in iter {
-        total_distance += right.abs_diff(left)
+        total_distance += right.abs_diff(*left)
     }
 
     total_distance
 }
 
-fn get_similarity_score(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_similarity_score(input_file: &str) -> Result<u32, Error> {
+    Ok(similarity_score(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
+/// Part two: each left ID times how often it appears in the right list, summed
+pub fn similarity_score(input: &Input) -> u32 {
     let mut similarity_score = 0;
 
-    for left in input.left_list {
+    for &left in &input.left_list {
         let right_count = input.right_list.iter().filter(|&i| *i == left).count();
         similarity_score += left * right_count as u32;
     }
@@ -59,27 +104,40 @@ fn get_similarity_score(input_file: &str) -> u32 {
     similarity_score
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 1: [`total_distance`] and [`similarity_score`]
+pub struct Day01;
 
-    #[test]
-    fn test_get_total_distance_test01() {
-        assert_eq!(11, get_total_distance("input/day01_test01.txt"));
+impl Solution for Day01 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_total_distance() {
-        assert_eq!(1151792, get_total_distance("input/day01.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_similarity_score_test01() {
-        assert_eq!(31, get_similarity_score("input/day01_test01.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(total_distance(input))
     }
 
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(similarity_score(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
-    fn test_similarity_score() {
-        assert_eq!(21790168, get_similarity_score("input/day01.txt"));
+    fn test_get_total_distance_invalid01() {
+        match get_total_distance("input/day01_invalid01.txt") {
+            Err(Error::Parse { source, .. }) => {
+                assert_eq!(ParseError::new(2, 4, "a location ID", "x5"), source)
+            }
+            _ => panic!("expected a parse error"),
+        }
     }
 }
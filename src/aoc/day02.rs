@@ -1,25 +1,63 @@
 // https://adventofcode.com/2024/day/2
 
-use super::utils::get_lines;
+//! Day 2: Red-Nosed Reports. Checking which reactor reports are safe.
 
-struct Input {
-    reports: Vec<Vec<i32>>,
-}
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::parallel::sum_by;
+use super::solution::Solution;
+use super::utils::{parse_field, parse_input_file, validate_lines};
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+/// The reports, one list of levels per line
+pub struct Input {
+    /// Each report's levels, in order
+    pub reports: Vec<Vec<i32>>,
+}
 
+/// Reads each report's levels, which are separated by spaces
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut reports: Vec<Vec<i32>> = Vec::new();
 
-    for line in lines {
+    for (line_index, line) in input.lines().enumerate() {
         let report: Vec<i32> = line
             .split_whitespace()
-            .map(|level| level.parse::<i32>().unwrap())
-            .collect();
+            .map(|level| parse_field(line_index, line, level, "a level"))
+            .collect::<Result<_, _>>()?;
         reports.push(report);
     }
 
-    Input { reports }
+    Ok(Input { reports })
+}
+
+/// Every report with a level which isn't a number
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_lines(input, parse_input)
+}
+
+/// `size` reports of 5 to 8 levels, each a random walk which is often but not always safe
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let direction = rng.pick(&[-1, 1]);
+        let mut level = rng.between(10, 90);
+        let mut levels = vec![level];
+        for _ in 1..rng.between(5, 8) {
+            level += if rng.chance(0.9) {
+                direction * rng.between(1, 3)
+            } else {
+                rng.between(-4, 4)
+            };
+            levels.push(level);
+        }
+        input.push_str(&levels.iter().join(" "));
+        input.push('\n');
+    }
+    input
 }
 
 fn is_all_increasing(arr: &[i32]) -> bool {
@@ -34,39 +72,44 @@ fn is_all_adj_diff_in_limits(arr: &[i32]) -> bool {
     arr.iter().all(|elem| elem.abs() >= 1 && elem.abs() <= 3)
 }
 
-fn get_num_safe_reports(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
-
-    let mut num_safe_reports = 0;
-
-    for report in input.reports {
-        let gradients: Vec<i32> = report
-            .windows(2)
-            .map(|elems| {
-                if let [left, right] = elems {
-                    *right - *left
-                } else {
-                    panic!()
-                }
-            })
-            .collect();
-        if (is_all_increasing(&gradients) || is_all_decreasing(&gradients))
-            && is_all_adj_diff_in_limits(&gradients)
-        {
-            num_safe_reports += 1;
-        }
-    }
+pub fn get_num_safe_reports(input_file: &str) -> Result<u32, Error> {
+    Ok(num_safe_reports(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
-    num_safe_reports
+/// Part one: how many reports only increase or only decrease, by 1 to 3 each step
+pub fn num_safe_reports(input: &Input) -> u32 {
+    sum_by(&input.reports, |report| u32::from(is_safe(report)))
 }
 
-fn get_num_safe_reports_with_prob_damp(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+fn is_safe(report: &[i32]) -> bool {
+    let gradients: Vec<i32> = report
+        .windows(2)
+        .map(|elems| {
+            if let [left, right] = elems {
+                *right - *left
+            } else {
+                panic!()
+            }
+        })
+        .collect();
+    (is_all_increasing(&gradients) || is_all_decreasing(&gradients))
+        && is_all_adj_diff_in_limits(&gradients)
+}
 
-    let mut num_safe_reports = 0;
+pub fn get_num_safe_reports_with_prob_damp(input_file: &str) -> Result<u32, Error> {
+    Ok(num_safe_reports_with_prob_damp(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
-    for report in input.reports {
-        for n in 0..report.len() {
+/// Part two: how many reports are safe once the Problem Dampener can remove one level
+pub fn num_safe_reports_with_prob_damp(input: &Input) -> u32 {
+    sum_by(&input.reports, |report| {
+        let safe = (0..report.len()).any(|n| {
             let filtered_report: Vec<i32> = report
                 .iter()
                 .enumerate()
@@ -74,52 +117,31 @@ fn get_num_safe_reports_with_prob_damp(input_file: &str) -> u32 {
                 .map(|(_, n)| n)
                 .cloned()
                 .collect();
-            let gradients: Vec<i32> = filtered_report
-                .windows(2)
-                .map(|elems| {
-                    if let [left, right] = elems {
-                        *right - *left
-                    } else {
-                        panic!()
-                    }
-                })
-                .collect();
-            if (is_all_increasing(&gradients) || is_all_decreasing(&gradients))
-                && is_all_adj_diff_in_limits(&gradients)
-            {
-                num_safe_reports += 1;
-                break;
-            }
-        }
-    }
-
-    num_safe_reports
+            is_safe(&filtered_report)
+        });
+        u32::from(safe)
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 2: [`num_safe_reports`] and [`num_safe_reports_with_prob_damp`]
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_num_safe_reports_test01() {
-        assert_eq!(2, get_num_safe_reports("input/day02_test01.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_num_safe_reports() {
-        assert_eq!(686, get_num_safe_reports("input/day02.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_num_safe_reports_with_prob_damp_test01() {
-        assert_eq!(
-            4,
-            get_num_safe_reports_with_prob_damp("input/day02_test01.txt")
-        );
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(num_safe_reports(input))
     }
 
-    #[test]
-    fn test_get_num_safe_reports_with_prob_damp() {
-        assert_eq!(717, get_num_safe_reports_with_prob_damp("input/day02.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(num_safe_reports_with_prob_damp(input))
     }
 }
@@ -1,18 +1,67 @@
 // https://adventofcode.com/2024/day/3
 
+//! Day 3: Mull It Over. Finding the `mul` instructions in corrupted memory.
+
 use regex::Regex;
-use std::fs;
+use std::fmt::Display;
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::parse_input_file;
+
+/// The corrupted memory
+pub struct Input {
+    /// The whole input, including any line breaks
+    pub memory: String,
+}
+
+/// Takes the memory as it is, as any text is valid
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        memory: input.to_string(),
+    })
+}
 
-fn get_sum_mul(input_file: &str) -> u32 {
+/// `size` pieces of corrupted memory, each a `mul`, a `do()` or `don't()`, an almost `mul`
+/// or junk
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut memory = String::new();
+    for _ in 0..size {
+        let (x, y) = (rng.between(1, 999), rng.between(1, 999));
+        match rng.below(6) {
+            0 | 1 => memory.push_str(&format!("mul({},{})", x, y)),
+            2 => memory.push_str("do()"),
+            3 => memory.push_str("don't()"),
+            4 => memory.push_str(&match rng.below(3) {
+                0 => format!("mul[{},{}]", x, y),
+                1 => format!("mul ( {},{} )", x, y),
+                _ => format!("mul({},{}", x, y),
+            }),
+            _ => memory.extend(
+                (0..rng.between(1, 5))
+                    .map(|_| rng.pick(&['x', '%', '&', '!', '@', '^', '?', '<', ')'])),
+            ),
+        }
+    }
+    memory.push('\n');
+    memory
+}
+
+pub fn get_sum_mul(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_mul(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part one: the sum of every `mul(x,y)`
+pub fn sum_mul(input: &Input) -> u32 {
     lazy_static! {
         static ref RE_MUL: Regex =
-            Regex::new(r"(?P<operation>mul)\((?P<left>\d*),(?P<right>\d*)\)").unwrap();
+            Regex::new(r"(?P<operation>mul)\((?P<left>\d{1,3}),(?P<right>\d{1,3})\)").unwrap();
     }
-    let line: String = fs::read_to_string(input_file).unwrap();
-
     let mut sum_mul = 0;
 
-    for cap_mul in RE_MUL.captures_iter(&line) {
+    for cap_mul in RE_MUL.captures_iter(&input.memory) {
         let _ = cap_mul["operation"].to_string();
         let left_str = cap_mul["left"].to_string();
         let right_str = cap_mul["right"].to_string();
@@ -23,19 +72,22 @@ fn get_sum_mul(input_file: &str) -> u32 {
     sum_mul
 }
 
-fn get_sum_mul_cond(input_file: &str) -> u32 {
+pub fn get_sum_mul_cond(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_mul_cond(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part two: the sum of every `mul(x,y)` which isn't switched off by `don't()`
+pub fn sum_mul_cond(input: &Input) -> u32 {
     lazy_static! {
         static ref RE_MUL_COND: Regex =
-            Regex::new(r"(?P<operation>mul\((?P<left>\d*),(?P<right>\d*)\))|(?P<enable>do\(\))|(?P<disable>don't\(\))").unwrap();
+            Regex::new(r"(?P<operation>mul\((?P<left>\d{1,3}),(?P<right>\d{1,3})\))|(?P<enable>do\(\))|(?P<disable>don't\(\))").unwrap();
     }
-    let line: String = fs::read_to_string(input_file).unwrap();
-
     let mut sum_mul = 0;
 
     // We start out enabled
     let mut is_enabled = true;
 
-    for caps in RE_MUL_COND.captures_iter(&line) {
+    for caps in RE_MUL_COND.captures_iter(&input.memory) {
         if caps.name("enable").is_some() {
             is_enabled = true;
         } else if caps.name("disable").is_some() {
@@ -52,27 +104,36 @@ fn get_sum_mul_cond(input_file: &str) -> u32 {
     sum_mul
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 3: [`sum_mul`] and [`sum_mul_cond`]
+pub struct Day03;
 
-    #[test]
-    fn test_get_sum_mul_test01() {
-        assert_eq!(161, get_sum_mul("input/day03_test01.txt"));
+impl Solution for Day03 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_sum_mul() {
-        assert_eq!(180233229, get_sum_mul("input/day03.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_mul(input))
     }
 
-    #[test]
-    fn test_get_sum_mul_cond_test01() {
-        assert_eq!(48, get_sum_mul_cond("input/day03_test02.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_mul_cond(input))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
-    fn test_get_sum_mul_cond() {
-        assert_eq!(95411583, get_sum_mul_cond("input/day03.txt"));
+    fn test_sum_mul_operand_digits() {
+        // Neither a missing operand nor one of more than three digits makes a `mul`
+        let input = Input {
+            memory: String::from("mul(,4)mul(1234,5)mul(2,3)don't()mul(4,)do()mul(6,7)"),
+        };
+        assert_eq!(48, sum_mul(&input));
+        assert_eq!(48, sum_mul_cond(&input));
     }
 }
@@ -1,72 +1,38 @@
 // https://adventofcode.com/2024/day/4
 
-use super::utils::get_lines;
-use super::utils::Compass;
+//! Day 4: Ceres Search. A word search for XMAS.
 
-struct Input {
-    search_grid: Vec<Vec<char>>,
-    row_size: usize,
-    col_size: usize,
-}
-
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    let mut search_grid: Vec<Vec<char>> = Vec::new();
-
-    let mut row_size: usize = 0;
-
-    for line in lines {
-        let row: Vec<char> = line.chars().collect();
-        row_size = row.len();
-        search_grid.push(row);
-    }
+use std::fmt::Display;
 
-    let col_size = search_grid.len();
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::{Compass, Grid, parse_input_file};
 
-    Input {
-        search_grid,
-        row_size,
-        col_size,
-    }
+/// The word search
+pub struct Input {
+    /// The letters of the word search
+    pub search_grid: Grid<char>,
 }
 
-fn print_grid(search_grid: &Vec<Vec<char>>) {
-    for row in search_grid {
-        let _: String = row.iter().collect();
-    }
+/// Reads the word search as a grid of letters
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        search_grid: Grid::parse(input, "a letter", Some)?,
+    })
 }
 
-fn min_row_index(row_index: usize) -> usize {
-    if row_index as i32 - 1 < 0 {
-        0
-    } else {
-        row_index - 1
-    }
+/// Every row of the word search which isn't as long as the first
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    Grid::parse_all(input, "a letter", Some)
+        .err()
+        .unwrap_or_default()
 }
 
-fn min_col_index(col_index: usize) -> usize {
-    if col_index as i32 - 1 < 0 {
-        0
-    } else {
-        col_index - 1
-    }
-}
-
-fn max_row_index(row_index: usize, row_size: usize) -> usize {
-    if row_index as i32 + 1 > row_size as i32 - 1 {
-        row_size - 1
-    } else {
-        row_index + 1
-    }
-}
-
-fn max_col_index(col_index: usize, col_size: usize) -> usize {
-    if col_index as i32 + 1 > col_size as i32 - 1 {
-        col_size - 1
-    } else {
-        col_index + 1
-    }
+/// A `size` by `size` word search of X, M, A and S
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    rng.grid(size, size, |rng, _| rng.pick(&['X', 'M', 'A', 'S']))
 }
 
 fn check_next_in_xmas_seq(
@@ -76,11 +42,6 @@ fn check_next_in_xmas_seq(
     col_index: usize,
     input: &Input,
 ) -> Option<(usize, usize)> {
-    let min_row_index = min_row_index(row_index);
-    let min_col_index = min_col_index(col_index);
-    let max_row_index = max_row_index(row_index, input.row_size);
-    let max_col_index = max_col_index(col_index, input.col_size);
-
     //   0 1 2 3 4 5 6 7 8 9
     // 0 M M M S X X M A S M
     // 1 M S A M X M S M S A
@@ -93,69 +54,10 @@ fn check_next_in_xmas_seq(
     // 8 M A M M M X M M M M
     // 9 M X M X A X M A S X
 
-    match dir {
-        Compass::North => {
-            if exp_letter == input.search_grid[min_row_index][col_index]
-                && row_index != min_row_index
-            {
-                return Some((min_row_index, col_index));
-            }
-        }
-        Compass::NorthEast => {
-            if exp_letter == input.search_grid[min_row_index][max_col_index]
-                && row_index != min_row_index
-                && col_index != max_col_index
-            {
-                return Some((min_row_index, max_col_index));
-            }
-        }
-        Compass::East => {
-            if exp_letter == input.search_grid[row_index][max_col_index]
-                && col_index != max_col_index
-            {
-                return Some((row_index, max_col_index));
-            }
-        }
-        Compass::SouthEast => {
-            if exp_letter == input.search_grid[max_row_index][max_col_index]
-                && row_index != max_row_index
-                && col_index != max_col_index
-            {
-                return Some((max_row_index, max_col_index));
-            }
-        }
-        Compass::South => {
-            if exp_letter == input.search_grid[max_row_index][col_index]
-                && row_index != max_row_index
-            {
-                return Some((max_row_index, col_index));
-            }
-        }
-        Compass::SouthWest => {
-            if exp_letter == input.search_grid[max_row_index][min_col_index]
-                && row_index != max_row_index
-                && col_index != min_col_index
-            {
-                return Some((max_row_index, min_col_index));
-            }
-        }
-        Compass::West => {
-            if exp_letter == input.search_grid[row_index][min_col_index]
-                && col_index != min_col_index
-            {
-                return Some((row_index, min_col_index));
-            }
-        }
-        Compass::NorthWest => {
-            if exp_letter == input.search_grid[min_row_index][min_col_index]
-                && row_index != min_row_index
-                && col_index != min_col_index
-            {
-                return Some((min_row_index, min_col_index));
-            }
-        }
-    }
-    None
+    input
+        .search_grid
+        .step((row_index, col_index), dir)
+        .filter(|&next_pos| input.search_grid[next_pos] == exp_letter)
 }
 
 fn trace_xmas(
@@ -186,22 +88,18 @@ fn trace_xmas(
     }
 }
 
-fn get_sum_xmas(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_sum_xmas(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_xmas(&parse_input_file(input_file, parse_input)?))
+}
 
+/// Part one: how many times XMAS appears, in any of the eight directions
+pub fn sum_xmas(input: &Input) -> u32 {
     let mut sum_xmas = 0;
 
-    for i in 0..input.row_size {
-        for j in 0..input.col_size {
-            if input.search_grid[i][j] == 'X' {
-                sum_xmas += trace_xmas('X', Compass::North, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::NorthEast, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::East, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::SouthEast, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::South, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::SouthWest, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::West, i, j, &input);
-                sum_xmas += trace_xmas('X', Compass::NorthWest, i, j, &input);
+    for ((i, j), &letter) in input.search_grid.iter() {
+        if letter == 'X' {
+            for dir in Compass::ALL {
+                sum_xmas += trace_xmas('X', dir, i, j, input);
             }
         }
     }
@@ -215,75 +113,37 @@ fn check_next_in_x_mas_seq(
     col_index: usize,
     input: &Input,
 ) -> Option<char> {
-    let min_row_index = min_row_index(row_index);
-    let min_col_index = min_col_index(col_index);
-    let max_row_index = max_row_index(row_index, input.row_size);
-    let max_col_index = max_col_index(col_index, input.col_size);
+    let next_pos = input.search_grid.step((row_index, col_index), dir)?;
 
-    match dir {
-        Compass::NorthEast => {
-            if (input.search_grid[min_row_index][max_col_index] == 'S'
-                || input.search_grid[min_row_index][max_col_index] == 'M')
-                && row_index != min_row_index
-                && col_index != max_col_index
-            {
-                return Some(input.search_grid[min_row_index][max_col_index]);
-            }
-        }
-        Compass::SouthEast => {
-            if (input.search_grid[max_row_index][max_col_index] == 'S'
-                || input.search_grid[max_row_index][max_col_index] == 'M')
-                && row_index != max_row_index
-                && col_index != max_col_index
-            {
-                return Some(input.search_grid[max_row_index][max_col_index]);
-            }
-        }
-        Compass::SouthWest => {
-            if (input.search_grid[max_row_index][min_col_index] == 'S'
-                || input.search_grid[max_row_index][min_col_index] == 'M')
-                && row_index != max_row_index
-                && col_index != min_col_index
-            {
-                return Some(input.search_grid[max_row_index][min_col_index]);
-            }
-        }
-        Compass::NorthWest => {
-            if (input.search_grid[min_row_index][min_col_index] == 'S'
-                || input.search_grid[min_row_index][min_col_index] == 'M')
-                && row_index != min_row_index
-                && col_index != min_col_index
-            {
-                return Some(input.search_grid[min_row_index][min_col_index]);
-            }
-        }
-        _ => (),
+    match input.search_grid[next_pos] {
+        letter @ ('S' | 'M') => Some(letter),
+        _ => None,
     }
-    None
 }
 
-fn get_sum_x_mas(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_sum_x_mas(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_x_mas(&parse_input_file(input_file, parse_input)?))
+}
 
+/// Part two: how many times two diagonal MASes cross in an X
+pub fn sum_x_mas(input: &Input) -> u32 {
     let mut sum_x_mas = 0;
 
-    for i in 0..input.row_size {
-        for j in 0..input.col_size {
-            if input.search_grid[i][j] == 'A' {
-                let nw = check_next_in_x_mas_seq(Compass::NorthWest, i, j, &input);
-                let ne = check_next_in_x_mas_seq(Compass::NorthEast, i, j, &input);
-                let sw = check_next_in_x_mas_seq(Compass::SouthWest, i, j, &input);
-                let se = check_next_in_x_mas_seq(Compass::SouthEast, i, j, &input);
+    for ((i, j), &letter) in input.search_grid.iter() {
+        if letter == 'A' {
+            let nw = check_next_in_x_mas_seq(Compass::NorthWest, i, j, input);
+            let ne = check_next_in_x_mas_seq(Compass::NorthEast, i, j, input);
+            let sw = check_next_in_x_mas_seq(Compass::SouthWest, i, j, input);
+            let se = check_next_in_x_mas_seq(Compass::SouthEast, i, j, input);
 
-                let mut fwd_str: Vec<char> = vec![nw.unwrap_or('.'), 'A', se.unwrap_or('.')];
-                fwd_str.sort();
+            let mut fwd_str: Vec<char> = vec![nw.unwrap_or('.'), 'A', se.unwrap_or('.')];
+            fwd_str.sort();
 
-                let mut back_str: Vec<char> = vec![sw.unwrap_or('.'), 'A', ne.unwrap_or('.')];
-                back_str.sort();
+            let mut back_str: Vec<char> = vec![sw.unwrap_or('.'), 'A', ne.unwrap_or('.')];
+            back_str.sort();
 
-                if String::from_iter(fwd_str) == "AMS" && String::from_iter(back_str) == "AMS" {
-                    sum_x_mas += 1;
-                }
+            if String::from_iter(fwd_str) == "AMS" && String::from_iter(back_str) == "AMS" {
+                sum_x_mas += 1;
             }
         }
     }
@@ -291,27 +151,25 @@ fn get_sum_x_mas(input_file: &str) -> u32 {
     sum_x_mas
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 4: [`sum_xmas`] and [`sum_x_mas`]
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_sum_xmas_test01() {
-        assert_eq!(18, get_sum_xmas("input/day04_test01.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_sum_xmas() {
-        assert_eq!(2336, get_sum_xmas("input/day04.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_sum_x_mas_test01() {
-        assert_eq!(9, get_sum_x_mas("input/day04_test01.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_xmas(input))
     }
 
-    #[test]
-    fn test_get_sum_x_mas() {
-        assert_eq!(1831, get_sum_x_mas("input/day04.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_x_mas(input))
     }
 }
@@ -1,65 +1,143 @@
 // https://adventofcode.com/2024/day/5
 
-use super::utils::get_lines;
+//! Day 5: Print Queue. Checking safety manual updates against page ordering rules.
 
-struct Input {
-    page_order_rules: Vec<(u32, u32)>,
-    updates: Vec<Vec<u32>>,
-}
+use std::{collections::HashMap, fmt::Display};
+
+use itertools::Itertools;
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::dot::{DotOptions, to_dot};
+use super::utils::graph::{strongly_connected_components, topological_sort};
+use super::utils::{column_of, parse_field, parse_input_file, validate_lines};
+
+/// The page ordering rules followed by the updates
+pub struct Input {
+    /// Each `X|Y` rule, where page X must be printed before page Y
+    pub page_order_rules: Vec<(u32, u32)>,
+    /// The pages of each update, in the order they're printed
+    pub updates: Vec<Vec<u32>>,
+}
 
+/// Reads the `X|Y` page ordering rules and the comma separated updates. Each update needs
+/// an odd number of pages, so that it has a middle page, and can't repeat a page.
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut page_order_rules: Vec<(u32, u32)> = Vec::new();
     let mut updates: Vec<Vec<u32>> = Vec::new();
 
-    for line in lines {
-        let page_order_rule_parts: Vec<&str> = line.split(['|']).take(2).collect();
-        if let [left, right] = &page_order_rule_parts[..] {
-            page_order_rules.push((left.parse::<u32>().unwrap(), right.parse::<u32>().unwrap()));
+    for (line_index, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some((left, right)) = line.split_once('|') {
+            page_order_rules.push((
+                parse_field(line_index, line, left, "a page number")?,
+                parse_field(line_index, line, right, "a page number")?,
+            ));
+            continue;
         }
 
         let update_parts: Vec<&str> = line.split([',']).collect();
-        if update_parts.len() > 1 {
-            updates.push(
-                update_parts
-                    .iter()
-                    .map(|part| part.parse::<u32>().unwrap())
-                    .collect(),
-            );
+        // Without a middle page there's nothing to add up
+        if update_parts.len().is_multiple_of(2) {
+            return Err(ParseError::new(
+                line_index,
+                line.len(),
+                "an odd number of pages",
+                format!("{} pages", update_parts.len()),
+            ));
         }
+        let mut update: Vec<u32> = Vec::with_capacity(update_parts.len());
+        for part in update_parts {
+            let page = parse_field(line_index, line, part, "a page number")?;
+            // A page printed twice has no one place for the rules to put it
+            if update.contains(&page) {
+                return Err(ParseError::new(
+                    line_index,
+                    column_of(line, part),
+                    "each page only once",
+                    part,
+                ));
+            }
+            update.push(page);
+        }
+        updates.push(update);
     }
 
-    Input {
+    Ok(Input {
         page_order_rules,
         updates,
-    }
+    })
 }
 
-fn check_update_order(update: &[u32], page_order_rules: &Vec<(u32, u32)>) -> bool {
-    for i in 0..update.len() {
-        for j in i..update.len() {
-            if i != j {
-                let left = update[i];
-                let right = update[j];
-                for (left_rule, right_rule) in page_order_rules {
-                    if left == *right_rule && right == *left_rule {
-                        return false;
-                    }
-                }
-            }
+/// Every rule or update which doesn't parse, including updates with an even number of
+/// pages or a page repeated
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_lines(input, parse_input)
+}
+
+/// Rules ordering every pair of up to `size` pages, followed by `size` updates of an odd
+/// number of those pages, about half already in the right order and the rest in a random one
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut pages: Vec<u32> = (10..100).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(size.clamp(3, pages.len()));
+
+    let mut input = String::new();
+    for (i, left) in pages.iter().enumerate() {
+        for right in &pages[i + 1..] {
+            input.push_str(&format!("{}|{}\n", left, right));
         }
     }
-    true
+    input.push('\n');
+
+    for _ in 0..size {
+        let mut update = pages.clone();
+        rng.shuffle(&mut update);
+        let len = rng.between(1, (pages.len() as i64 - 1) / 2) as usize * 2 + 1;
+        let update = &mut update[..len];
+        if rng.chance(0.5) {
+            // The rules order the pages as they come in `pages`
+            update.sort_by_key(|page| pages.iter().position(|other| other == page));
+        }
+        input.push_str(&update.iter().join(","));
+        input.push('\n');
+    }
+    input
 }
 
-fn get_sum_middle_page_num(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+/// Whether every page of `update` comes before the pages `after` says must follow it
+fn is_ordered(update: &[u32], after: &impl Fn(&u32) -> Vec<u32>) -> bool {
+    let positions: HashMap<u32, usize> = update
+        .iter()
+        .enumerate()
+        .map(|(position, &page)| (page, position))
+        .collect();
+    update.iter().enumerate().all(|(position, page)| {
+        after(page)
+            .iter()
+            .all(|next| positions.get(next).is_none_or(|&next| next > position))
+    })
+}
 
+pub fn get_sum_middle_page_num(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_middle_page_num(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
+
+/// Part one: the sum of the middle pages of the updates which are already in order
+pub fn sum_middle_page_num(input: &Input) -> u32 {
+    let after = pages_after(input);
     let mut sum_middle_page_num = 0;
 
-    for update in input.updates {
-        if check_update_order(&update, &input.page_order_rules) {
+    for update in &input.updates {
+        if is_ordered(update, &after) {
             let middle = update.len() / 2;
             sum_middle_page_num += update[middle];
         }
@@ -68,68 +146,157 @@ fn get_sum_middle_page_num(input_file: &str) -> u32 {
     sum_middle_page_num
 }
 
-fn correct_update(update: &mut [u32], page_order_rules: &Vec<(u32, u32)>) {
-    for i in 0..update.len() {
-        for j in i..update.len() {
-            if i != j {
-                let left = update[i];
-                let right = update[j];
-                for (left_rule, right_rule) in page_order_rules {
-                    if left == *right_rule && right == *left_rule {
-                        update.swap(j, i);
-                    }
-                }
+pub fn get_sum_corrected_middle_page_num(input_file: &str) -> Result<u32, Error> {
+    sum_corrected_middle_page_num(
+        &parse_input_file(input_file, parse_input)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
+
+/// Part two: the sum of the middle pages of the out of order updates, once they're sorted.
+/// An update whose rules contradict each other can't be sorted, so is left out.
+pub fn sum_corrected_middle_page_num(input: &Input, cancel: &Cancel) -> Result<u32, TimedOut> {
+    let after = pages_after(input);
+    let mut sum_middle_page_num = 0;
+
+    for update in &input.updates {
+        cancel.check()?;
+        if is_ordered(update, &after) {
+            continue;
+        }
+        match topological_sort(&after, update.iter().copied()) {
+            Ok(sorted) => sum_middle_page_num += sorted[sorted.len() / 2],
+            Err(cycle) => {
+                trace_decision!(Debug, "Can't sort update {:?}, {}", update, cycle);
             }
         }
     }
+
+    Ok(sum_middle_page_num)
 }
 
-fn get_sum_corrected_middle_page_num(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+/// Every page named in a rule, in the order they're first named
+fn rule_pages(input: &Input) -> Vec<u32> {
+    input
+        .page_order_rules
+        .iter()
+        .flat_map(|&(left, right)| [left, right])
+        .unique()
+        .collect()
+}
 
-    let mut sum_middle_page_num = 0;
+/// The pages which must be printed after a page
+fn pages_after(input: &Input) -> impl Fn(&u32) -> Vec<u32> + use<> {
+    let after = input.page_order_rules.iter().copied().into_group_map();
+    move |page| after.get(page).cloned().unwrap_or_default()
+}
 
-    for update in input.updates {
-        if !check_update_order(&update, &input.page_order_rules) {
-            loop {
-                let mut mut_update: Vec<u32> = update.to_vec();
-                correct_update(&mut mut_update, &input.page_order_rules);
-                if check_update_order(&mut_update, &input.page_order_rules) {
-                    let middle = mut_update.len() / 2;
-                    sum_middle_page_num += mut_update[middle];
-                    break;
-                }
-            }
-        }
+/// Each set of pages whose rules contradict each other, by going round in a circle
+pub fn rule_cycles(input: &Input) -> Vec<Vec<u32>> {
+    strongly_connected_components(&pages_after(input), rule_pages(input))
+        .into_iter()
+        .filter(|pages| pages.len() > 1)
+        .collect()
+}
+
+/// The page ordering rules as a DOT digraph, with an edge from each page to each page
+/// which must come after it
+pub fn rules_to_dot(input: &Input, options: &DotOptions<u32>) -> String {
+    to_dot(&pages_after(input), rule_pages(input), options)
+}
+
+/// The [`Solution`] for day 5: [`sum_middle_page_num`] and [`sum_corrected_middle_page_num`]
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    sum_middle_page_num
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
+    }
+
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_middle_page_num(input))
+    }
+
+    fn part2(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        sum_corrected_middle_page_num(input, cancel)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
-    fn test_get_sum_middle_page_num_test01() {
-        assert_eq!(143, get_sum_middle_page_num("input/day05_test01.txt"));
+    fn test_generate_input() {
+        for seed in 0..10 {
+            let input = parse_input(&generate_input(&mut Rng::new(seed), 12)).unwrap();
+            assert!(sum_middle_page_num(&input) > 0, "seed {}", seed);
+            assert!(
+                sum_corrected_middle_page_num(&input, &Cancel::never()).unwrap() > 0,
+                "seed {}",
+                seed
+            );
+        }
     }
 
     #[test]
-    fn test_get_sum_middle_page_num() {
-        assert_eq!(4996, get_sum_middle_page_num("input/day05.txt"));
+    fn test_sum_corrected_middle_page_num_contradictory_rules() {
+        let input = parse_input("1|2\n2|3\n3|1\n4|5\n5|6\n\n1,2,3\n6,5,4\n").unwrap();
+        assert_eq!(
+            Ok(5),
+            sum_corrected_middle_page_num(&input, &Cancel::never())
+        );
+        assert_eq!(
+            Err(TimedOut),
+            sum_corrected_middle_page_num(&input, &Cancel::after(Duration::ZERO))
+        );
     }
 
     #[test]
-    fn test_get_sum_corrected_middle_page_num_test01() {
+    fn test_rules_to_dot() {
+        let input = parse_input("1|2\n2|3\n3|2\n\n1,2,3\n").unwrap();
+        assert_eq!(vec![vec![2, 3]], rule_cycles(&input));
         assert_eq!(
-            123,
-            get_sum_corrected_middle_page_num("input/day05_test01.txt")
+            "digraph \"G\" {
+    n0 [label=\"1\"];
+    n1 [label=\"2\"];
+    n2 [label=\"3\"];
+    n0 -> n1;
+    n1 -> n2;
+    n2 -> n1;
+}
+",
+            rules_to_dot(&input, &DotOptions::new())
         );
+
+        let input = parse_input_file("input/day05_test01.txt", parse_input).unwrap();
+        assert!(rule_cycles(&input).is_empty());
     }
 
     #[test]
-    fn test_get_sum_corrected_middle_page_num() {
-        assert_eq!(6311, get_sum_corrected_middle_page_num("input/day05.txt"));
+    fn test_validate_input() {
+        assert_eq!(
+            vec![
+                ParseError::new(1, 2, "a page number", "x"),
+                ParseError::new(3, 3, "an odd number of pages", "2 pages"),
+                ParseError::new(5, 2, "a page number", "y"),
+                ParseError::new(6, 0, "a page number", "abc"),
+                ParseError::new(8, 4, "each page only once", "1"),
+            ],
+            validate_input("1|2\n2|x\n\n1,2\n1,2,3\n1,y,3\nabc\n7\n1,2,1\n")
+        );
+
+        // A single page is its own middle page
+        let input = parse_input("1|2\n\n7\n").unwrap();
+        assert_eq!(7, sum_middle_page_num(&input));
     }
 }
@@ -1,14 +1,23 @@
 // https://adventofcode.com/2024/day/6
 
-use std::collections::HashSet;
+//! Day 6: Guard Gallivant. Following a guard's patrol around a lab.
 
-use super::utils::get_lines;
-use super::utils::Direction;
-use super::utils::ListNode;
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
 
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::parallel::sum_by;
+use super::solution::Solution;
+use super::utils::{Direction, Grid, parse_input_file};
+
+/// A tile of the lab map, as the character it's drawn with
 #[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
 #[repr(u8)]
-enum MapEntry {
+pub enum MapEntry {
     #[default]
     Obstruction = b'#',
     GuardN = b'^',
@@ -28,55 +37,81 @@ impl TryFrom<u8> for MapEntry {
     }
 }
 
-struct Input {
-    map: Vec<Vec<MapEntry>>,
+impl Display for MapEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as u8 as char)
+    }
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    let mut iter = lines.split(|e| e.is_empty());
+/// The map of the lab
+pub struct Input {
+    /// The lab, with the guard facing north at their start
+    pub map: Grid<MapEntry>,
+    /// Where the guard starts
+    pub start: (usize, usize),
+}
 
-    Input {
-        map: parse_map(iter.next().unwrap().to_owned()),
+/// Reads the map of the lab and finds the guard, who must be its only `^`
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    if let Some(error) = validate_input(input).into_iter().next() {
+        return Err(error);
     }
+    let map = parse_map(input).unwrap();
+    let start = get_start_pos(&map).ok_or_else(missing_guard)?;
+    Ok(Input { map, start })
 }
 
-fn parse_map(map_lines: Vec<String>) -> Vec<Vec<MapEntry>> {
-    let mut map = vec![];
-    for map_line in map_lines.into_iter() {
-        let mut map_entries: Vec<MapEntry> = Vec::new();
-        for map_entry in map_line.chars() {
-            match MapEntry::try_from(map_entry as u8) {
-                Ok(pipe) => map_entries.push(pipe),
-                Err(_) => panic!("Invalid map entry {}", map_entry),
-            }
-        }
-        map.push(map_entries);
-    }
-    map
+/// Every tile of the lab which isn't `#`, `^` or `.`, and a missing guard or any after the
+/// first, in the order they appear
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    let mut errors = parse_map(input).err().unwrap_or_default();
+    errors.extend(guard_errors(input));
+    errors.sort_by_key(|error| (error.line, error.column));
+    errors
 }
 
-fn print_map(map: &[Vec<MapEntry>]) {
-    println!("Grid:");
-    for map_row in map.iter() {
-        for map_entry in map_row {
-            print!("{:#}", *map_entry as u8 as char);
-        }
-        println!();
+fn parse_map(input: &str) -> Result<Grid<MapEntry>, Vec<ParseError>> {
+    Grid::parse_all(input, "one of '#', '^' or '.'", |c| {
+        u8::try_from(c)
+            .ok()
+            .and_then(|b| MapEntry::try_from(b).ok())
+    })
+}
+
+/// There must be exactly one guard, else there's no patrol to follow
+fn guard_errors(input: &str) -> Vec<ParseError> {
+    let guards: Vec<(usize, usize)> = input
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .enumerate()
+        .flat_map(|(line_index, line)| {
+            line.match_indices('^')
+                .map(move |(col_index, _)| (line_index, col_index))
+        })
+        .collect();
+    if guards.is_empty() {
+        return vec![missing_guard()];
     }
+    guards[1..]
+        .iter()
+        .map(|&(line_index, col_index)| {
+            ParseError::new(line_index, col_index, "only one guard", "^")
+        })
+        .collect()
 }
 
-struct ListNodeEntry {
-    map_entry: MapEntry,
-    pos: (usize, usize),
-    direction: Direction,
+fn missing_guard() -> ParseError {
+    ParseError::new(0, 0, "a guard '^'", "")
 }
 
-struct ListNodePos {
-    next_pos: (usize, usize),
-    next_dir: Direction,
-    alternate_dir: Direction,
+/// A `size` by `size` lab with scattered obstructions and the guard somewhere clear
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let guard = (rng.below(size), rng.below(size));
+    rng.grid(size, size, |rng, pos| match pos {
+        pos if pos == guard => '^',
+        _ if rng.chance(0.1) => '#',
+        _ => '.',
+    })
 }
 
 #[derive(PartialEq)]
@@ -85,192 +120,74 @@ struct VisitedNodeEntry {
     direction: Direction,
 }
 
-fn process_list_entry(
-    map: &Vec<Vec<MapEntry>>,
-    list_node: &mut ListNode<ListNodeEntry>,
-    visited_nodes: &mut Vec<VisitedNodeEntry>,
-    has_loop: &mut bool,
-    list_node_pos: &ListNodePos,
-) {
-    let (curr_row, curr_col) = list_node.val.pos;
-    let (next_pos_row, next_pos_col) = list_node_pos.next_pos;
-    let next_pri_map_entry = map[next_pos_row][next_pos_col];
-    if next_pri_map_entry == MapEntry::Obstruction {
-        /*println!(
-            "Found obstruction at pos {:?}, changing direction from {:?} to {:?}, pos {:?}",
-            (next_pos_row, next_pos_col),
-            list_node_pos.next_dir,
-            list_node_pos.alternate_dir,
-            (curr_row, curr_col)
-        );*/
-        list_node.next = Some(Box::new(ListNode::new(ListNodeEntry {
-            map_entry: MapEntry::Clear,
-            pos: (curr_row, curr_col),
-            direction: list_node_pos.alternate_dir,
-        })));
-        build_list(
-            map,
-            list_node.next.as_mut().unwrap(),
-            visited_nodes,
-            has_loop,
-        );
-    } else {
-        /*println!(
-            "Maintaining direction {:?}, pos {:?}",
-            list_node_pos.next_dir,
-            (next_pos_row, next_pos_col)
-        );*/
-        list_node.next = Some(Box::new(ListNode::new(ListNodeEntry {
-            map_entry: next_pri_map_entry,
-            pos: (next_pos_row, next_pos_col),
-            direction: list_node_pos.next_dir,
-        })));
-        visited_nodes.push(VisitedNodeEntry {
-            pos: (next_pos_row, next_pos_col),
-            direction: list_node_pos.next_dir,
-        });
-        build_list(
-            map,
-            list_node.next.as_mut().unwrap(),
-            visited_nodes,
-            has_loop,
-        );
-    }
-}
-
-fn build_list(
-    map: &Vec<Vec<MapEntry>>,
-    list_node: &mut ListNode<ListNodeEntry>,
-    visited_nodes: &mut Vec<VisitedNodeEntry>,
-    has_loop: &mut bool,
-) {
-    let (curr_row, curr_col) = list_node.val.pos;
-    if curr_row as i32 - 1 < 0 {
-        return;
-    }
-    if curr_col as i32 - 1 < 0 {
-        return;
-    }
-    if curr_row as i32 + 1 >= map.len() as i32 {
-        return;
-    }
-    if curr_col as i32 + 1 >= map[0].len() as i32 {
-        return;
-    }
-    if detect_loop(visited_nodes) {
-        *has_loop = true;
-        /*println!(
-            "Detected loop at pos {:?}, loop count {}",
-            list_node.val.pos, *has_loop
-        );*/
-        return;
-    }
-    let n_dir = (curr_row - 1, curr_col);
-    let s_dir = (curr_row + 1, curr_col);
-    let e_dir = (curr_row, curr_col + 1);
-    let w_dir = (curr_row, curr_col - 1);
-    match list_node.val.direction {
-        Direction::N => {
-            process_list_entry(
-                map,
-                list_node,
-                visited_nodes,
-                has_loop,
-                &ListNodePos {
-                    next_pos: n_dir,
-                    next_dir: Direction::N,
-                    alternate_dir: Direction::E,
-                },
-            );
-        }
-        Direction::S => {
-            process_list_entry(
-                map,
-                list_node,
-                visited_nodes,
-                has_loop,
-                &ListNodePos {
-                    next_pos: s_dir,
-                    next_dir: Direction::S,
-                    alternate_dir: Direction::W,
-                },
-            );
+/// Follows the guard from where they were last seen in `visited_nodes` until they leave the
+/// map, recording every step. `true` if they're caught in a loop instead. A loop, rather
+/// than recursing once per step, so that a long patrol can't run out of stack.
+fn patrol(map: &Grid<MapEntry>, visited_nodes: &mut Vec<VisitedNodeEntry>) -> bool {
+    let last = visited_nodes.last().unwrap();
+    let (mut pos, mut direction) = (last.pos, last.direction);
+    loop {
+        // The guard leaves once the next step would take them off the map
+        let Some(next_pos) = map.step(pos, direction.into()) else {
+            return false;
+        };
+        if detect_loop(visited_nodes) {
+            trace_decision!(Debug, "Detected loop at pos {:?}", pos);
+            return true;
         }
-        Direction::E => {
-            process_list_entry(
-                map,
-                list_node,
-                visited_nodes,
-                has_loop,
-                &ListNodePos {
-                    next_pos: e_dir,
-                    next_dir: Direction::E,
-                    alternate_dir: Direction::S,
-                },
+        // The guard turns right whenever something is in their way
+        if map[next_pos] == MapEntry::Obstruction {
+            trace_decision!(
+                Trace,
+                "Found obstruction at pos {:?}, changing direction from {:?} to {:?}, pos {:?}",
+                next_pos,
+                direction,
+                direction.turn_right(),
+                pos
             );
-        }
-        Direction::W => {
-            process_list_entry(
-                map,
-                list_node,
-                visited_nodes,
-                has_loop,
-                &ListNodePos {
-                    next_pos: w_dir,
-                    next_dir: Direction::W,
-                    alternate_dir: Direction::N,
-                },
+            direction = direction.turn_right();
+        } else {
+            trace_decision!(
+                Trace,
+                "Maintaining direction {:?}, pos {:?}",
+                direction,
+                next_pos
             );
+            pos = next_pos;
+            visited_nodes.push(VisitedNodeEntry { pos, direction });
         }
     }
 }
 
-fn get_start_pos(map: &[Vec<MapEntry>]) -> Option<(usize, usize)> {
-    for (row, map_col) in map.iter().enumerate() {
-        for (col, map_entry) in map_col.iter().enumerate() {
-            if *map_entry == MapEntry::GuardN {
-                return Some((row, col));
-            }
-        }
-    }
-    None
+fn get_start_pos(map: &Grid<MapEntry>) -> Option<(usize, usize)> {
+    map.position(&MapEntry::GuardN)
 }
 
 fn get_distinct_pos_vec(
-    map: &Vec<Vec<MapEntry>>,
+    map: &Grid<MapEntry>,
     (start_row, start_col): (usize, usize),
 ) -> (HashSet<(usize, usize)>, bool) {
-    let mut list_root = ListNode::new(ListNodeEntry {
-        map_entry: map[start_row][start_col],
-        pos: (start_row, start_col),
-        direction: Direction::N,
-    });
-
     let mut visited_nodes: Vec<VisitedNodeEntry> = vec![];
     visited_nodes.push(VisitedNodeEntry {
         pos: (start_row, start_col),
         direction: Direction::N,
     });
-    let mut has_loop = false;
-    build_list(map, &mut list_root, &mut visited_nodes, &mut has_loop);
+    let has_loop = patrol(map, &mut visited_nodes);
 
     let unique_visited_nodes: HashSet<(usize, usize)> =
         visited_nodes.drain(..).map(|entry| entry.pos).collect();
     (unique_visited_nodes, has_loop)
 }
 
-fn get_distinct_pos(input_file: &str) -> usize {
-    let input = parse_input(input_file);
+pub fn get_distinct_pos(input_file: &str) -> Result<usize, Error> {
+    Ok(distinct_pos(&parse_input_file(input_file, parse_input)?))
+}
 
-    if let Some((start_row, start_col)) = get_start_pos(&input.map) {
-        //println!("Start node found at pos {:?}", (start_row, start_col));
+/// Part one: how many distinct positions the guard visits before leaving the map
+pub fn distinct_pos(input: &Input) -> usize {
+    trace_decision!(Info, "Start node found at pos {:?}", input.start);
 
-        get_distinct_pos_vec(&input.map, (start_row, start_col))
-            .0
-            .len()
-    } else {
-        panic!("Invalid start node");
-    }
+    get_distinct_pos_vec(&input.map, input.start).0.len()
 }
 
 // Implements Floyd's cycle-finding algorithm using indices
@@ -296,42 +213,69 @@ fn detect_loop(visited_nodes: &mut [VisitedNodeEntry]) -> bool {
     false
 }
 
-fn get_sum_time_loop_pos(input_file: &str) -> usize {
-    let input = parse_input(input_file);
+pub fn get_sum_time_loop_pos(input_file: &str) -> Result<usize, Error> {
+    sum_time_loop_pos(
+        &parse_input_file(input_file, parse_input)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
-    let mut sum_time_loop_pos = 0usize;
-    if let Some((start_row, start_col)) = get_start_pos(&input.map) {
-        //println!("Start node found at pos {:?}", (start_row, start_col));
+/// Part two: how many positions a new obstruction could go which would trap the guard in a loop
+pub fn sum_time_loop_pos(input: &Input, cancel: &Cancel) -> Result<usize, TimedOut> {
+    trace_decision!(Info, "Start node found at pos {:?}", input.start);
 
-        let res = get_distinct_pos_vec(&input.map, (start_row, start_col));
+    let res = get_distinct_pos_vec(&input.map, input.start);
 
-        // Remove start node from map as instructed
-        let mut distinct_pos = res.0;
-        distinct_pos.remove(&(start_row, start_col));
+    // Remove start node from map as instructed
+    let mut distinct_pos = res.0;
+    distinct_pos.remove(&input.start);
 
-        //println!("Processing {} potential maps ", distinct_pos.len());
+    trace_counter!(Info, "potential maps", distinct_pos.len());
 
-        for (visited_row, visited_col) in distinct_pos {
-            let mut pot_map = input.map.clone();
-            pot_map[visited_row][visited_col] = MapEntry::Obstruction;
+    // Each obstruction is tried on its own copy of the map, so they can run in parallel
+    let distinct_pos: Vec<(usize, usize)> = distinct_pos.into_iter().collect();
+    let sum_time_loop_pos = sum_by(&distinct_pos, |&(visited_row, visited_col)| {
+        cancel.check()?;
+        let mut pot_map = input.map.clone();
+        pot_map[(visited_row, visited_col)] = MapEntry::Obstruction;
 
-            /*println!(
-                "Processing map with new obstruction at {:?}",
-                (visited_row, visited_col)
-            );
+        trace_decision!(
+            Debug,
+            "Processing map with new obstruction at {:?}",
+            (visited_row, visited_col)
+        );
+        trace_state!(Trace, "map", pot_map);
 
-            print_map(&pot_map);*/
+        let new_res = get_distinct_pos_vec(&pot_map, input.start);
+        Ok(usize::from(new_res.1))
+    })?;
 
-            let new_res = get_distinct_pos_vec(&pot_map, (start_row, start_col));
-            if new_res.1 {
-                sum_time_loop_pos += 1;
-            }
-        }
-    } else {
-        panic!("Invalid start node");
+    trace_counter!(Info, "loop positions", sum_time_loop_pos);
+    Ok(sum_time_loop_pos)
+}
+
+/// The [`Solution`] for day 6: [`distinct_pos`] and [`sum_time_loop_pos`]
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    sum_time_loop_pos
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
+    }
+
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(distinct_pos(input))
+    }
+
+    fn part2(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        sum_time_loop_pos(input, cancel)
+    }
 }
 
 #[cfg(test)]
@@ -340,78 +284,96 @@ mod tests {
 
     #[test]
     fn test_get_distinct_pos_vec_test02() {
-        let input = parse_input("input/day06_test02.txt");
+        let input = parse_input_file("input/day06_test02.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test03() {
-        let input = parse_input("input/day06_test03.txt");
+        let input = parse_input_file("input/day06_test03.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test04() {
-        let input = parse_input("input/day06_test04.txt");
+        let input = parse_input_file("input/day06_test04.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test05() {
-        let input = parse_input("input/day06_test05.txt");
+        let input = parse_input_file("input/day06_test05.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test06() {
-        let input = parse_input("input/day06_test06.txt");
+        let input = parse_input_file("input/day06_test06.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test07() {
-        let input = parse_input("input/day06_test07.txt");
+        let input = parse_input_file("input/day06_test07.txt", parse_input).unwrap();
         assert!(get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test08() {
-        let input = parse_input("input/day06_test08.txt");
+        let input = parse_input_file("input/day06_test08.txt", parse_input).unwrap();
         assert!(!get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
     fn test_get_distinct_pos_vec_test09() {
-        let input = parse_input("input/day06_test09.txt");
+        let input = parse_input_file("input/day06_test09.txt", parse_input).unwrap();
         assert!(!get_distinct_pos_vec(&input.map, (6, 4)).1);
     }
 
     #[test]
-    fn test_get_distinct_pos_test01() {
-        assert_eq!(41, get_distinct_pos("input/day06_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_distinct_pos() {
-        // Requires 8MB of stack space
-        stacker::grow(8 * 1024 * 1024, || {
-            assert_eq!(5564, get_distinct_pos("input/day06.txt"));
-        });
-    }
-
-    #[test]
-    fn test_get_sum_time_loop_pos_test01() {
-        assert_eq!(6, get_sum_time_loop_pos("input/day06_test01.txt"));
+    fn test_distinct_pos_small_stack() {
+        // The public API doesn't get the solver's stack, so mustn't need it
+        let input = parse_input_file("input/day06.txt", parse_input).unwrap();
+        let distinct_pos = std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(move || distinct_pos(&input))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(distinct_pos > 0);
     }
 
-    // This test takes a while so ignore in CI
-
-    #[ignore]
     #[test]
-    fn test_get_sum_time_loop_pos() {
-        // Requires 16MB of stack space
-        stacker::grow(16 * 1024 * 1024, || {
-            assert_eq!(1976, get_sum_time_loop_pos("input/day06.txt"));
-        });
+    fn test_validate_input() {
+        assert_eq!(
+            vec![ParseError::new(0, 0, "a guard '^'", "")],
+            validate_input("..#\n...\n")
+        );
+        assert_eq!(
+            vec![
+                ParseError::new(1, 2, "only one guard", "^"),
+                ParseError::new(2, 0, "only one guard", "^"),
+            ],
+            validate_input(".^.\n..^\n^..\n")
+        );
+        assert_eq!(
+            vec![
+                ParseError::new(0, 1, "one of '#', '^' or '.'", "x"),
+                ParseError::new(1, 2, "one of '#', '^' or '.'", "v"),
+            ],
+            validate_input(".x.\n^.v\n")
+        );
+        assert_eq!(
+            vec![
+                ParseError::new(1, 1, "one of '#', '^' or '.'", "x"),
+                ParseError::new(2, 0, "only one guard", "^"),
+            ],
+            validate_input("^..\n.x.\n^..\n")
+        );
+        assert_eq!(
+            Some(ParseError::new(0, 0, "a guard '^'", "")),
+            parse_input("...\n").err()
+        );
+        assert_eq!((1, 2), parse_input("...\n..^\n").unwrap().start);
     }
 }
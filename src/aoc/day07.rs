@@ -1,68 +1,117 @@
 // https://adventofcode.com/2024/day/7
 
-use std::iter::{self};
+//! Day 7: Bridge Repair. Finding which operators make calibration equations true.
 
-use itertools::Itertools;
+use std::{
+    fmt::Display,
+    iter::{self},
+};
 
-use super::utils::get_lines;
+use itertools::Itertools;
 
-struct Input {
-    equations: Vec<CalibrationEquation>,
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::parallel::sum_by;
+use super::solution::Solution;
+use super::utils::{parse_field, parse_input_file, validate_lines};
+
+/// The calibration equations, one per line
+pub struct Input {
+    /// The equations, in the order they were given
+    pub equations: Vec<CalibrationEquation>,
 }
 
+/// An equation such as `190: 10 19`, which is missing its operators
 #[derive(Debug)]
-struct CalibrationEquation {
-    result: u64,
-    terms: Vec<u64>,
+pub struct CalibrationEquation {
+    /// The value the equation should come to
+    pub result: u64,
+    /// The numbers to combine, always from left to right
+    pub terms: Vec<u64>,
 }
 
+/// The operators which can go between the terms
 #[derive(Debug, Clone, PartialEq)]
-enum Operator {
+pub enum Operator {
     Add,
     Multiply,
+    /// Joins the digits of the left and right terms, only used in part two
     Concat,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
+/// Reads each calibration equation, written as `<test value>: <numbers>`
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut equations: Vec<CalibrationEquation> = Vec::new();
 
-    for line in lines {
+    for (line_index, line) in input.lines().enumerate() {
         let line_parts: Vec<&str> = line.split(':').take(2).collect();
         if let [left, right] = &line_parts[..] {
-            let result = left.parse::<u64>().unwrap();
+            let result = parse_field(line_index, line, left, "a test value")?;
             let terms: Vec<u64> = right
                 .split_whitespace()
-                .map(|x| x.parse::<u64>().unwrap())
-                .collect();
+                .map(|x| parse_field(line_index, line, x, "a number"))
+                .collect::<Result<_, _>>()?;
             equations.push(CalibrationEquation { result, terms });
+        } else if !line.trim().is_empty() {
+            return Err(ParseError::new(line_index, line.len(), "':'", ""));
         }
     }
 
-    Input { equations }
+    Ok(Input { equations })
+}
+
+/// Every equation which isn't a test value followed by a colon and numbers
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_lines(input, parse_input)
+}
+
+/// `size` equations of 2 to 6 terms, about half of which some operators make true
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let terms: Vec<u64> = (0..rng.between(2, 6))
+            .map(|_| rng.between(1, 99) as u64)
+            .collect();
+        let result = if rng.chance(0.5) {
+            terms[1..]
+                .iter()
+                .fold(terms[0], |acc, &term| match rng.below(3) {
+                    0 => acc + term,
+                    1 => acc * term,
+                    _ => (acc.to_string() + &term.to_string()).parse().unwrap(),
+                })
+        } else {
+            rng.between(1, 100_000) as u64
+        };
+        input.push_str(&format!("{}: {}\n", result, terms.iter().join(" ")));
+    }
+    input
 }
 
-fn is_valid_calibration_result(equation: &CalibrationEquation) -> bool {
+fn is_valid_calibration_result(
+    equation: &CalibrationEquation,
+    cancel: &Cancel,
+) -> Result<bool, TimedOut> {
     let operators = [Operator::Add, Operator::Multiply];
 
-    let operator_seqs: Vec<_> = iter::repeat(operators.iter())
-        .take(equation.terms.len())
-        .multi_cartesian_product()
-        .collect();
+    // There are 2^n of these, so they're made one at a time rather than all up front
+    let operator_seqs =
+        iter::repeat_n(operators.iter(), equation.terms.len()).multi_cartesian_product();
 
     let first_val = *equation.terms.first().unwrap_or(&0);
 
     for operator_seq in operator_seqs {
+        cancel.check()?;
         let result = equation.terms.iter().zip(operator_seq.iter()).skip(1).fold(
             first_val,
             |acc, (term, operator)| match operator {
                 Operator::Add => {
-                    //println!("{} + {} = {}", acc, term, acc + term);
+                    trace_decision!(Trace, "{} + {} = {}", acc, term, acc + term);
                     acc + term
                 }
                 Operator::Multiply => {
-                    //println!("{} * {} = {}", acc, term, acc * term);
+                    trace_decision!(Trace, "{} * {} = {}", acc, term, acc * term);
                     acc * term
                 }
                 Operator::Concat => 0,
@@ -70,39 +119,46 @@ fn is_valid_calibration_result(equation: &CalibrationEquation) -> bool {
         );
 
         if result == equation.result {
-            //println!("{} == {}", result, equation.result);
-            return true;
+            trace_decision!(Debug, "{} == {}", result, equation.result);
+            return Ok(true);
         } else {
-            //println!("{} != {}", result, equation.result);
+            trace_decision!(Trace, "{} != {}", result, equation.result);
         }
     }
 
-    false
+    Ok(false)
 }
 
-fn get_total_calibration_result(input_file: &str) -> u64 {
-    let input = parse_input(input_file);
-
-    let mut total_calibration_result = 0;
+pub fn get_total_calibration_result(input_file: &str) -> Result<u64, Error> {
+    total_calibration_result(
+        &parse_input_file(input_file, parse_input)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
-    for equation in input.equations {
-        //println!("{:?}", equation);
-        if is_valid_calibration_result(&equation) {
-            total_calibration_result += equation.result;
+/// Part one: the sum of the results of the equations which `+` and `*` can make true
+pub fn total_calibration_result(input: &Input, cancel: &Cancel) -> Result<u64, TimedOut> {
+    sum_by(&input.equations, |equation| {
+        trace_decision!(Debug, "{:?}", equation);
+        if is_valid_calibration_result(equation, cancel)? {
+            Ok(equation.result)
+        } else {
+            Ok(0)
         }
-    }
-
-    total_calibration_result
+    })
 }
 
-fn is_valid_calibration_result_concat(equation: &CalibrationEquation) -> bool {
+fn is_valid_calibration_result_concat(
+    equation: &CalibrationEquation,
+    cancel: &Cancel,
+) -> Result<bool, TimedOut> {
     let operators = [Operator::Add, Operator::Multiply, Operator::Concat];
-    let operator_seqs: Vec<_> = iter::repeat(operators.iter())
-        .take(equation.terms.len())
-        .multi_cartesian_product()
-        .collect();
+    let operator_seqs =
+        iter::repeat_n(operators.iter(), equation.terms.len()).multi_cartesian_product();
 
     for operator_seq in operator_seqs {
+        cancel.check()?;
         for i in 0..operator_seq.len() {
             let (left_operator_seq, right_operator_seq) = operator_seq.split_at(i);
             let (left_terms, right_terms) = equation.terms.split_at(i);
@@ -113,11 +169,11 @@ fn is_valid_calibration_result_concat(equation: &CalibrationEquation) -> bool {
                     .zip(left_operator_seq.iter())
                     .fold(0, |acc, (term, operator)| match operator {
                         Operator::Add => {
-                            //println!("left {} + {} = {}", acc, term, acc + term);
+                            trace_decision!(Trace, "left {} + {} = {}", acc, term, acc + term);
                             acc + term
                         }
                         Operator::Multiply => {
-                            //println!("left {} * {} = {}", acc, term, acc * term);
+                            trace_decision!(Trace, "left {} * {} = {}", acc, term, acc * term);
                             acc * term
                         }
                         Operator::Concat => (acc.to_string() + &term.to_string()).parse().unwrap(),
@@ -127,11 +183,11 @@ fn is_valid_calibration_result_concat(equation: &CalibrationEquation) -> bool {
                 left_result,
                 |acc, (term, operator)| match operator {
                     Operator::Add => {
-                        //println!("right {} + {} = {}", acc, term, acc + term);
+                        trace_decision!(Trace, "right {} + {} = {}", acc, term, acc + term);
                         acc + term
                     }
                     Operator::Multiply => {
-                        //println!("right {} * {} = {}", acc, term, acc * term);
+                        trace_decision!(Trace, "right {} * {} = {}", acc, term, acc * term);
                         acc * term
                     }
                     Operator::Concat => (acc.to_string() + &term.to_string()).parse().unwrap(),
@@ -139,167 +195,60 @@ fn is_valid_calibration_result_concat(equation: &CalibrationEquation) -> bool {
             );
 
             if right_result == equation.result {
-                //println!("{} == {}", right_result, equation.result);
-                return true;
+                trace_decision!(Debug, "{} == {}", right_result, equation.result);
+                return Ok(true);
             } else {
-                //println!("{} != {}", right_result, equation.result);
+                trace_decision!(Trace, "{} != {}", right_result, equation.result);
             }
         }
     }
-    false
+    Ok(false)
 }
 
-fn get_total_calibration_result_with_concat(input_file: &str) -> u64 {
-    let input = parse_input(input_file);
-
-    let mut total_calibration_result = 0;
+pub fn get_total_calibration_result_with_concat(input_file: &str) -> Result<u64, Error> {
+    total_calibration_result_with_concat(
+        &parse_input_file(input_file, parse_input)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
-    for equation in input.equations {
-        if is_valid_calibration_result(&equation) || is_valid_calibration_result_concat(&equation) {
-            total_calibration_result += equation.result;
+/// Part two: as for part one, but also allowing the concatenation operator `||`
+pub fn total_calibration_result_with_concat(
+    input: &Input,
+    cancel: &Cancel,
+) -> Result<u64, TimedOut> {
+    sum_by(&input.equations, |equation| {
+        if is_valid_calibration_result(equation, cancel)?
+            || is_valid_calibration_result_concat(equation, cancel)?
+        {
+            Ok(equation.result)
+        } else {
+            Ok(0)
         }
-    }
-
-    total_calibration_result
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_total_calibration_result_test01() {
-        assert_eq!(3749, get_total_calibration_result("input/day07_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration() {
-        assert_eq!(
-            3245122495150,
-            get_total_calibration_result("input/day07.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test02() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test02.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test03() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test04() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test04.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test05() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test05.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test06() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test06.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test07() {
-        assert_eq!(3267, get_total_calibration_result("input/day07_test07.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test08() {
-        assert_eq!(10, get_total_calibration_result("input/day07_test08.txt"));
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_test09() {
-        assert_eq!(0, get_total_calibration_result("input/day07_test09.txt"));
-    }
+/// The [`Solution`] for day 7: [`total_calibration_result`] and
+/// [`total_calibration_result_with_concat`]
+pub struct Day07;
 
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test01() {
-        assert_eq!(
-            11387,
-            get_total_calibration_result_with_concat("input/day07_test01.txt")
-        );
-    }
+impl Solution for Day07 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test02() {
-        assert_eq!(
-            156,
-            get_total_calibration_result_with_concat("input/day07_test02.txt")
-        );
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test03() {
-        assert_eq!(
-            7290,
-            get_total_calibration_result_with_concat("input/day07_test03.txt")
-        );
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test04() {
-        assert_eq!(
-            192,
-            get_total_calibration_result_with_concat("input/day07_test04.txt")
-        );
+    fn part1(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        total_calibration_result(input, cancel)
     }
 
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test05() {
-        assert_eq!(
-            2,
-            get_total_calibration_result_with_concat("input/day07_test05.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test06() {
-        assert_eq!(
-            507905413443,
-            get_total_calibration_result_with_concat("input/day07_test06.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test07() {
-        assert_eq!(
-            3267,
-            get_total_calibration_result_with_concat("input/day07_test07.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test08() {
-        assert_eq!(
-            10,
-            get_total_calibration_result_with_concat("input/day07_test08.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_total_calibration_result_with_concat_test09() {
-        assert_eq!(
-            123,
-            get_total_calibration_result_with_concat("input/day07_test09.txt")
-        );
-    }
-
-    // This test takes a while so ignore in CI
-
-    #[ignore]
-    #[test]
-    fn test_get_total_distance_with_concat() {
-        assert_eq!(
-            105517128211543,
-            get_total_calibration_result_with_concat("input/day07.txt")
-        );
+    fn part2(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        total_calibration_result_with_concat(input, cancel)
     }
 }
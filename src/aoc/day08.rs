@@ -1,51 +1,63 @@
 // https://adventofcode.com/2024/day/8
 
-use std::collections::{HashMap, HashSet};
-
-use super::utils::get_lines;
-
-struct Input {
-    map: Vec<Vec<char>>,
+//! Day 8: Resonant Collinearity. Finding the antinodes of antennas.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::{Grid, parse_input_file};
+
+/// The map of antennas
+pub struct Input {
+    /// The map, where every character other than `.` is an antenna's frequency
+    pub map: Grid<char>,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    let mut map: Vec<Vec<char>> = Vec::new();
-
-    for line in lines {
-        let map_line: Vec<char> = line.chars().collect();
-        map.push(map_line);
-    }
-
-    Input { map }
+/// Reads the map, every tile of which is either `.` or an antenna
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        map: Grid::parse(input, "a map tile", Some)?,
+    })
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Node {
-    pos: (usize, usize),
-    val: char,
+/// Every row of the map which isn't as long as the first
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    Grid::parse_all(input, "a map tile", Some)
+        .err()
+        .unwrap_or_default()
 }
 
-fn print_map(map: &[Vec<char>]) {
-    println!("Grid:");
-    for map_row in map.iter() {
-        for map_entry in map_row {
-            print!("{:#}", *map_entry);
+/// A `size` by `size` map with a few antennas on each of a handful of frequencies
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let frequencies = ['a', 'A', '0', 'z', 'Z', '9'];
+    rng.grid(size, size, |rng, _| {
+        if rng.chance(0.05) {
+            rng.pick(&frequencies)
+        } else {
+            '.'
         }
-        println!();
-    }
+    })
 }
 
-fn get_node_map(map: &[Vec<char>]) -> HashMap<char, Vec<Node>> {
+/// An antenna on the map
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Node {
+    /// Where the antenna is, as (row, column)
+    pub pos: (usize, usize),
+    /// The antenna's frequency
+    pub val: char,
+}
+
+fn get_node_map(map: &Grid<char>) -> HashMap<char, Vec<Node>> {
     map.iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, c)| **c != '.')
-                .map(move |(j, &val)| (val, Node { pos: (i, j), val }))
-        })
+        .filter(|(_, c)| **c != '.')
+        .map(|(pos, &val)| (val, Node { pos, val }))
         .fold(HashMap::new(), |mut acc, (key, node)| {
             acc.entry(key).or_default().push(node);
             acc
@@ -55,50 +67,22 @@ fn get_node_map(map: &[Vec<char>]) -> HashMap<char, Vec<Node>> {
 fn get_antinode_pos(
     (row, col): (usize, usize),
     (other_row, other_col): (usize, usize),
-    antinode_map: &[Vec<char>],
+    antinode_map: &Grid<char>,
 ) -> Option<(usize, usize)> {
-    let row_diff = row as i32 - other_row as i32;
-    let col_diff = col as i32 - other_col as i32;
-    if row_diff.is_negative() && col_diff.is_negative() {
-        let new_row = row as i32 - row_diff.abs();
-        let new_col = col as i32 - col_diff.abs();
-        if new_row < 0 || new_col < 0 {
-            None
-        } else {
-            Some((new_row as usize, new_col as usize))
-        }
-    } else if row_diff.is_negative() && col_diff.is_positive() {
-        let new_row = row as i32 - row_diff.abs();
-        let new_col = col as i32 + col_diff.abs();
-        if new_row < 0 || new_col >= antinode_map[0].len() as i32 {
-            None
-        } else {
-            Some((new_row as usize, new_col as usize))
-        }
-    } else if row_diff.is_positive() && col_diff.is_negative() {
-        let new_row = row as i32 + row_diff.abs();
-        let new_col = col as i32 - col_diff.abs();
-        if new_row >= antinode_map.len() as i32 || new_col < 0 {
-            None
-        } else {
-            Some((new_row as usize, new_col as usize))
-        }
-    } else if row_diff.is_positive() && col_diff.is_positive() {
-        let new_row = row as i32 + row_diff.abs();
-        let new_col = col as i32 + col_diff.abs();
-        if new_row >= antinode_map.len() as i32 || new_col >= antinode_map[0].len() as i32 {
-            None
-        } else {
-            Some((new_row as usize, new_col as usize))
-        }
-    } else {
-        None
-    }
+    let row_diff = row as isize - other_row as isize;
+    let col_diff = col as isize - other_col as isize;
+    antinode_map.offset((row, col), (row_diff, col_diff))
 }
 
-fn get_unique_antinodes(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_unique_antinodes(input_file: &str) -> Result<u32, Error> {
+    Ok(unique_antinodes(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
+/// Part one: how many positions on the map hold an antinode
+pub fn unique_antinodes(input: &Input) -> u32 {
     let node_map: HashMap<char, Vec<Node>> = get_node_map(&input.map);
 
     let mut unique_antinodes: u32 = 0;
@@ -117,50 +101,56 @@ fn get_unique_antinodes(input_file: &str) -> u32 {
                     get_antinode_pos(node.pos, other_node.pos, &antinode_map);
 
                 if let Some((antinode_row, antinode_col)) = antinode_pos
-                    && let Some(map_node) = antinode_map
-                        .get(antinode_row)
-                        .and_then(|row| row.get(antinode_col))
+                    && let Some(map_node) = antinode_map.get((antinode_row, antinode_col))
                 {
                     match *map_node {
                         '.' => {
                             if !antinode_pos_set.contains(&(antinode_row, antinode_col)) {
-                                /*println!(
+                                trace_decision!(
+                                    Debug,
                                     "Found new antinode: {:?}, {:?}",
                                     *map_node,
                                     (antinode_row, antinode_col)
-                                );*/
-                                antinode_map[antinode_row][antinode_col] = '#';
+                                );
+                                antinode_map[(antinode_row, antinode_col)] = '#';
                                 unique_antinodes += 1;
                                 antinode_pos_set.insert((antinode_row, antinode_col));
                             }
                         }
                         '#' => {
-                            /*println!(
+                            trace_decision!(
+                                Trace,
                                 "Already occupied antinode: {:?}, {:?}",
                                 *map_node,
                                 (antinode_row, antinode_col)
-                            );*/
+                            );
                         }
                         _ => {
                             if !antinode_pos_set.contains(&(antinode_row, antinode_col)) {
-                                /*println!(
+                                trace_decision!(
+                                    Trace,
                                     "Found overlapping antinode: {:?}, {:?}",
                                     *map_node,
                                     (antinode_row, antinode_col)
-                                );*/
+                                );
                                 unique_antinodes += 1;
                                 antinode_pos_set.insert((antinode_row, antinode_col));
                             }
                         }
                     }
                 } else {
-                    //println!("Antinode out of bounds: {:?}", (antinode_row, antinode_col));
+                    trace_decision!(
+                        Trace,
+                        "Antinode out of bounds for {:?} and {:?}",
+                        node.pos,
+                        other_node.pos
+                    );
                 };
             }
         }
     }
 
-    //print_map(&antinode_map);
+    trace_state!(Debug, "antinodes", antinode_map);
 
     unique_antinodes
 }
@@ -168,42 +158,30 @@ fn get_unique_antinodes(input_file: &str) -> u32 {
 fn get_antinode_pos_vec(
     (row, col): (usize, usize),
     (other_row, other_col): (usize, usize),
-    antinode_map: &[Vec<char>],
+    antinode_map: &Grid<char>,
 ) -> Vec<(usize, usize)> {
     let mut antinode_pos_vec = vec![];
     antinode_pos_vec.push((row, col));
     antinode_pos_vec.push((other_row, other_col));
-    let row_diff = row as i32 - other_row as i32;
-    let col_diff = col as i32 - other_col as i32;
-    let mut count = 0;
-    loop {
-        let (curr_row, curr_col) = (row as i32 + row_diff * count, col as i32 + col_diff * count);
-        let (next_row, next_col) = (
-            other_row as i32 + row_diff * count,
-            other_col as i32 + col_diff * count,
-        );
-        let antinode_pos = get_antinode_pos(
-            (curr_row as usize, curr_col as usize),
-            (next_row as usize, next_col as usize),
-            antinode_map,
-        );
-        if let Some((antinode_row, antinode_col)) = antinode_pos {
-            antinode_pos_vec.push((antinode_row, antinode_col));
-            if antinode_row == other_row && antinode_col == other_col {
-                break;
-            }
-        } else {
-            break;
-        }
 
-        count += 1;
+    // Keep stepping away from the other antenna until we fall off the map
+    let (mut prev_pos, mut antinode_pos) = ((other_row, other_col), (row, col));
+    while let Some(next_pos) = get_antinode_pos(antinode_pos, prev_pos, antinode_map) {
+        antinode_pos_vec.push(next_pos);
+        (prev_pos, antinode_pos) = (antinode_pos, next_pos);
     }
     antinode_pos_vec
 }
 
-fn get_unique_antinodes_with_hr(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_unique_antinodes_with_hr(input_file: &str) -> Result<u32, Error> {
+    Ok(unique_antinodes_with_hr(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
+/// Part two: as for part one, but taking resonant harmonics into account
+pub fn unique_antinodes_with_hr(input: &Input) -> u32 {
     let node_map: HashMap<char, Vec<Node>> = get_node_map(&input.map);
 
     let mut unique_antinodes: u32 = 0;
@@ -222,101 +200,72 @@ fn get_unique_antinodes_with_hr(input_file: &str) -> u32 {
                     get_antinode_pos_vec(node.pos, other_node.pos, &antinode_map);
 
                 for (antinode_row, antinode_col) in antinode_pos_vec {
-                    if let Some(map_node) = antinode_map
-                        .get(antinode_row)
-                        .and_then(|row| row.get(antinode_col))
-                    {
+                    if let Some(map_node) = antinode_map.get((antinode_row, antinode_col)) {
                         match *map_node {
                             '.' => {
                                 if !antinode_pos_set.contains(&(antinode_row, antinode_col)) {
-                                    /*println!(
+                                    trace_decision!(
+                                        Debug,
                                         "Found new antinode: {:?}, {:?}",
                                         *map_node,
                                         (antinode_row, antinode_col)
-                                    );*/
-                                    antinode_map[antinode_row][antinode_col] = '#';
+                                    );
+                                    antinode_map[(antinode_row, antinode_col)] = '#';
                                     unique_antinodes += 1;
                                     antinode_pos_set.insert((antinode_row, antinode_col));
                                 }
                             }
                             '#' => {
-                                /*println!(
+                                trace_decision!(
+                                    Trace,
                                     "Already occupied antinode: {:?}, {:?}",
                                     *map_node,
                                     (antinode_row, antinode_col)
-                                );*/
+                                );
                             }
                             _ => {
                                 if !antinode_pos_set.contains(&(antinode_row, antinode_col)) {
-                                    /*println!(
+                                    trace_decision!(
+                                        Trace,
                                         "Found overlapping antinode: {:?}, {:?}",
                                         *map_node,
                                         (antinode_row, antinode_col)
-                                    );*/
+                                    );
                                     unique_antinodes += 1;
                                     antinode_pos_set.insert((antinode_row, antinode_col));
                                 }
                             }
                         }
-                    } else {
-                        //println!("Antinode out of bounds: {:?}", (antinode_row, antinode_col));
-                    };
+                    }
                 }
             }
         }
     }
 
-    //print_map(&antinode_map);
+    trace_state!(Debug, "antinodes", antinode_map);
 
     unique_antinodes
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 8: [`unique_antinodes`] and [`unique_antinodes_with_hr`]
+pub struct Day08;
 
-    #[test]
-    fn test_get_unique_antinodes_test01() {
-        assert_eq!(14, get_unique_antinodes("input/day08_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_unique_antinodes_test02() {
-        assert_eq!(2, get_unique_antinodes("input/day08_test02.txt"));
-    }
-
-    #[test]
-    fn test_get_unique_antinodes_test03() {
-        assert_eq!(4, get_unique_antinodes("input/day08_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_unique_antinodes_test04() {
-        assert_eq!(4, get_unique_antinodes("input/day08_test04.txt"));
-    }
-
-    #[test]
-    fn test_get_unique_antinodes() {
-        assert_eq!(261, get_unique_antinodes("input/day08.txt"));
-    }
+impl Solution for Day08 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_unique_antinodes_with_hr_test01() {
-        assert_eq!(34, get_unique_antinodes_with_hr("input/day08_test01.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_unique_antinodes_with_hr_test05() {
-        assert_eq!(9, get_unique_antinodes_with_hr("input/day08_test05.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_unique_antinodes_with_hr_test06() {
-        assert_eq!(9, get_unique_antinodes_with_hr("input/day08_test06.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(unique_antinodes(input))
     }
 
-    #[test]
-    fn test_get_unique_antinodes_with_hr() {
-        assert_eq!(898, get_unique_antinodes_with_hr("input/day08.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(unique_antinodes_with_hr(input))
     }
 }
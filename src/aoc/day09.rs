@@ -1,99 +1,193 @@
 // https://adventofcode.com/2024/day/9
 
-use std::collections::HashSet;
-
-use super::utils::get_lines;
+//! Day 9: Disk Fragmenter. Compacting the files on an amphipod's disk.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+use itertools::Itertools;
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::parse_input_file;
+
+/// The disk map, expanded both ways the two parts need it
+pub struct Input {
+    /// One entry per block, for moving blocks one at a time
+    pub part_one: InputPartOne,
+    /// One entry per file or span of free space, for moving whole files
+    pub part_two: InputPartTwo,
+}
 
-struct InputPartOne {
-    disk: Vec<DiskEntry>,
+/// The disk as individual blocks
+pub struct InputPartOne {
+    pub disk: Vec<DiskEntry>,
 }
 
-struct InputPartTwo {
-    disk: Vec<DiskEntryWithLen>,
+/// The disk as whole files and spans of free space
+pub struct InputPartTwo {
+    pub disk: Vec<DiskEntryWithLen>,
 }
 
+/// Whether part of the disk holds a file or is free
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum DiskEntryType {
+pub enum DiskEntryType {
     File,
     FreeSpace,
 }
 
+/// A single block of the disk
 #[derive(Debug, Clone)]
-struct DiskEntry {
-    id: Option<usize>,
-    entry: DiskEntryType,
+pub struct DiskEntry {
+    /// The ID of the file this block belongs to, if it isn't free
+    pub id: Option<usize>,
+    pub entry: DiskEntryType,
 }
 
+/// A whole file, or a span of free space
 #[derive(Debug, Clone, Copy)]
-struct DiskEntryWithLen {
-    id: Option<usize>,
-    entry: DiskEntryType,
-    len: usize,
+pub struct DiskEntryWithLen {
+    /// The ID of the file, if this isn't free space
+    pub id: Option<usize>,
+    pub entry: DiskEntryType,
+    /// How many blocks it takes up
+    pub len: usize,
+}
+
+/// Drawn the way the puzzle shows the disk, e.g. `00...111`
+impl Display for DiskEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "{}", id),
+            None => write!(f, "."),
+        }
+    }
+}
+
+impl Display for DiskEntryWithLen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "{}", id.to_string().repeat(self.len)),
+            None => write!(f, "{}", ".".repeat(self.len)),
+        }
+    }
+}
+
+/// Reads the one line disk map, expanding it both ways, see [`parse_input_part_one`] and
+/// [`parse_input_part_two`]
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        part_one: parse_input_part_one(input)?,
+        part_two: parse_input_part_two(input)?,
+    })
+}
+
+/// A disk map of `size` files, each followed by up to 9 blocks of free space
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut disk_map = String::new();
+    for file in 0..size {
+        disk_map.push_str(&rng.between(1, 9).to_string());
+        if file + 1 < size {
+            disk_map.push_str(&rng.between(0, 9).to_string());
+        }
+    }
+    disk_map.push('\n');
+    disk_map
+}
+
+/// Every character of the disk map which isn't a digit, and anything after it
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    parse_disk_map(input).err().unwrap_or_default()
 }
 
-fn parse_input_part_one(input_file: &str) -> InputPartOne {
-    let lines = get_lines(input_file);
-
-    if let Some(line) = lines.first() {
-        let chars: Vec<char> = line.chars().collect();
-
-        let mut id = 0usize;
-        let disk: Vec<DiskEntry> =
-            chars
-                .into_iter()
-                .enumerate()
-                .fold(vec![], |mut acc: Vec<DiskEntry>, (i, c)| {
-                    if i % 2 != 0 {
-                        id += 1;
-                    }
-                    for _ in 0..c.to_digit(10).unwrap_or(0) {
-                        acc.push(DiskEntry {
-                            id: if i % 2 == 0 { Some(id) } else { None },
-                            entry: if i % 2 == 0 {
-                                DiskEntryType::File
-                            } else {
-                                DiskEntryType::FreeSpace
-                            },
-                        });
-                    }
-                    acc
-                });
-        InputPartOne { disk }
+fn parse_disk_map(input: &str) -> Result<Vec<usize>, Vec<ParseError>> {
+    let mut lines = input.lines();
+    let line = lines
+        .next()
+        .ok_or_else(|| vec![ParseError::new(0, 0, "a disk map", "")])?;
+
+    let mut disk_map = vec![];
+    let mut errors = vec![];
+    for (col_index, c) in line.char_indices() {
+        match c.to_digit(10) {
+            Some(len) => disk_map.push(len as usize),
+            None => errors.push(ParseError::new(0, col_index, "a digit", c.to_string())),
+        }
+    }
+    for (line_index, line) in lines.enumerate() {
+        if !line.is_empty() {
+            errors.push(ParseError::new(
+                line_index + 1,
+                0,
+                "nothing after the disk map",
+                line,
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(disk_map)
     } else {
-        panic!("Invalid input file: {}", input_file);
+        Err(errors)
     }
 }
 
-fn parse_input_part_two(input_file: &str) -> InputPartTwo {
-    let lines = get_lines(input_file);
-
-    if let Some(line) = lines.first() {
-        let chars: Vec<char> = line.chars().collect();
-
-        let mut id = 0usize;
-        let disk: Vec<DiskEntryWithLen> =
-            chars
-                .into_iter()
-                .enumerate()
-                .fold(vec![], |mut acc: Vec<DiskEntryWithLen>, (i, c)| {
-                    if i % 2 != 0 {
-                        id += 1;
-                    }
-                    acc.push(DiskEntryWithLen {
+/// Expands the disk map into individual blocks
+pub fn parse_input_part_one(input: &str) -> Result<InputPartOne, ParseError> {
+    let disk_map = parse_disk_map(input).map_err(|mut errors| errors.swap_remove(0))?;
+
+    let mut id = 0usize;
+    let disk: Vec<DiskEntry> =
+        disk_map
+            .into_iter()
+            .enumerate()
+            .fold(vec![], |mut acc: Vec<DiskEntry>, (i, len)| {
+                if i % 2 != 0 {
+                    id += 1;
+                }
+                for _ in 0..len {
+                    acc.push(DiskEntry {
                         id: if i % 2 == 0 { Some(id) } else { None },
                         entry: if i % 2 == 0 {
                             DiskEntryType::File
                         } else {
                             DiskEntryType::FreeSpace
                         },
-                        len: c.to_digit(10).unwrap() as usize,
                     });
-                    acc
-                });
-        InputPartTwo { disk }
-    } else {
-        panic!("Invalid input file: {}", input_file);
-    }
+                }
+                acc
+            });
+    Ok(InputPartOne { disk })
+}
+
+/// Expands the disk map into whole files and spans of free space
+pub fn parse_input_part_two(input: &str) -> Result<InputPartTwo, ParseError> {
+    let disk_map = parse_disk_map(input).map_err(|mut errors| errors.swap_remove(0))?;
+
+    let mut id = 0usize;
+    let disk: Vec<DiskEntryWithLen> = disk_map.into_iter().enumerate().fold(
+        vec![],
+        |mut acc: Vec<DiskEntryWithLen>, (i, len)| {
+            if i % 2 != 0 {
+                id += 1;
+            }
+            acc.push(DiskEntryWithLen {
+                id: if i % 2 == 0 { Some(id) } else { None },
+                entry: if i % 2 == 0 {
+                    DiskEntryType::File
+                } else {
+                    DiskEntryType::FreeSpace
+                },
+                len,
+            });
+            acc
+        },
+    );
+    Ok(InputPartTwo { disk })
 }
 
 fn find_first_free_space_block(blocks: &[DiskEntry]) -> Option<usize> {
@@ -130,12 +224,21 @@ fn calc_checksum(blocks: &[DiskEntry]) -> usize {
         .sum()
 }
 
-fn get_checksum(input_file: &str) -> usize {
-    let input = parse_input_part_one(input_file);
+pub fn get_checksum(input_file: &str) -> Result<usize, Error> {
+    checksum(
+        &parse_input_file(input_file, parse_input_part_one)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
+/// Part one: the filesystem checksum after moving file blocks one at a time into the
+/// leftmost free block
+pub fn checksum(input: &InputPartOne, cancel: &Cancel) -> Result<usize, TimedOut> {
     let mut blocks = input.disk.clone();
 
     loop {
+        cancel.check()?;
         if has_file_block_gaps(&blocks) {
             if let Some(first_free_space_block_pos) = find_first_free_space_block(&blocks)
                 && let Some(last_file_block_pos) = find_last_file_block(&blocks)
@@ -151,28 +254,9 @@ fn get_checksum(input_file: &str) -> usize {
         }
     }
 
-    //println!("{:?}", blocks);
+    trace_state!(Debug, "blocks", blocks.iter().format(""));
 
-    calc_checksum(&blocks)
-}
-
-fn print_blocks(blocks: &Vec<DiskEntryWithLen>) {
-    for block in blocks {
-        match block {
-            DiskEntryWithLen {
-                id: Some(id),
-                entry: DiskEntryType::File,
-                len,
-            } => print!("{}", id.to_string().repeat(*len)),
-            DiskEntryWithLen {
-                id: None,
-                entry: DiskEntryType::FreeSpace,
-                len,
-            } => print!("{}", ".".repeat(*len)),
-            _ => (),
-        }
-    }
-    println!()
+    Ok(calc_checksum(&blocks))
 }
 
 fn find_first_whole_free_space(
@@ -210,9 +294,17 @@ fn calc_checksum_whole_files(blocks: &[DiskEntryWithLen]) -> usize {
     checksum
 }
 
-fn get_checksum_whole_files(input_file: &str) -> usize {
-    let input = parse_input_part_two(input_file);
+pub fn get_checksum_whole_files(input_file: &str) -> Result<usize, Error> {
+    checksum_whole_files(
+        &parse_input_file(input_file, parse_input_part_two)?,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
+/// Part two: the filesystem checksum after moving whole files into the leftmost span of
+/// free space they fit in
+pub fn checksum_whole_files(input: &InputPartTwo, cancel: &Cancel) -> Result<usize, TimedOut> {
     let mut blocks = input.disk.clone();
 
     let mut ignored_file_ids: HashSet<usize> = HashSet::new();
@@ -220,11 +312,13 @@ fn get_checksum_whole_files(input_file: &str) -> usize {
     while let Some((last_whole_file_pos, &last_whole_file)) =
         find_last_whole_file(&blocks, &ignored_file_ids)
     {
+        cancel.check()?;
         if let Some((first_free_space_pos, &first_free_space)) =
             find_first_whole_free_space(&blocks, last_whole_file.len, last_whole_file_pos)
         {
             if first_free_space.len >= last_whole_file.len {
-                /*println!(
+                trace_decision!(
+                    Debug,
                     "Found free space block pos at {} with len {}, moving file block id {} at {} with len {}",
                     first_free_space_pos,
                     first_free_space.len,
@@ -232,16 +326,10 @@ fn get_checksum_whole_files(input_file: &str) -> usize {
                     last_whole_file_pos,
                     last_whole_file.len
                 );
-                println!("BEFORE");
-                print_blocks(&blocks);
-
-                println!("DURING");
-                */
+                trace_state!(Trace, "before", blocks.iter().format(""));
 
                 blocks.remove(last_whole_file_pos);
-                //print_blocks(&blocks);
                 blocks.remove(first_free_space_pos);
-                //print_blocks(&blocks);
                 blocks.insert(
                     last_whole_file_pos - 1,
                     DiskEntryWithLen {
@@ -250,9 +338,7 @@ fn get_checksum_whole_files(input_file: &str) -> usize {
                         len: last_whole_file.len,
                     },
                 );
-                //print_blocks(&blocks);
                 blocks.insert(first_free_space_pos, last_whole_file);
-                //print_blocks(&blocks);
                 if first_free_space.len - last_whole_file.len > 0 {
                     blocks.insert(
                         first_free_space_pos + 1,
@@ -262,72 +348,69 @@ fn get_checksum_whole_files(input_file: &str) -> usize {
                             len: first_free_space.len - last_whole_file.len,
                         },
                     );
-                    //print_blocks(&blocks);
                 }
-                //println!("AFTER");
-                //print_blocks(&blocks);
+                trace_state!(Trace, "after", blocks.iter().format(""));
             }
         } else {
-            /*println!(
+            trace_decision!(
+                Debug,
                 "No more free space blocks to move file block id {} at {} with len {}",
                 last_whole_file.id.unwrap(),
                 last_whole_file_pos,
                 last_whole_file.len
-            );*/
+            );
             ignored_file_ids.insert(last_whole_file.id.unwrap());
             if last_whole_file.id.unwrap() == 1 {
-                //println!("No more files to move");
+                trace_decision!(Debug, "No more files to move");
                 break;
             }
             continue;
         }
     }
 
-    calc_checksum_whole_files(&blocks)
+    Ok(calc_checksum_whole_files(&blocks))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The [`Solution`] for day 9: [`checksum`] and [`checksum_whole_files`]
+pub struct Day09;
 
-    #[test]
-    fn test_get_checksum_test01() {
-        assert_eq!(1928, get_checksum("input/day09_test01.txt"));
-    }
+impl Solution for Day09 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_checksum_test02() {
-        assert_eq!(60, get_checksum("input/day09_test02.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_checksum_test03() {
-        assert_eq!(513, get_checksum("input/day09_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_checksum_test04() {
-        assert_eq!(57, get_checksum("input/day09_test04.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    // This test takes a while so ignore in CI
-
-    #[ignore]
-    #[test]
-    fn test_get_checksum() {
-        assert_eq!(6435922584968, get_checksum("input/day09.txt"));
+    fn part1(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        checksum(&input.part_one, cancel)
     }
 
-    #[test]
-    fn test_get_checksum_whole_files_test01() {
-        assert_eq!(2858, get_checksum_whole_files("input/day09_test01.txt"));
+    fn part2(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        checksum_whole_files(&input.part_two, cancel)
     }
+}
 
-    // This test takes a while so ignore in CI
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[ignore]
     #[test]
-    fn test_get_checksum_whole_files() {
-        assert_eq!(6469636832766, get_checksum_whole_files("input/day09.txt"));
+    fn test_validate_input() {
+        assert_eq!(
+            vec![
+                ParseError::new(0, 2, "a digit", "x"),
+                ParseError::new(0, 4, "a digit", " "),
+                ParseError::new(2, 0, "nothing after the disk map", "45"),
+            ],
+            validate_input("12x3 4\n\n45\n")
+        );
+        assert_eq!(
+            Vec::<ParseError>::new(),
+            validate_input("2333133121414131402\n")
+        );
     }
 }
@@ -1,317 +1,239 @@
 // https://adventofcode.com/2024/day/10
 
-use std::collections::HashSet;
+//! Day 10: Hoof It. Scoring the hiking trails on a topographic map.
 
-use super::utils::get_lines;
-use super::utils::ArenaTree;
+use std::{collections::HashMap, fmt::Display};
 
-struct Input {
-    top_map: Vec<Vec<u32>>,
-}
+use itertools::Itertools;
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::Solution;
+use super::utils::dot::DotOptions;
+use super::utils::{ArenaTree, Compass, Grid, parse_input_file};
 
-    let mut top_map: Vec<Vec<u32>> = Vec::new();
+/// The topographic map
+pub struct Input {
+    /// The height of each position, from 0 to 9
+    pub top_map: Grid<u32>,
+}
 
-    for line in lines {
-        let report: Vec<u32> = line
-            .chars()
-            .map(|level| level.to_string().parse::<u32>().unwrap())
-            .collect();
-        top_map.push(report);
-    }
+/// Reads the height of each position on the topographic map
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        top_map: Grid::parse(input, "a height", |level| level.to_digit(10))?,
+    })
+}
 
-    Input { top_map }
+/// Every position on the map which isn't a height from 0 to 9, and every row which isn't
+/// as long as the first
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    Grid::parse_all(input, "a height", |level| level.to_digit(10))
+        .err()
+        .unwrap_or_default()
 }
 
-fn print_top_map(top_map: &Vec<Vec<u32>>) {
-    for row in top_map {
-        for level in row {
-            print!("{:?}", level);
+/// A `size` by `size` topographic map of random heights, with trails carved into it by
+/// climbing from 0 to 9 along random walks. Walks don't cross, so every one which reaches 9
+/// is a trail.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut heights = vec![vec![None; size]; size];
+    for _ in 0..size {
+        let mut pos = (rng.below(size), rng.below(size));
+        if heights[pos.0][pos.1].is_some() {
+            continue;
+        }
+        heights[pos.0][pos.1] = Some(0);
+        for height in 1..=9 {
+            let (row, col) = pos;
+            let free: Vec<(usize, usize)> = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ]
+            .into_iter()
+            .filter(|&(row, col)| row < size && col < size && heights[row][col].is_none())
+            .collect();
+            if free.is_empty() {
+                break;
+            }
+            pos = rng.pick(&free);
+            heights[pos.0][pos.1] = Some(height);
         }
-        println!();
     }
+    rng.grid(size, size, |rng, (row, col)| {
+        let height = heights[row][col].unwrap_or_else(|| rng.below(10) as u32);
+        char::from_digit(height, 10).unwrap()
+    })
 }
 
-fn print_tree(tree: &ArenaTree<u32>) {
-    for node in tree.arena.iter() {
-        println!("Node: {:?}", node);
+/// A position on the map, and its height
+type TrailMap = ArenaTree<(usize, usize), u32>;
+
+/// Adds every trail climbing from `curr_pos` to `tree`, as a graph keyed by position in
+/// which trails that meet share nodes
+fn build_tree(tree: &mut TrailMap, top_map: &Grid<u32>, curr_pos: (usize, usize)) -> usize {
+    let curr_level = top_map[curr_pos];
+    let (curr_node, _) = tree.add_node(curr_pos, curr_level);
+    if curr_level == 9 {
+        trace_decision!(Debug, "Found hiking trail at {:?}", curr_pos);
+    }
+
+    for dir in [Compass::North, Compass::South, Compass::East, Compass::West] {
+        if let Some(next_pos) = top_map.step(curr_pos, dir)
+            && top_map[next_pos] == curr_level + 1
+        {
+            let next_level = top_map[next_pos];
+            trace_decision!(
+                Trace,
+                "At {:?}, level: {:?}, moving {:?}, level: {:?}",
+                curr_pos,
+                curr_level,
+                dir,
+                next_level
+            );
+            // Trails already followed from here don't need following again
+            let next_node = match tree.find(&next_pos) {
+                Some(next_node) => next_node,
+                None => build_tree(tree, top_map, next_pos),
+            };
+            tree.add_edge(curr_node, next_node);
+        }
     }
+    curr_node
 }
 
-fn build_tree(
-    tree: &mut ArenaTree<u32>,
-    top_map: &Vec<Vec<u32>>,
-    curr_level: u32,
-    curr_pos: (usize, usize),
-    visited_hiking_trails: &mut HashSet<(usize, usize)>,
-) {
-    if curr_level == 9 && !visited_hiking_trails.contains(&curr_pos) {
-        //println!("Found hiking trail at {:?}, level: {:?}, visited", curr_pos, curr_level);
-        visited_hiking_trails.insert(curr_pos);
-    }
-
-    let (curr_row, curr_col) = curr_pos;
-    let n_dir = if curr_row as i32 - 1 < 0 {
-        None
-    } else {
-        Some((curr_row - 1, curr_col))
-    };
-    let s_dir = if curr_row as i32 + 1 >= top_map.len() as i32 {
-        None
-    } else {
-        Some((curr_row + 1, curr_col))
-    };
-    let e_dir = if curr_col as i32 + 1 >= top_map[0].len() as i32 {
-        None
-    } else {
-        Some((curr_row, curr_col + 1))
-    };
-    let w_dir = if curr_col as i32 - 1 < 0 {
-        None
-    } else {
-        Some((curr_row, curr_col - 1))
-    };
-
-    let curr_node = tree.add_node(curr_level);
-
-    if let Some((n_row, n_col)) = n_dir
-        && let Some(n_level) = top_map.get(n_row).and_then(|row| row.get(n_col))
-        && *n_level == curr_level + 1
-    {
-        /*println!(
-            "At {:?}, level: {:?}, moving N, level: {:?}",
-            curr_pos, curr_level, n_level
-        );*/
-        let n_node: usize = tree.add_node(*n_level);
-        tree.arena[curr_node].children.push(n_node);
-        tree.arena[n_node].parent = Some(curr_node);
-        build_tree(
-            tree,
-            top_map,
-            *n_level,
-            (n_row, n_col),
-            visited_hiking_trails,
-        );
-    }
-
-    if let Some((s_row, s_col)) = s_dir
-        && let Some(s_level) = top_map.get(s_row).and_then(|row| row.get(s_col))
-        && *s_level == curr_level + 1
-    {
-        /*println!(
-            "At {:?}, level: {:?}, moving S, level: {:?}",
-            curr_pos, curr_level, s_level
-        );*/
-        let s_node = tree.add_node(*s_level);
-        tree.arena[curr_node].children.push(s_node);
-        tree.arena[s_node].parent = Some(curr_node);
-        build_tree(
-            tree,
-            top_map,
-            *s_level,
-            (s_row, s_col),
-            visited_hiking_trails,
-        );
-    }
-
-    if let Some((e_row, e_col)) = e_dir
-        && let Some(e_level) = top_map.get(e_row).and_then(|row| row.get(e_col))
-        && *e_level == curr_level + 1
-    {
-        /*println!(
-            "At {:?}, level: {:?}, moving E, level: {:?}",
-            curr_pos, curr_level, e_level
-        );*/
-        let e_node = tree.add_node(*e_level);
-        tree.arena[curr_node].children.push(e_node);
-        tree.arena[e_node].parent = Some(curr_node);
-        build_tree(
-            tree,
-            top_map,
-            *e_level,
-            (e_row, e_col),
-            visited_hiking_trails,
-        );
+/// The number of distinct trails from `node` up to a 9
+fn count_trails(tree: &TrailMap, node: usize, counts: &mut HashMap<usize, usize>) -> usize {
+    if tree[node].val == 9 {
+        return 1;
     }
-
-    if let Some((w_row, w_col)) = w_dir
-        && let Some(w_level) = top_map.get(w_row).and_then(|row| row.get(w_col))
-        && *w_level == curr_level + 1
-    {
-        /*println!(
-            "At {:?}, level: {:?}, moving W, level: {:?}",
-            curr_pos, curr_level, w_level
-        );*/
-        let w_node = tree.add_node(*w_level);
-        tree.arena[curr_node].children.push(w_node);
-        tree.arena[w_node].parent = Some(curr_node);
-        build_tree(
-            tree,
-            top_map,
-            *w_level,
-            (w_row, w_col),
-            visited_hiking_trails,
-        );
+    if let Some(&count) = counts.get(&node) {
+        return count;
     }
+    let count = tree
+        .children(node)
+        .iter()
+        .map(|&child| count_trails(tree, child, counts))
+        .sum();
+    counts.insert(node, count);
+    count
 }
 
-fn get_sum_trailheads(input_file: &str) -> usize {
-    let input = parse_input(input_file);
-
-    let trailheads: Vec<(usize, usize)> = input
-        .top_map
+/// Each path from `root` to a dead end or a 9, a line of positions each
+fn print_trails(tree: &TrailMap, root: usize) -> String {
+    tree.paths(root)
         .iter()
-        .enumerate()
-        .flat_map(|(row_index, row)| {
-            row.iter()
-                .enumerate()
-                .filter_map(move |(col_index, level)| {
-                    if *level == 0 {
-                        Some((row_index, col_index))
-                    } else {
-                        None
-                    }
-                })
+        .map(|path| {
+            path.iter()
+                .map(|&idx| format!("{:?}", tree[idx].key()))
+                .join(" ")
         })
-        .collect::<Vec<(usize, usize)>>();
+        .join("\n")
+}
 
-    let mut sum_trailheads = 0usize;
+pub fn get_sum_trailheads(input_file: &str) -> Result<usize, Error> {
+    Ok(sum_trailheads(&parse_input_file(input_file, parse_input)?))
+}
 
-    for (start_row, start_col) in trailheads {
-        let mut visited_hiking_trails: HashSet<(usize, usize)> = HashSet::new();
+/// Part one: the sum of each trailhead's score, the number of 9s it can reach
+pub fn sum_trailheads(input: &Input) -> usize {
+    let mut sum_trailheads = 0usize;
 
-        let mut tree: ArenaTree<u32> = ArenaTree::default();
-        build_tree(
-            &mut tree,
-            &input.top_map,
-            0,
-            (start_row, start_col),
-            &mut visited_hiking_trails,
-        );
+    for trailhead in input.top_map.positions_of(&0) {
+        let mut tree = TrailMap::default();
+        let root = build_tree(&mut tree, &input.top_map, trailhead);
 
-        sum_trailheads += visited_hiking_trails.len();
+        // Every step climbs by one, so the 9s are exactly the leaves nine steps down
+        sum_trailheads += tree.leaves_at_depth(9);
 
-        print_tree(&tree);
+        trace_state!(Trace, "trails", print_trails(&tree, root));
     }
 
     sum_trailheads
 }
 
-fn get_sum_trailhead_ratings(input_file: &str) -> usize {
-    let input = parse_input(input_file);
-
-    let trailheads: Vec<(usize, usize)> = input
-        .top_map
-        .iter()
-        .enumerate()
-        .flat_map(|(row_index, row)| {
-            row.iter()
-                .enumerate()
-                .filter_map(move |(col_index, level)| {
-                    if *level == 0 {
-                        Some((row_index, col_index))
-                    } else {
-                        None
-                    }
-                })
-        })
-        .collect::<Vec<(usize, usize)>>();
+pub fn get_sum_trailhead_ratings(input_file: &str) -> Result<usize, Error> {
+    Ok(sum_trailhead_ratings(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
+}
 
+/// Part two: the sum of each trailhead's rating, the number of distinct trails from it
+pub fn sum_trailhead_ratings(input: &Input) -> usize {
     let mut sum_trailhead_ratings = 0usize;
 
-    for (start_row, start_col) in trailheads {
-        let mut visited_hiking_trails: HashSet<(usize, usize)> = HashSet::new();
+    for trailhead in input.top_map.positions_of(&0) {
+        let mut tree = TrailMap::default();
+        let root = build_tree(&mut tree, &input.top_map, trailhead);
 
-        let mut tree: ArenaTree<u32> = ArenaTree::default();
-        build_tree(
-            &mut tree,
-            &input.top_map,
-            0,
-            (start_row, start_col),
-            &mut visited_hiking_trails,
-        );
-
-        sum_trailhead_ratings += tree.arena[tree.depth(8)].children.len();
+        sum_trailhead_ratings += count_trails(&tree, root, &mut HashMap::new());
 
-        //print_tree(&tree);
+        trace_state!(Trace, "trails", print_trails(&tree, root));
     }
 
     sum_trailhead_ratings
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_sum_trailheads_test01() {
-        assert_eq!(1, get_sum_trailheads("input/day10_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_trailheads_test02() {
-        assert_eq!(2, get_sum_trailheads("input/day10_test02.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_trailheads_test03() {
-        assert_eq!(4, get_sum_trailheads("input/day10_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_trailheads_test04() {
-        assert_eq!(3, get_sum_trailheads("input/day10_test04.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_trailheads_test05() {
-        assert_eq!(36, get_sum_trailheads("input/day10_test05.txt"));
-    }
+/// The map as a DOT digraph, with an edge for every step up a trail
+pub fn top_map_to_dot(input: &Input, options: &DotOptions<(usize, usize)>) -> String {
+    let top_map = &input.top_map;
+    top_map.to_dot(|from, to| top_map[to] == top_map[from] + 1, options)
+}
 
-    #[test]
-    fn test_get_sum_trailheads() {
-        assert_eq!(782, get_sum_trailheads("input/day10.txt"));
-    }
+/// The [`Solution`] for day 10: [`sum_trailheads`] and [`sum_trailhead_ratings`]
+pub struct Day10;
 
-    #[test]
-    fn test_get_sum_trailhead_ratings_test01() {
-        assert_eq!(16, get_sum_trailhead_ratings("input/day10_test01.txt"));
-    }
+impl Solution for Day10 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_sum_trailheads_ratings_test02() {
-        assert_eq!(2, get_sum_trailhead_ratings("input/day10_test02.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_sum_trailheads_ratings_test03() {
-        assert_eq!(13, get_sum_trailhead_ratings("input/day10_test03.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_sum_trailheads_ratings_test04() {
-        assert_eq!(3, get_sum_trailhead_ratings("input/day10_test04.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_trailheads(input))
     }
 
-    #[test]
-    fn test_get_sum_trailhead_ratings_test05() {
-        assert_eq!(81, get_sum_trailhead_ratings("input/day10_test05.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_trailhead_ratings(input))
     }
+}
 
-    #[test]
-    fn test_get_sum_trailhead_ratings_test06() {
-        assert_eq!(3, get_sum_trailhead_ratings("input/day10_test06.txt"));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
-    fn test_get_sum_trailhead_ratings_test07() {
-        assert_eq!(227, get_sum_trailhead_ratings("input/day10_test07.txt"));
+    fn test_generate_input() {
+        for seed in 0..10 {
+            let input = parse_input(&generate_input(&mut Rng::new(seed), 12)).unwrap();
+            assert!(sum_trailheads(&input) > 0, "seed {}", seed);
+            assert!(sum_trailhead_ratings(&input) > 0, "seed {}", seed);
+        }
     }
 
     #[test]
-    fn test_get_sum_trailhead_ratings() {
-        assert_eq!(1694, get_sum_trailhead_ratings("input/day10.txt"));
+    fn test_top_map_to_dot() {
+        let input = parse_input("01\n21\n").unwrap();
+        assert_eq!(
+            "digraph \"G\" {
+    n0 [label=\"0 (0,0)\", color=red, penwidth=2];
+    n1 [label=\"1 (0,1)\", color=red, penwidth=2];
+    n2 [label=\"2 (1,0)\"];
+    n3 [label=\"1 (1,1)\"];
+    n0 -> n1 [color=red, penwidth=2];
+    n3 -> n2;
+}
+",
+            top_map_to_dot(&input, &DotOptions::new().highlight(vec![(0, 0), (0, 1)]))
+        );
     }
 }
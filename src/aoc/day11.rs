@@ -1,32 +1,49 @@
 // https://adventofcode.com/2024/day/11
 
-use std::collections::HashMap;
+//! Day 11: Plutonian Pebbles. Counting stones which change every time you blink.
 
-use super::utils::get_lines;
+use std::fmt::Display;
+
+use super::cancel::{Cancel, TimedOut};
+use super::differential::Implementation;
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::{Part, Solution};
+use super::utils::memo::Memo;
+use super::utils::{parse_field, parse_input_file, validate_lines};
 
 use itertools::Itertools;
+use serde::Deserialize;
 
-struct Input {
-    stones: Vec<usize>,
+/// The stones, in the order they're lined up
+pub struct Input {
+    /// The number engraved on each stone
+    pub stones: Vec<usize>,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
+/// Reads the numbers engraved on the stones, which are separated by spaces
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut stones: Vec<usize> = Vec::new();
 
-    for line in lines {
+    for (line_index, line) in input.lines().enumerate() {
         let line_parts: Vec<&str> = line.split_whitespace().collect();
         for line_part in line_parts {
-            stones.push(line_part.parse::<usize>().unwrap());
+            stones.push(parse_field(line_index, line, line_part, "a stone number")?);
         }
     }
 
-    Input { stones }
+    Ok(Input { stones })
 }
 
-fn print_stones(stones: &[usize]) {
-    println!("{}", stones.iter().format(" "));
+/// Every line with a stone whose engraving isn't a number
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_lines(input, parse_input)
+}
+
+/// A line of `size` stones
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let stones: Vec<i64> = (0..size).map(|_| rng.between(0, 1_000_000)).collect();
+    format!("{}\n", stones.iter().join(" "))
 }
 
 fn get_new_stones(stone: usize) -> Vec<usize> {
@@ -36,7 +53,7 @@ fn get_new_stones(stone: usize) -> Vec<usize> {
             // Replace with stone engraved with the number 1
             new_stones.push(1);
         }
-        _ if stone.to_string().len() % 2 == 0 => {
+        _ if stone.to_string().len().is_multiple_of(2) => {
             // Replace with two stones (split each number string in half)
             let stone_str = stone.to_string();
             let (first_half, second_half) = stone_str.split_at(stone_str.len() / 2);
@@ -51,12 +68,21 @@ fn get_new_stones(stone: usize) -> Vec<usize> {
     new_stones
 }
 
-fn get_num_stones(input_file: &str, blinks: usize) -> usize {
-    let input = parse_input(input_file);
+pub fn get_num_stones(input_file: &str, blinks: usize) -> Result<usize, Error> {
+    Ok(num_stones(
+        &parse_input_file(input_file, parse_input)?,
+        blinks,
+    ))
+}
 
-    println!("Initial arrangement:");
-    print_stones(&input.stones);
-    println!();
+/// How many stones there are after blinking `blinks` times, found by changing every stone
+/// in turn. Only practical for a few dozen blinks, see [`num_stones_memoize`].
+pub fn num_stones(input: &Input, blinks: usize) -> usize {
+    trace_state!(
+        Debug,
+        "initial arrangement",
+        input.stones.iter().format(" ")
+    );
 
     let mut stones: Vec<usize> = input.stones.clone();
 
@@ -68,167 +94,134 @@ fn get_num_stones(input_file: &str, blinks: usize) -> usize {
         }
         stones = new_stones;
 
-        print_stones(&stones);
+        trace_state!(Debug, "arrangement", stones.iter().format(" "));
     }
 
     stones.len()
 }
 
+/// How many stones `stone` becomes after `blinks` blinks
 fn count_stones(
-    stone: usize,
-    blinks: usize,
-    stones_cache: &mut HashMap<(usize, usize), usize>,
+    memo: &mut Memo<(usize, usize), usize>,
+    &(stone, blinks): &(usize, usize),
 ) -> usize {
-    if stones_cache.contains_key(&(stone, blinks)) {
-        return stones_cache[&(stone, blinks)];
-    }
-
     if blinks == 0 {
         return 1;
     }
 
-    let num_stones: usize = match stone {
-        0 => count_stones(1, blinks - 1, stones_cache),
-        _ if stone.to_string().len() % 2 == 0 => {
+    match stone {
+        0 => memo.call((1, blinks - 1)),
+        _ if stone.to_string().len().is_multiple_of(2) => {
             let stone_str = stone.to_string();
             let (first_half, second_half) = stone_str.split_at(stone_str.len() / 2);
-            count_stones(
-                first_half.parse::<usize>().unwrap(),
-                blinks - 1,
-                stones_cache,
-            ) + count_stones(
-                second_half.parse::<usize>().unwrap(),
-                blinks - 1,
-                stones_cache,
-            )
+            memo.call((first_half.parse::<usize>().unwrap(), blinks - 1))
+                + memo.call((second_half.parse::<usize>().unwrap(), blinks - 1))
         }
-        _ => count_stones(stone * 2024, blinks - 1, stones_cache),
-    };
-
-    stones_cache.insert((stone, blinks), num_stones);
-
-    num_stones
+        _ => memo.call((stone * 2024, blinks - 1)),
+    }
 }
 
-fn get_num_stones_memoize(input_file: &str, blinks: usize) -> usize {
-    let input = parse_input(input_file);
-
-    /*println!("Initial arrangement:");
-    print_stones(&input.stones);
-    println!("");*/
+pub fn get_num_stones_memoize(input_file: &str, blinks: usize) -> Result<usize, Error> {
+    Ok(num_stones_memoize(
+        &parse_input_file(input_file, parse_input)?,
+        blinks,
+    ))
+}
 
-    let stones: Vec<usize> = input.stones.clone();
+/// How many stones there are after blinking `blinks` times, counting each distinct stone once
+pub fn num_stones_memoize(input: &Input, blinks: usize) -> usize {
+    trace_state!(
+        Debug,
+        "initial arrangement",
+        input.stones.iter().format(" ")
+    );
 
-    let mut num_stones = 0;
+    let mut memo = Memo::new(count_stones);
 
-    let mut stones_cache: HashMap<(usize, usize), usize> = HashMap::new();
+    let num_stones = input
+        .stones
+        .iter()
+        .map(|&stone| memo.call((stone, blinks)))
+        .sum();
 
-    for stone in stones {
-        num_stones += count_stones(stone, blinks, &mut stones_cache)
-    }
+    let stats = memo.stats();
+    trace_counter!(Debug, "cache hits", stats.hits);
+    trace_counter!(Debug, "cache misses", stats.misses);
 
     num_stones
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_num_stones_test01() {
-        assert_eq!(7, get_num_stones("input/day11_test01.txt", 1));
-    }
-
-    #[test]
-    fn test_get_num_stones_test02() {
-        assert_eq!(3, get_num_stones("input/day11_test02.txt", 1));
-    }
-
-    #[test]
-    fn test_get_num_stones_test03() {
-        assert_eq!(4, get_num_stones("input/day11_test02.txt", 2));
-    }
-
-    #[test]
-    fn test_get_num_stones_test04() {
-        assert_eq!(5, get_num_stones("input/day11_test02.txt", 3));
-    }
-
-    #[test]
-    fn test_get_num_stones_test05() {
-        assert_eq!(9, get_num_stones("input/day11_test02.txt", 4));
-    }
-
-    #[test]
-    fn test_get_num_stones_test06() {
-        assert_eq!(13, get_num_stones("input/day11_test02.txt", 5));
-    }
-
-    #[test]
-    fn test_get_num_stones_test07() {
-        assert_eq!(22, get_num_stones("input/day11_test02.txt", 6));
-    }
-
-    #[test]
-    fn test_get_num_stones_test08() {
-        assert_eq!(55312, get_num_stones("input/day11_test02.txt", 25));
-    }
-
-    #[test]
-    fn test_get_num_stones() {
-        assert_eq!(187738, get_num_stones("input/day11.txt", 25));
-    }
-
-    #[test]
-    fn test_get_num_stones_memoize_test01() {
-        assert_eq!(7, get_num_stones_memoize("input/day11_test01.txt", 1));
+/// Part one both ways, for [`differential`](super::differential) testing. Part two's blinks
+/// are too many to change every stone in turn.
+pub fn implementations(part: Part) -> Vec<Implementation> {
+    let blinks = Day11::default().part1_blinks;
+    match part {
+        Part::One => vec![
+            Implementation::new("every stone", parse_input, move |input| {
+                num_stones(input, blinks)
+            }),
+            Implementation::new("memoize", parse_input, move |input| {
+                num_stones_memoize(input, blinks)
+            }),
+            Implementation::new("memoize on a stack", parse_input, move |input| {
+                let mut memo = Memo::new(count_stones);
+                input
+                    .stones
+                    .iter()
+                    .map(|&stone| memo.call_iterative((stone, blinks)))
+                    .sum::<usize>()
+            }),
+        ],
+        Part::Two => vec![],
     }
+}
 
-    #[test]
-    fn test_get_num_stones_memoize_test02() {
-        assert_eq!(3, get_num_stones_memoize("input/day11_test02.txt", 1));
-    }
+/// The [`Solution`] for day 11: [`num_stones_memoize`] after each part's number of blinks
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Day11 {
+    /// Blinks for part one, 25 in the puzzle
+    pub part1_blinks: usize,
+    /// Blinks for part two, 75 in the puzzle
+    pub part2_blinks: usize,
+}
 
-    #[test]
-    fn test_get_num_stones_memoize_test03() {
-        assert_eq!(4, get_num_stones_memoize("input/day11_test02.txt", 2));
+impl Default for Day11 {
+    fn default() -> Self {
+        Day11 {
+            part1_blinks: 25,
+            part2_blinks: 75,
+        }
     }
+}
 
-    #[test]
-    fn test_get_num_stones_memoize_test04() {
-        assert_eq!(5, get_num_stones_memoize("input/day11_test02.txt", 3));
-    }
+impl Solution for Day11 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_num_stones_memoize_test05() {
-        assert_eq!(9, get_num_stones_memoize("input/day11_test02.txt", 4));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_num_stones_memoize_test06() {
-        assert_eq!(13, get_num_stones_memoize("input/day11_test02.txt", 5));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_num_stones_memoize_test07() {
-        assert_eq!(22, get_num_stones_memoize("input/day11_test02.txt", 6));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(num_stones_memoize(input, self.part1_blinks))
     }
 
-    #[test]
-    fn test_get_num_stones_memoize_test08() {
-        assert_eq!(55312, get_num_stones_memoize("input/day11_test02.txt", 25));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(num_stones_memoize(input, self.part2_blinks))
     }
+}
 
-    #[test]
-    fn test_get_num_stones_memoize_25_blinks() {
-        assert_eq!(187738, get_num_stones_memoize("input/day11.txt", 25));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
-    fn test_get_num_stones_memoize_75_blinks() {
-        assert_eq!(
-            223767210249237,
-            get_num_stones_memoize("input/day11.txt", 75)
-        );
+    fn test_num_stones_memoize_inline() {
+        let input = parse_input("125 17").unwrap();
+        assert_eq!(55312, num_stones_memoize(&input, 25));
     }
 }
@@ -1,44 +1,69 @@
 // https://adventofcode.com/2024/day/12
 
-use std::collections::{HashMap, HashSet};
-
-use super::utils::get_lines;
-
-struct Input {
-    plants: Vec<Vec<char>>,
+//! Day 12: Garden Groups. Pricing fences around regions of a garden.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use super::cancel::{Cancel, TimedOut};
+use super::differential::Implementation;
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::solution::{Part, Solution};
+use super::utils::graph::dfs;
+use super::utils::{Compass, Grid, parse_input_file};
+
+/// The map of the garden
+pub struct Input {
+    /// The type of plant growing in each plot
+    pub plants: Grid<char>,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    let mut plants: Vec<Vec<char>> = Vec::new();
-
-    for line in lines {
-        let report: Vec<char> = line.chars().collect();
-        plants.push(report);
-    }
+/// Reads the garden's plots, each a letter for the type of plant growing in it
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        plants: Grid::parse(input, "a plant", Some)?,
+    })
+}
 
-    Input { plants }
+/// Every row of the garden which isn't as long as the first
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    Grid::parse_all(input, "a plant", Some)
+        .err()
+        .unwrap_or_default()
 }
 
-fn print_plants(plants: &Vec<Vec<char>>) {
-    for row in plants {
-        for level in row {
-            print!("{}", level);
+/// A `size` by `size` garden, where each plot usually grows the same as one next to it
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let plants = ['A', 'B', 'C', 'D', 'E'];
+    let mut garden = vec![vec!['A'; size]; size];
+    for row in 0..size {
+        for col in 0..size {
+            garden[row][col] = match rng.below(5) {
+                0 | 1 if row > 0 => garden[row - 1][col],
+                2 | 3 if col > 0 => garden[row][col - 1],
+                _ => rng.pick(&plants),
+            };
         }
-        println!();
     }
+    rng.grid(size, size, |_, (row, col)| garden[row][col])
 }
 
+/// A group of touching plots growing the same plant
 #[derive(Debug)]
-struct Region {
-    area: usize,
-    perimeter: usize,
-    corners: usize,
+pub struct Region {
+    /// How many plots are in the region
+    pub area: usize,
+    /// How many plot edges border a different region or the edge of the map
+    pub perimeter: usize,
+    /// How many corners the region has, which is also how many straight sides it has
+    pub corners: usize,
 }
 
 impl Region {
-    fn new(area: usize, perimeter: usize, corners: usize) -> Region {
+    pub fn new(area: usize, perimeter: usize, corners: usize) -> Region {
         Region {
             area,
             perimeter,
@@ -50,136 +75,96 @@ impl Region {
 fn flood_fill(
     plant: char,
     plant_pos: (usize, usize),
-    plants: &[Vec<char>],
+    plants: &Grid<char>,
     regions: &mut HashMap<char, Vec<Region>>,
     visited_plants: &mut HashSet<(usize, usize)>,
 ) {
-    let mut stack: Vec<(usize, usize)> = Vec::new();
+    // This makes sure we don't double count positions
+    if visited_plants.contains(&plant_pos) {
+        return;
+    }
 
-    // Add current node to the stack
-    stack.push(plant_pos);
+    let same_plant_neighbours = |&pos: &(usize, usize)| -> Vec<(usize, usize)> {
+        plants
+            .neighbours4(pos)
+            .filter(|&next| plants[next] == plant)
+            .collect()
+    };
 
     let mut area = 0;
     let mut perimeter = 0;
     let mut corners = 0;
 
-    while let Some((row, col)) = stack.pop() {
-        // This makes sure we don't double count positions
-        if visited_plants.contains(&(row, col)) {
-            continue;
+    for pos in dfs(&same_plant_neighbours, plant_pos) {
+        area += 1;
+        visited_plants.insert(pos);
+
+        // The neighbour in this direction, if it's part of the same region
+        let same_plant = |dir| plants.step(pos, dir).filter(|&next| plants[next] == plant);
+
+        let n_dir = same_plant(Compass::North);
+        let nw_dir = same_plant(Compass::NorthWest);
+        let w_dir = same_plant(Compass::West);
+        let sw_dir = same_plant(Compass::SouthWest);
+        let s_dir = same_plant(Compass::South);
+        let se_dir = same_plant(Compass::SouthEast);
+        let e_dir = same_plant(Compass::East);
+        let ne_dir = same_plant(Compass::NorthEast);
+
+        // Every side without the same plant next to it needs fencing
+        perimeter += [n_dir, w_dir, s_dir, e_dir]
+            .iter()
+            .filter(|next| next.is_none())
+            .count();
+
+        // We want to check concave and convex corners
+
+        // Convex corners
+        // ...
+        // ###<- This is a convex corner
+        // ###
+
+        if n_dir.is_none() && e_dir.is_none() {
+            corners += 1;
+        }
+        if s_dir.is_none() && e_dir.is_none() {
+            corners += 1;
+        }
+        if s_dir.is_none() && w_dir.is_none() {
+            corners += 1;
+        }
+        if n_dir.is_none() && w_dir.is_none() {
+            corners += 1;
         }
 
-        // Is this 'Inside'?
-        if plants[row][col] == plant {
-            // If so, increment the area, and mark as visited
-            area += 1;
-            visited_plants.insert((row, col));
-
-            let n_dir = if row as i32 - 1 < 0 || plants[row - 1][col] != plant {
-                perimeter += 1;
-                None
-            } else {
-                stack.push((row - 1, col));
-                Some((row - 1, col))
-            };
-            let nw_dir =
-                if row as i32 - 1 < 0 || col as i32 - 1 < 0 || plants[row - 1][col - 1] != plant {
-                    None
-                } else {
-                    Some((row - 1, col - 1))
-                };
-            let w_dir = if col as i32 - 1 < 0 || plants[row][col - 1] != plant {
-                perimeter += 1;
-                None
-            } else {
-                stack.push((row, col - 1));
-                Some((row, col - 1))
-            };
-            let sw_dir = if row as i32 + 1 >= plants.len() as i32
-                || col as i32 - 1 < 0
-                || plants[row + 1][col - 1] != plant
-            {
-                None
-            } else {
-                Some((row + 1, col - 1))
-            };
-            let s_dir = if row as i32 + 1 >= plants.len() as i32 || plants[row + 1][col] != plant {
-                perimeter += 1;
-                None
-            } else {
-                stack.push((row + 1, col));
-                Some((row + 1, col))
-            };
-            let se_dir = if row as i32 + 1 >= plants.len() as i32
-                || col as i32 + 1 >= plants[0].len() as i32
-                || plants[row + 1][col + 1] != plant
-            {
-                None
-            } else {
-                Some((row + 1, col + 1))
-            };
-            let e_dir = if col as i32 + 1 >= plants[0].len() as i32 || plants[row][col + 1] != plant
-            {
-                perimeter += 1;
-                None
-            } else {
-                stack.push((row, col + 1));
-                Some((row, col + 1))
-            };
-            let ne_dir = if row as i32 - 1 < 0
-                || col as i32 + 1 >= plants[0].len() as i32
-                || plants[row - 1][col + 1] != plant
-            {
-                None
-            } else {
-                Some((row - 1, col + 1))
-            };
-
-            // We want to check concave and convex corners
-
-            // Convex corners
-            // ...
-            // ###<- This is a convex corner
-            // ###
-
-            if n_dir.is_none() && e_dir.is_none() {
-                corners += 1;
-            }
-            if s_dir.is_none() && e_dir.is_none() {
-                corners += 1;
-            }
-            if s_dir.is_none() && w_dir.is_none() {
-                corners += 1;
-            }
-            if n_dir.is_none() && w_dir.is_none() {
-                corners += 1;
-            }
-
-            // Concave corners
-            //                           #..
-            //                           ##.
-            // This is a concave corner->###
+        // Concave corners
+        //                           #..
+        //                           ##.
+        // This is a concave corner->###
 
-            if n_dir.is_some() && e_dir.is_some() && ne_dir.is_none() {
-                corners += 1;
-            }
-            if s_dir.is_some() && e_dir.is_some() && se_dir.is_none() {
-                corners += 1;
-            }
-            if s_dir.is_some() && w_dir.is_some() && sw_dir.is_none() {
-                corners += 1;
-            }
-            if n_dir.is_some() && w_dir.is_some() && nw_dir.is_none() {
-                corners += 1;
-            }
+        if n_dir.is_some() && e_dir.is_some() && ne_dir.is_none() {
+            corners += 1;
+        }
+        if s_dir.is_some() && e_dir.is_some() && se_dir.is_none() {
+            corners += 1;
+        }
+        if s_dir.is_some() && w_dir.is_some() && sw_dir.is_none() {
+            corners += 1;
+        }
+        if n_dir.is_some() && w_dir.is_some() && nw_dir.is_none() {
+            corners += 1;
         }
     }
 
     if area > 0 && perimeter > 0 {
-        /*println!(
+        trace_decision!(
+            Debug,
             "Plant: {}, Area: {}, Perimeter: {}, Corners: {}",
-            plant, area, perimeter, corners
-        );*/
+            plant,
+            area,
+            perimeter,
+            corners
+        );
         // Once we are done, we can add the new region
         regions
             .entry(plant)
@@ -188,21 +173,26 @@ fn flood_fill(
     }
 }
 
-fn get_price_fencing_all_regions(input_file: &str, apply_discount: bool) -> usize {
-    let input = parse_input(input_file);
+pub fn get_price_fencing_all_regions(
+    input_file: &str,
+    apply_discount: bool,
+) -> Result<usize, Error> {
+    Ok(price_fencing_all_regions(
+        &parse_input_file(input_file, parse_input)?,
+        apply_discount,
+    ))
+}
 
-    //print_plants(&input.plants);
+/// Part one: the total price of fencing every region, area times perimeter.
+/// Part two, with `apply_discount`: the total at the bulk discount, area times number of sides
+pub fn price_fencing_all_regions(input: &Input, apply_discount: bool) -> usize {
+    trace_state!(Trace, "plants", input.plants);
 
     let plant_pos_list: Vec<(char, (usize, usize))> = input
         .plants
         .iter()
-        .enumerate()
-        .flat_map(|(row_index, row)| {
-            row.iter()
-                .enumerate()
-                .map(move |(col_index, plant)| (*plant, (row_index, col_index)))
-        })
-        .collect::<Vec<(char, (usize, usize))>>();
+        .map(|(pos, plant)| (*plant, pos))
+        .collect();
 
     let mut regions: HashMap<char, Vec<Region>> = HashMap::new();
     let mut visited_plants: HashSet<(usize, usize)> = HashSet::new();
@@ -217,7 +207,11 @@ fn get_price_fencing_all_regions(input_file: &str, apply_discount: bool) -> usiz
         );
     }
 
-    //println!("Regions: {:?}", regions);
+    trace_counter!(
+        Info,
+        "regions",
+        regions.values().map(Vec::len).sum::<usize>()
+    );
 
     if apply_discount {
         regions
@@ -242,103 +236,108 @@ fn get_price_fencing_all_regions(input_file: &str, apply_discount: bool) -> usiz
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_price_fencing_all_regions_test01() {
-        assert_eq!(
-            140,
-            get_price_fencing_all_regions("input/day12_test01.txt", false)
-        );
-    }
-
-    #[test]
-    fn test_get_price_fencing_all_regions_test02() {
-        assert_eq!(
-            772,
-            get_price_fencing_all_regions("input/day12_test02.txt", false)
-        );
-    }
+/// The same total as [`price_fencing_all_regions`], worked out more simply and slowly: every
+/// plot is labelled with its region first, then each fence is counted, and each side is
+/// counted at the fence it starts with
+pub fn price_fencing_by_fences(input: &Input, apply_discount: bool) -> usize {
+    let plants = &input.plants;
 
-    #[test]
-    fn test_get_price_fencing_all_regions_test03() {
-        assert_eq!(
-            1930,
-            get_price_fencing_all_regions("input/day12_test03.txt", false)
-        );
+    let mut region_of: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut num_regions = 0;
+    for (start, _) in plants.iter() {
+        if region_of.contains_key(&start) {
+            continue;
+        }
+        region_of.insert(start, num_regions);
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            for dir in [Compass::North, Compass::South, Compass::East, Compass::West] {
+                if let Some(next) = plants.step(pos, dir)
+                    && plants[next] == plants[pos]
+                    && !region_of.contains_key(&next)
+                {
+                    region_of.insert(next, num_regions);
+                    stack.push(next);
+                }
+            }
+        }
+        num_regions += 1;
     }
 
-    #[test]
-    fn test_get_price_fencing_all_regions_test04() {
-        assert_eq!(
-            692,
-            get_price_fencing_all_regions("input/day12_test04.txt", false)
-        );
+    let fenced = |pos, dir| {
+        plants
+            .step(pos, dir)
+            .is_none_or(|next| region_of[&next] != region_of[&pos])
+    };
+    let mut areas = vec![0; num_regions];
+    let mut fences = vec![0; num_regions];
+    let mut sides = vec![0; num_regions];
+    for (pos, _) in plants.iter() {
+        let region = region_of[&pos];
+        areas[region] += 1;
+        // Each fence, and the way to the fence before it along the same side
+        for (dir, back) in [
+            (Compass::North, Compass::West),
+            (Compass::South, Compass::West),
+            (Compass::East, Compass::North),
+            (Compass::West, Compass::North),
+        ] {
+            if fenced(pos, dir) {
+                fences[region] += 1;
+                let continues = plants
+                    .step(pos, back)
+                    .is_some_and(|prev| region_of[&prev] == region && fenced(prev, dir));
+                if !continues {
+                    sides[region] += 1;
+                }
+            }
+        }
     }
 
-    #[test]
-    fn test_get_price_fencing_all_regions_test05() {
-        assert_eq!(
-            1184,
-            get_price_fencing_all_regions("input/day12_test05.txt", false)
-        );
-    }
+    (0..num_regions)
+        .map(|region| {
+            let fencing = if apply_discount {
+                sides[region]
+            } else {
+                fences[region]
+            };
+            areas[region] * fencing
+        })
+        .sum()
+}
 
-    #[test]
-    fn test_get_price_fencing_all_regions() {
-        assert_eq!(
-            1363682,
-            get_price_fencing_all_regions("input/day12.txt", false)
-        );
-    }
+/// Both parts both ways, for [`differential`](super::differential) testing
+pub fn implementations(part: Part) -> Vec<Implementation> {
+    let apply_discount = part == Part::Two;
+    vec![
+        Implementation::new("corners", parse_input, move |input| {
+            price_fencing_all_regions(input, apply_discount)
+        }),
+        Implementation::new("fences", parse_input, move |input| {
+            price_fencing_by_fences(input, apply_discount)
+        }),
+    ]
+}
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_test01() {
-        assert_eq!(
-            80,
-            get_price_fencing_all_regions("input/day12_test01.txt", true)
-        );
-    }
+/// The [`Solution`] for day 12: [`price_fencing_all_regions`] without and with the discount
+pub struct Day12;
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_test02() {
-        assert_eq!(
-            436,
-            get_price_fencing_all_regions("input/day12_test02.txt", true)
-        );
-    }
+impl Solution for Day12 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_test03() {
-        assert_eq!(
-            1206,
-            get_price_fencing_all_regions("input/day12_test03.txt", true)
-        );
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_test04() {
-        assert_eq!(
-            236,
-            get_price_fencing_all_regions("input/day12_test04.txt", true)
-        );
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_test05() {
-        assert_eq!(
-            368,
-            get_price_fencing_all_regions("input/day12_test05.txt", true)
-        );
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(price_fencing_all_regions(input, false))
     }
 
-    #[test]
-    fn test_get_price_fencing_all_regions_bulk_discount_() {
-        assert_eq!(
-            787680,
-            get_price_fencing_all_regions("input/day12.txt", true)
-        );
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(price_fencing_all_regions(input, true))
     }
 }
@@ -1,149 +1,389 @@
 // https://adventofcode.com/2024/day/13
 
+//! Day 13: Claw Contraption. Winning prizes from claw machines for the fewest tokens.
+
+use std::fmt::Display;
+
+use itertools::Itertools;
 use nalgebra::{Matrix2, Vector2};
 use regex::Regex;
 
-use super::utils::get_lines;
+use super::cancel::{Cancel, TimedOut};
+use super::differential::Implementation;
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::parallel::sum_by;
+use super::solution::{Part, Solution};
+use super::utils::{parse_field, parse_input_file};
 
+/// How far a button moves the claw, or where a prize is
 #[derive(Clone, Copy, Debug)]
-struct GamePosition {
-    x_right: usize,
-    y_forward: usize,
+pub struct GamePosition {
+    pub x_right: usize,
+    pub y_forward: usize,
 }
 
+/// A claw machine
 #[derive(Debug)]
-struct Game {
-    button_a: GamePosition,
-    button_b: GamePosition,
-    prize: GamePosition,
+pub struct Game {
+    /// Costs 3 tokens to push
+    pub button_a: GamePosition,
+    /// Costs 1 token to push
+    pub button_b: GamePosition,
+    pub prize: GamePosition,
 }
 
-struct Input {
-    games: Vec<Game>,
-}
+/// Part two moves every prize this far along both axes
+pub const PRIZE_POS_INC: usize = 10000000000000;
+
+/// Part one only allows each button to be pressed this many times
+pub const MAX_PRESSES: usize = 100;
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+/// The claw machines
+pub struct Input {
+    /// Each claw machine, with its buttons and prize
+    pub games: Vec<Game>,
+}
 
+/// Reads each claw machine's button A, button B and prize lines, with a blank line between
+/// machines
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
     let mut games: Vec<Game> = Vec::new();
 
-    lines.split(|line| line.trim().is_empty()).for_each(|game| {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut line_index = 0;
+    for game in lines.split(|line| line.trim().is_empty()) {
         if let [button_a, button_b, prize] = game {
             games.push(Game {
-                button_a: parse_button_a(button_a),
-                button_b: parse_button_b(button_b),
-                prize: parse_prize(prize),
+                button_a: parse_button_a(line_index, button_a)?,
+                button_b: parse_button_b(line_index + 1, button_b)?,
+                prize: parse_prize(line_index + 2, prize)?,
             });
+        } else if !game.is_empty() {
+            return Err(ParseError::new(
+                line_index,
+                0,
+                "3 lines describing a claw machine",
+                format!("{} lines", game.len()),
+            ));
         }
-    });
-    Input { games }
+        // Skip past this game and the blank line after it
+        line_index += game.len() + 1;
+    }
+    Ok(Input { games })
 }
 
-fn parse_button_a(button_a: &str) -> GamePosition {
-    let re = Regex::new(r"Button A: X\+(\d+), Y\+(\d+)").unwrap();
-    let caps = re.captures(button_a).unwrap();
+/// `size` claw machines, along with the fewest tokens needed to win every prize in part
+/// one. Most prizes can be won, but some can't be reached by whole numbers of presses and
+/// some need more than [`MAX_PRESSES`] of a button, so win nothing.
+pub fn generate_games(rng: &mut Rng, size: usize) -> (Vec<Game>, usize) {
+    let mut games = vec![];
+    let mut tokens = 0;
+    while games.len() < size {
+        let button = |rng: &mut Rng| GamePosition {
+            x_right: rng.between(10, 99) as usize,
+            y_forward: rng.between(10, 99) as usize,
+        };
+        let (button_a, button_b) = (button(rng), button(rng));
+        // Buttons moving the claw in the same direction could win the prize more than
+        // one way, and the cheapest is then harder to know
+        let det = button_a.x_right as i64 * button_b.y_forward as i64
+            - button_a.y_forward as i64 * button_b.x_right as i64;
+        if det == 0 {
+            continue;
+        }
+        let (mut a, mut b) = (rng.between(1, 100) as usize, rng.between(1, 100) as usize);
+        let mut x_offset = 0;
+        match rng.below(4) {
+            0 => {
+                // Out of reach, needing too many presses of one button or the other
+                if rng.chance(0.5) {
+                    a += MAX_PRESSES;
+                } else {
+                    b += MAX_PRESSES;
+                }
+            }
+            1 => {
+                // Moving the prize one to the right means pressing each button a fraction
+                // of a time more or less, unless both fractions happen to be whole
+                if button_a.y_forward as i64 % det == 0 && button_b.y_forward as i64 % det == 0 {
+                    continue;
+                }
+                x_offset = 1;
+            }
+            _ => tokens += a * 3 + b,
+        }
+        games.push(Game {
+            button_a,
+            button_b,
+            prize: GamePosition {
+                x_right: a * button_a.x_right + b * button_b.x_right + x_offset,
+                y_forward: a * button_a.y_forward + b * button_b.y_forward,
+            },
+        });
+    }
+    (games, tokens)
+}
 
-    let x = caps.get(1).unwrap().as_str().parse::<i32>().unwrap();
-    let y = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
+/// Every problem with the puzzle input, each line of each claw machine being checked on
+/// its own
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    let mut errors = vec![];
 
-    GamePosition {
-        x_right: x as usize,
-        y_forward: y as usize,
+    let lines: Vec<&str> = input.lines().collect();
+    let mut line_index = 0;
+    for game in lines.split(|line| line.trim().is_empty()) {
+        if let [button_a, button_b, prize] = game {
+            errors.extend(parse_button_a(line_index, button_a).err());
+            errors.extend(parse_button_b(line_index + 1, button_b).err());
+            errors.extend(parse_prize(line_index + 2, prize).err());
+        } else if !game.is_empty() {
+            errors.push(ParseError::new(
+                line_index,
+                0,
+                "3 lines describing a claw machine",
+                format!("{} lines", game.len()),
+            ));
+        }
+        line_index += game.len() + 1;
     }
+    errors
 }
 
-fn parse_button_b(button_b: &str) -> GamePosition {
-    let re = Regex::new(r"Button B: X\+(\d+), Y\+(\d+)").unwrap();
-    let caps = re.captures(button_b).unwrap();
+/// `size` claw machines, as [`generate_games`] makes them
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    generate_games(rng, size)
+        .0
+        .iter()
+        .map(|game| {
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}\n",
+                game.button_a.x_right,
+                game.button_a.y_forward,
+                game.button_b.x_right,
+                game.button_b.y_forward,
+                game.prize.x_right,
+                game.prize.y_forward
+            )
+        })
+        .join("\n")
+}
 
-    let x = caps.get(1).unwrap().as_str().parse::<i32>().unwrap();
-    let y = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
+fn parse_game_position(
+    line_index: usize,
+    line: &str,
+    re: &Regex,
+    expected: &str,
+) -> Result<GamePosition, ParseError> {
+    let caps = re
+        .captures(line)
+        .ok_or_else(|| ParseError::new(line_index, 0, expected, line))?;
 
-    GamePosition {
-        x_right: x as usize,
-        y_forward: y as usize,
+    let x = parse_field(line_index, line, caps.get(1).unwrap().as_str(), "a number")?;
+    let y = parse_field(line_index, line, caps.get(2).unwrap().as_str(), "a number")?;
+    // Anything left over is pointed at where it starts, rather than at the whole line
+    let rest = caps.get(3).unwrap();
+    if !rest.is_empty() {
+        return Err(ParseError::new(
+            line_index,
+            rest.start(),
+            "the end of the line",
+            rest.as_str(),
+        ));
     }
+
+    Ok(GamePosition {
+        x_right: x,
+        y_forward: y,
+    })
 }
 
-fn parse_prize(prize: &str) -> GamePosition {
-    let re = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
-    let caps = re.captures(prize).unwrap();
+fn parse_button_a(line_index: usize, button_a: &str) -> Result<GamePosition, ParseError> {
+    lazy_static! {
+        static ref RE_BUTTON_A: Regex = Regex::new(r"^Button A: X\+(\d+), Y\+(\d+)(.*)$").unwrap();
+    }
+    parse_game_position(
+        line_index,
+        button_a,
+        &RE_BUTTON_A,
+        "\"Button A: X+<x>, Y+<y>\"",
+    )
+}
 
-    let x = caps.get(1).unwrap().as_str().parse::<i32>().unwrap();
-    let y = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
+fn parse_button_b(line_index: usize, button_b: &str) -> Result<GamePosition, ParseError> {
+    lazy_static! {
+        static ref RE_BUTTON_B: Regex = Regex::new(r"^Button B: X\+(\d+), Y\+(\d+)(.*)$").unwrap();
+    }
+    parse_game_position(
+        line_index,
+        button_b,
+        &RE_BUTTON_B,
+        "\"Button B: X+<x>, Y+<y>\"",
+    )
+}
 
-    GamePosition {
-        x_right: x as usize,
-        y_forward: y as usize,
+fn parse_prize(line_index: usize, prize: &str) -> Result<GamePosition, ParseError> {
+    lazy_static! {
+        static ref RE_PRIZE: Regex = Regex::new(r"^Prize: X=(\d+), Y=(\d+)(.*)$").unwrap();
     }
+    parse_game_position(line_index, prize, &RE_PRIZE, "\"Prize: X=<x>, Y=<y>\"")
+}
+
+/// The presses of buttons A and B which win `game`'s prize after moving it `pos_inc` along
+/// both axes, if whole numbers of presses can reach it
+fn winning_presses(game: &Game, pos_inc: usize) -> Option<(usize, usize)> {
+    // Create a system of linear equations in the form Ax = b
+    let a = Matrix2::new(
+        game.button_a.x_right as f64,
+        game.button_b.x_right as f64,
+        game.button_a.y_forward as f64,
+        game.button_b.y_forward as f64,
+    );
+    let b = Vector2::new(
+        game.prize.x_right as f64 + pos_inc as f64,
+        game.prize.y_forward as f64 + pos_inc as f64,
+    );
+
+    let x = a.lu().solve(&b)?;
+    // Define a small tolerance value
+    let tolerance = 1e-3;
+    // Check if the solutions are whole numbers within the tolerance, and presses which
+    // could be made
+    let presses = |x: f64| {
+        let rounded = x.round();
+        ((x - rounded).abs() < tolerance && rounded >= 0.0).then_some(rounded as usize)
+    };
+    Some((presses(x[0])?, presses(x[1])?))
+}
+
+/// What it costs to win a prize by pressing button A `a` times and button B `b` times
+fn tokens((a, b): (usize, usize)) -> usize {
+    let prize_calc = a * 3 + b;
+    trace_decision!(
+        Debug,
+        "Won prize! A: {}, B: {}, Prize: {}",
+        a,
+        b,
+        prize_calc
+    );
+    prize_calc
+}
+
+pub fn get_fewest_tokens(input_file: &str) -> Result<usize, Error> {
+    Ok(fewest_tokens(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part one: the fewest tokens needed to win every prize which can be won, pressing each
+/// button no more than [`MAX_PRESSES`] times
+pub fn fewest_tokens(input: &Input) -> usize {
+    sum_by(&input.games, |game| {
+        winning_presses(game, 0)
+            .filter(|&(a, b)| a <= MAX_PRESSES && b <= MAX_PRESSES)
+            .map_or(0, tokens)
+    })
+}
+
+pub fn get_fewest_tokens_moved_prizes(input_file: &str) -> Result<usize, Error> {
+    Ok(fewest_tokens_moved_prizes(&parse_input_file(
+        input_file,
+        parse_input,
+    )?))
 }
 
-fn get_fewest_tokens(input_file: &str, pos_inc: usize) -> usize {
-    let input = parse_input(input_file);
+/// Part two: as for part one, but with every prize moved [`PRIZE_POS_INC`] along both axes,
+/// and so many presses needed that there's no limit on them
+pub fn fewest_tokens_moved_prizes(input: &Input) -> usize {
+    sum_by(&input.games, |game| {
+        winning_presses(game, PRIZE_POS_INC).map_or(0, tokens)
+    })
+}
 
+/// Part one's answer found by trying every number of presses of each button up to the
+/// [`MAX_PRESSES`] the puzzle allows
+pub fn fewest_tokens_brute_force(input: &Input) -> usize {
     input
         .games
-        .into_iter()
+        .iter()
         .map(|game| {
-            // Create a system of linear equations in the form Ax = b
-            let a = Matrix2::new(
-                game.button_a.x_right as f64,
-                game.button_b.x_right as f64,
-                game.button_a.y_forward as f64,
-                game.button_b.y_forward as f64,
-            );
-            let b = Vector2::new(
-                game.prize.x_right as f64 + pos_inc as f64,
-                game.prize.y_forward as f64 + pos_inc as f64,
-            );
-
-            let mut prize = 0;
-            if let Some(x) = a.lu().solve(&b) {
-                // Define a small tolerance value
-                let tolerance = 1e-3;
-                // Check if the solutions are whole numbers within the tolerance
-                if (x[0] - x[0].round()).abs() < tolerance
-                    && (x[1] - x[1].round()).abs() < tolerance
-                {
-                    let x0 = x[0].round() as usize;
-                    let x1 = x[1].round() as usize;
-                    let prize_calc = x0 * 3 + x1;
-                    //println!("Won prize! A: {}, B: {}, Prize: {}", x0, x1, prize_calc);
-                    prize = prize_calc;
-                }
-            }
-            prize
+            (0..=MAX_PRESSES)
+                .cartesian_product(0..=MAX_PRESSES)
+                .filter(|(a, b)| {
+                    a * game.button_a.x_right + b * game.button_b.x_right == game.prize.x_right
+                        && a * game.button_a.y_forward + b * game.button_b.y_forward
+                            == game.prize.y_forward
+                })
+                .map(|(a, b)| a * 3 + b)
+                .min()
+                .unwrap_or(0)
         })
         .sum()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Part one both ways, for [`differential`](super::differential) testing. Part two's
+/// presses are far too many to try them all.
+pub fn implementations(part: Part) -> Vec<Implementation> {
+    match part {
+        Part::One => vec![
+            Implementation::new("linear solve", parse_input, fewest_tokens),
+            Implementation::new("brute force", parse_input, fewest_tokens_brute_force),
+        ],
+        Part::Two => vec![],
+    }
+}
 
-    #[test]
-    fn test_get_fewest_tokens_test01() {
-        assert_eq!(480, get_fewest_tokens("input/day13_test01.txt", 0));
+/// The [`Solution`] for day 13: [`fewest_tokens`] and [`fewest_tokens_moved_prizes`]
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_fewest_tokens() {
-        assert_eq!(29517, get_fewest_tokens("input/day13.txt", 0));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
+    }
+
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(fewest_tokens(input))
     }
 
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(fewest_tokens_moved_prizes(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
-    fn test_get_fewest_tokens_pos_inc_test01() {
-        assert_eq!(
-            875318608908,
-            get_fewest_tokens("input/day13_test01.txt", 10000000000000)
-        );
+    fn test_fewest_tokens_generated() {
+        for seed in 0..10 {
+            let (games, tokens) = generate_games(&mut Rng::new(seed), 20);
+            let input = Input { games };
+            assert_eq!(tokens, fewest_tokens(&input));
+            assert_eq!(tokens, fewest_tokens_brute_force(&input));
+        }
     }
 
     #[test]
-    fn test_get_fewest_token_pos_inc_() {
+    fn test_validate_input() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22\nPrize: X=8400, Y=5400\n\n\
+                     Button A: X+26, Y+66\nButton B: X+67, Y+21\n\n\
+                     Button A: X+17, Y+86\nButton B: X+84, Y+37\nPrize: X=, Y=7870\n\n\
+                     Button A: X+1, Y+2junk\nButton B: X+3, Y+4\nxxPrize: X=1, Y=2\n";
+
         assert_eq!(
-            103570327981381,
-            get_fewest_tokens("input/day13.txt", 10000000000000)
+            vec![
+                ParseError::new(1, 0, "\"Button B: X+<x>, Y+<y>\"", "Button B: X+22"),
+                ParseError::new(4, 0, "3 lines describing a claw machine", "2 lines"),
+                ParseError::new(9, 0, "\"Prize: X=<x>, Y=<y>\"", "Prize: X=, Y=7870"),
+                ParseError::new(11, 18, "the end of the line", "junk"),
+                ParseError::new(13, 0, "\"Prize: X=<x>, Y=<y>\"", "xxPrize: X=1, Y=2"),
+            ],
+            validate_input(input)
         );
     }
 }
@@ -1,113 +1,230 @@
 // https://adventofcode.com/2024/day/14
 
-use std::collections::HashMap;
+//! Day 14: Restroom Redoubt. Predicting where security robots will be.
 
-use regex::Regex;
-
-use super::utils::get_lines;
+use std::{collections::HashMap, fmt::Display};
 
+use regex::Regex;
+use serde::{
+    Deserialize, Deserializer,
+    de::{Error as _, Unexpected},
+};
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::render::{Frames, Image, Rgb};
+use super::solution::{Part, Solution};
+use super::utils::{Grid, Point, Vec2, column_of, parse_field, parse_input_file, validate_lines};
+
+/// Width of the space the robots are in for the puzzle itself
+pub const WIDTH: i32 = 101;
+/// Height of the space the robots are in for the puzzle itself
+pub const HEIGHT: i32 = 103;
+
+/// A robot patrolling the space outside the restroom
 #[derive(Debug, Clone)]
-struct Robot {
-    pos: (i32, i32),
-    vel: (i32, i32),
+pub struct Robot {
+    /// Where the robot starts, with x increasing to the right and y downwards
+    pub pos: Point,
+    /// How far the robot moves each second
+    pub vel: Vec2,
 }
 
-struct Input {
-    robots: Vec<Robot>,
+/// The robots, one per line
+pub struct Input {
+    /// Each robot's starting position and velocity
+    pub robots: Vec<Robot>,
 }
 
-fn parse_coordinates(input: &str) -> ((i32, i32), (i32, i32)) {
-    let re = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)").unwrap();
-    let caps = re.captures(input).unwrap();
-
-    let p1 = caps.get(1).unwrap().as_str().parse::<i32>().unwrap();
-    let p2 = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
-    let v1 = caps.get(3).unwrap().as_str().parse::<i32>().unwrap();
-    let v2 = caps.get(4).unwrap().as_str().parse::<i32>().unwrap();
+type Coordinates = (Point, Vec2);
+
+/// Parses one of a robot's starting coordinates, which must be from 0 to `size - 1`
+fn parse_position(
+    line_index: usize,
+    input: &str,
+    field: &str,
+    axis: &str,
+    size: i32,
+) -> Result<isize, ParseError> {
+    let expected = format!("{} position from 0 to {}", axis, size - 1);
+    let pos: i32 = parse_field(line_index, input, field, &expected)?;
+    if (0..size).contains(&pos) {
+        Ok(pos as isize)
+    } else {
+        Err(ParseError::new(
+            line_index,
+            column_of(input, field),
+            expected,
+            field,
+        ))
+    }
+}
 
-    ((p1, p2), (v1, v2))
+fn parse_coordinates(
+    line_index: usize,
+    input: &str,
+    width: i32,
+    height: i32,
+) -> Result<Coordinates, ParseError> {
+    lazy_static! {
+        static ref RE_ROBOT: Regex = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)").unwrap();
+    }
+    let caps = RE_ROBOT
+        .captures(input)
+        .ok_or_else(|| ParseError::new(line_index, 0, "\"p=<x>,<y> v=<x>,<y>\"", input))?;
+
+    let p1 = parse_position(
+        line_index,
+        input,
+        caps.get(1).unwrap().as_str(),
+        "an x",
+        width,
+    )?;
+    let p2 = parse_position(
+        line_index,
+        input,
+        caps.get(2).unwrap().as_str(),
+        "a y",
+        height,
+    )?;
+    let v1 = parse_field(
+        line_index,
+        input,
+        caps.get(3).unwrap().as_str(),
+        "a velocity",
+    )?;
+    let v2 = parse_field(
+        line_index,
+        input,
+        caps.get(4).unwrap().as_str(),
+        "a velocity",
+    )?;
+
+    Ok((Point::new(p1, p2), Vec2::new(v1, v2)))
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+/// Parses the robots for the puzzle's [`WIDTH`] by [`HEIGHT`] space
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    parse_input_within(input, WIDTH, HEIGHT)
+}
 
+/// Parses the robots, each of which must start inside the `width` by `height` space
+pub fn parse_input_within(input: &str, width: i32, height: i32) -> Result<Input, ParseError> {
     let mut robots: Vec<Robot> = Vec::new();
 
-    for line in lines {
-        let (pos, vel) = parse_coordinates(&line);
+    for (line_index, line) in input.lines().enumerate() {
+        let (pos, vel) = parse_coordinates(line_index, line, width, height)?;
         robots.push(Robot { pos, vel });
     }
 
-    Input { robots }
+    Ok(Input { robots })
 }
 
-fn print_robots(width: i32, height: i32, robot_map: &HashMap<(i32, i32), Vec<Robot>>) {
-    for row in 0..height {
-        for col in 0..width {
-            let mut found = false;
-            if let Some(robots) = robot_map.get(&(col, row)) {
-                print!("{}", robots.len());
-                found = true;
-            }
-            if !found {
-                print!(".");
-            }
-        }
-        println!();
+/// Every line which isn't a robot starting inside the puzzle's [`WIDTH`] by [`HEIGHT`] space
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    validate_input_within(input, WIDTH, HEIGHT)
+}
+
+/// Every line which isn't a robot starting inside the `width` by `height` space
+pub fn validate_input_within(input: &str, width: i32, height: i32) -> Vec<ParseError> {
+    validate_lines(input, |line| parse_input_within(line, width, height))
+}
+
+/// `size` robots somewhere in the puzzle's 101 by 103 space
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        input.push_str(&format!(
+            "p={},{} v={},{}\n",
+            rng.between(0, WIDTH as i64 - 1),
+            rng.between(0, HEIGHT as i64 - 1),
+            rng.between(-99, 99),
+            rng.between(-99, 99)
+        ));
     }
+    input
 }
 
-fn get_safety_factor(input_file: &str, width: i32, height: i32, num_secs: usize) -> usize {
-    let input = parse_input(input_file);
+fn move_robots(robots: &mut [Robot], width: i32, height: i32) {
+    for robot in robots {
+        // Robots teleport to the other side when they walk off an edge
+        robot.pos = (robot.pos + robot.vel).wrap(width as isize, height as isize);
+        trace_decision!(Trace, "pos: {}, vel: {}", robot.pos, robot.vel);
+    }
+}
 
+/// How many robots are on each tile. A robot outside the space, which only an [`Input`] not
+/// made by [`parse_input_within`] can have, is counted where it wraps round to
+fn robot_counts(robots: &[Robot], width: i32, height: i32) -> Grid<usize> {
+    let mut robot_counts = Grid::new(width as usize, height as usize, 0);
+    for robot in robots {
+        robot_counts[robot.pos.wrap(width as isize, height as isize)] += 1;
+    }
+    robot_counts
+}
+
+/// Drawn the way the puzzle does, with the number of robots on each tile
+fn draw_robots(robots: &[Robot], width: i32, height: i32) -> Grid<char> {
+    robot_counts(robots, width, height).map(|&count| match count {
+        0 => '.',
+        _ => char::from_digit(count.min(9) as u32, 10).unwrap(),
+    })
+}
+
+fn robots_image(robots: &[Robot], width: i32, height: i32) -> Image {
+    Image::from_grid(&robot_counts(robots, width, height), |&count| {
+        if count == 0 { Rgb::BLACK } else { Rgb::GREEN }
+    })
+}
+
+pub fn get_safety_factor(
+    input_file: &str,
+    width: i32,
+    height: i32,
+    num_secs: usize,
+) -> Result<usize, Error> {
+    safety_factor(
+        &parse_input_file(input_file, |input| parse_input_within(input, width, height))?,
+        width,
+        height,
+        num_secs,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
+
+/// Part one: the number of robots in each quadrant after `num_secs` seconds, multiplied together
+pub fn safety_factor(
+    input: &Input,
+    width: i32,
+    height: i32,
+    num_secs: usize,
+    cancel: &Cancel,
+) -> Result<usize, TimedOut> {
     let mut robots = input.robots.clone();
 
     for _ in 0..num_secs {
-        for robot in &mut robots {
-            let (old_x, old_y) = robot.pos;
-            let (dx, dy) = robot.vel;
-
-            let new_x = if old_x + dx < 0 {
-                dx + width + old_x
-            } else if old_x + dx >= width {
-                dx - width + old_x
-            } else {
-                old_x + dx
-            };
-            let new_y = if old_y + dy < 0 {
-                dy + height + old_y
-            } else if old_y + dy >= height {
-                dy - height + old_y
-            } else {
-                old_y + dy
-            };
-
-            robot.pos = (new_x, new_y);
-
-            /*println!(
-                "Secs: {}, old_x: {}, old_y: {}, dx: {}, dy: {}, new_x: {}, new_y: {}",
-                secs + 1,
-                old_x,
-                old_y,
-                dx,
-                dy,
-                new_x,
-                new_y
-            );*/
-        }
+        cancel.check()?;
+        move_robots(&mut robots, width, height);
     }
 
-    let mut robot_map: HashMap<(i32, i32), Vec<Robot>> = HashMap::new();
+    trace_state!(Debug, "robots", draw_robots(&robots, width, height));
+
+    let mut robot_map: HashMap<Point, Vec<Robot>> = HashMap::new();
     for robot in robots {
         robot_map.entry(robot.pos).or_default().push(robot);
     }
 
-    print_robots(width, height, &robot_map);
-
     let quad_width = width as usize / 2;
     let quad_height = height as usize / 2;
 
-    //println!("quad_width: {}, quad_height: {}", quad_width, quad_height);
+    trace_decision!(
+        Debug,
+        "quad_width: {}, quad_height: {}",
+        quad_width,
+        quad_height
+    );
 
     let mut ne_robots = 0;
     let mut nw_robots = 0;
@@ -116,8 +233,7 @@ fn get_safety_factor(input_file: &str, width: i32, height: i32, num_secs: usize)
 
     for (pos, robots) in robot_map {
         for _ in robots {
-            let (x, y) = pos;
-            let (x, y) = (x as usize, y as usize);
+            let (x, y) = (pos.x as usize, pos.y as usize);
             if x < quad_width && y < quad_height {
                 ne_robots += 1;
             } else if x > quad_width && y < quad_height {
@@ -130,38 +246,41 @@ fn get_safety_factor(input_file: &str, width: i32, height: i32, num_secs: usize)
         }
     }
 
-    ne_robots * nw_robots * se_robots * sw_robots
+    Ok(ne_robots * nw_robots * se_robots * sw_robots)
 }
 
-fn check_xmas_tree(input_file: &str, width: i32, height: i32, max_secs: usize) -> usize {
-    let input = parse_input(input_file);
+pub fn check_xmas_tree(
+    input_file: &str,
+    width: i32,
+    height: i32,
+    max_secs: usize,
+) -> Result<usize, Error> {
+    find_xmas_tree(
+        &parse_input_file(input_file, |input| parse_input_within(input, width, height))?,
+        width,
+        height,
+        max_secs,
+        &Cancel::never(),
+    )
+    .map_err(|error| Error::timed_out(input_file, error))
+}
 
+/// Part two: the first second, up to `max_secs`, at which no two robots overlap, which is
+/// when they make a picture of a Christmas tree. 0 if they never do
+pub fn find_xmas_tree(
+    input: &Input,
+    width: i32,
+    height: i32,
+    max_secs: usize,
+    cancel: &Cancel,
+) -> Result<usize, TimedOut> {
     let mut robots = input.robots.clone();
 
     for secs in 0..max_secs {
-        for robot in &mut robots {
-            let (old_x, old_y) = robot.pos;
-            let (dx, dy) = robot.vel;
-
-            let new_x = if old_x + dx < 0 {
-                dx + width + old_x
-            } else if old_x + dx >= width {
-                dx - width + old_x
-            } else {
-                old_x + dx
-            };
-            let new_y = if old_y + dy < 0 {
-                dy + height + old_y
-            } else if old_y + dy >= height {
-                dy - height + old_y
-            } else {
-                old_y + dy
-            };
-
-            robot.pos = (new_x, new_y);
-        }
+        cancel.check()?;
+        move_robots(&mut robots, width, height);
 
-        let mut robot_map: HashMap<(i32, i32), Vec<Robot>> = HashMap::new();
+        let mut robot_map: HashMap<Point, Vec<Robot>> = HashMap::new();
         for robot in &robots {
             robot_map.entry(robot.pos).or_default().push(robot.clone());
         }
@@ -171,99 +290,170 @@ fn check_xmas_tree(input_file: &str, width: i32, height: i32, max_secs: usize) -
             .count();
 
         if sum_single_entries == robots.len() {
-            print_robots(width, height, &robot_map.clone());
-            return secs + 1;
+            trace_state!(Info, "Christmas tree", draw_robots(&robots, width, height));
+            return Ok(secs + 1);
         }
     }
 
-    0
+    Ok(0)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_safety_factor_test01() {
-        assert_eq!(0, get_safety_factor("input/day14_test01.txt", 11, 7, 0));
-    }
+/// The [`Solution`] for day 14: [`safety_factor`] and [`find_xmas_tree`]
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Day14 {
+    /// Width of the space the robots are in, 101 in the puzzle and 11 in the example
+    #[serde(deserialize_with = "positive")]
+    pub width: i32,
+    /// Height of the space the robots are in, 103 in the puzzle and 7 in the example
+    #[serde(deserialize_with = "positive")]
+    pub height: i32,
+    /// How many seconds part one runs for
+    pub num_secs: usize,
+    /// How many seconds part two waits for the Christmas tree before giving up
+    pub max_secs: usize,
+}
 
-    #[test]
-    fn test_get_safety_factor_test02() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 0));
+/// A width or height, which must leave some space for the robots to wrap round
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    let size = i32::deserialize(deserializer)?;
+    if size > 0 {
+        Ok(size)
+    } else {
+        Err(D::Error::invalid_value(
+            Unexpected::Signed(size.into()),
+            &"a size greater than 0",
+        ))
     }
+}
 
-    #[test]
-    fn test_get_safety_factor_test03() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 1));
+impl Default for Day14 {
+    fn default() -> Self {
+        Day14 {
+            width: WIDTH,
+            height: HEIGHT,
+            num_secs: 100,
+            max_secs: 10000,
+        }
     }
+}
 
-    #[test]
-    fn test_get_safety_factor_test04() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 2));
+impl Day14 {
+    /// Saves where the robots are at the start and after every second. Part one runs for
+    /// `num_secs` seconds, part two until the robots first make the Christmas tree.
+    pub fn render(&self, part: Part, input: &Input, frames: &mut Frames) -> Result<(), Error> {
+        let num_secs = match part {
+            Part::One => self.num_secs,
+            Part::Two => find_xmas_tree(
+                input,
+                self.width,
+                self.height,
+                self.max_secs,
+                &Cancel::never(),
+            )
+            .unwrap_or_else(|_| unreachable!("nothing else can cancel a new Cancel")),
+        };
+        let mut robots = input.robots.clone();
+
+        frames.push(&robots_image(&robots, self.width, self.height))?;
+        for _ in 0..num_secs {
+            move_robots(&mut robots, self.width, self.height);
+            frames.push(&robots_image(&robots, self.width, self.height))?;
+        }
+        Ok(())
     }
+}
 
-    #[test]
-    fn test_get_safety_factor_test05() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 3));
-    }
+impl Solution for Day14 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_safety_factor_test06() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 4));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input_within(input, self.width, self.height)
     }
 
-    #[test]
-    fn test_get_safety_factor_test07() {
-        assert_eq!(0, get_safety_factor("input/day14_test02.txt", 11, 7, 5));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input_within(input, self.width, self.height)
     }
 
-    #[test]
-    fn test_get_safety_factor_test08() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 0));
+    fn part1(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        safety_factor(input, self.width, self.height, self.num_secs, cancel)
     }
 
-    #[test]
-    fn test_get_safety_factor_test09() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 1));
+    fn part2(&self, input: &Input, cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        find_xmas_tree(input, self.width, self.height, self.max_secs, cancel)
     }
+}
 
-    #[test]
-    fn test_get_safety_factor_test10() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 2));
-    }
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-    #[test]
-    fn test_get_safety_factor_test11() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 3));
-    }
+    use super::*;
+    use crate::aoc::Format;
 
     #[test]
-    fn test_get_safety_factor_test12() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 4));
+    fn test_parse_outside_space() {
+        let day14 = Day14::default();
+        assert_eq!(
+            Some(ParseError::new(0, 2, "an x position from 0 to 100", "200")),
+            day14.parse("p=200,5 v=1,1").err()
+        );
+        assert_eq!(
+            Some(ParseError::new(0, 4, "a y position from 0 to 102", "-1")),
+            day14.parse("p=5,-1 v=1,1").err()
+        );
     }
 
     #[test]
-    fn test_get_safety_factor_test13() {
-        assert_eq!(0, get_safety_factor("input/day14_test03.txt", 11, 7, 5));
+    fn test_validate_outside_space() {
+        let day14 = Day14 {
+            width: 11,
+            height: 7,
+            ..Day14::default()
+        };
+        assert_eq!(
+            vec![
+                ParseError::new(1, 2, "an x position from 0 to 10", "11"),
+                ParseError::new(2, 4, "a y position from 0 to 6", "7"),
+            ],
+            day14.validate("p=10,6 v=1,1\np=11,0 v=1,1\np=0,7 v=1,1\n")
+        );
     }
 
     #[test]
-    fn test_get_safety_factor_test14() {
-        assert_eq!(12, get_safety_factor("input/day14_test01.txt", 11, 7, 100));
+    fn test_render_outside_space() {
+        let dir = std::env::temp_dir().join(format!("aoc_day14_{}", std::process::id()));
+        let mut frames = Frames::new(&dir, Format::Ppm, 1).unwrap();
+        let day14 = Day14 {
+            width: 11,
+            height: 7,
+            num_secs: 2,
+            ..Day14::default()
+        };
+        let input = Input {
+            robots: vec![Robot {
+                pos: Point::new(200, -5),
+                vel: Vec2::new(1, 1),
+            }],
+        };
+
+        day14.render(Part::One, &input, &mut frames).unwrap();
+        assert_eq!(3, frames.count());
+
+        std::fs::remove_dir_all(dir).unwrap();
     }
 
     #[test]
-    fn test_get_safety_factor() {
+    fn test_find_xmas_tree_cancelled() {
+        let input = parse_input("p=0,0 v=0,0\np=0,0 v=0,0\n").unwrap();
+        assert_eq!(Ok(0), find_xmas_tree(&input, 11, 7, 100, &Cancel::never()));
         assert_eq!(
-            222901875,
-            get_safety_factor("input/day14.txt", 101, 103, 100)
+            Err(TimedOut),
+            find_xmas_tree(&input, 11, 7, 1_000_000_000, &Cancel::after(Duration::ZERO))
+        );
+        assert_eq!(
+            Err(TimedOut),
+            safety_factor(&input, 11, 7, 1_000_000_000, &Cancel::after(Duration::ZERO))
         );
-    }
-
-    #[ignore]
-    #[test]
-    fn test_check_xmas_tree() {
-        assert_eq!(6243, check_xmas_tree("input/day14.txt", 101, 103, 10000));
     }
 }
@@ -1,112 +1,177 @@
 // https://adventofcode.com/2024/day/15
 
-use core::panic;
-use std::{
-    collections::{HashSet, VecDeque},
-    vec,
-};
-
-use super::utils::get_lines;
-
-#[derive(Debug, PartialEq)]
-enum Move {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+//! Day 15: Warehouse Woes. Predicting where a robot will push boxes around a warehouse.
 
-#[derive(Debug)]
-struct Input {
-    warehouse: Vec<Vec<char>>,
-    moves: Vec<Move>,
-}
+use std::{collections::VecDeque, fmt::Display, vec};
 
-fn parse_warehouse(warehouse_part: &[&str]) -> Vec<Vec<char>> {
-    warehouse_part
-        .iter()
-        .map(|line| line.chars().collect())
-        .collect()
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::generate::Rng;
+use super::render::{Frames, Image, Rgb};
+use super::solution::{Part, Solution};
+use super::utils::graph::bfs;
+use super::utils::{Direction, Grid, Point, Vec2, parse_input_file};
+
+/// The warehouse map followed by the robot's moves
+#[derive(Debug)]
+pub struct Input {
+    /// The warehouse, with `#` walls, `O` boxes and the robot `@`
+    pub warehouse: Grid<char>,
+    /// Every move the robot tries to make, in order
+    pub moves: Vec<Direction>,
 }
 
-fn parse_moves(moves_part: &[&str]) -> Vec<Move> {
-    let mut moves: Vec<Move> = vec![];
-    for move_line in moves_part {
-        move_line.chars().for_each(|c| {
+fn parse_moves(
+    first_line_index: usize,
+    moves_part: &[&str],
+) -> Result<Vec<Direction>, Vec<ParseError>> {
+    let mut moves: Vec<Direction> = vec![];
+    let mut errors = vec![];
+    for (line_index, move_line) in moves_part.iter().enumerate() {
+        for (col_index, c) in move_line.char_indices() {
             match c {
-                '^' => moves.push(Move::Up),
-                'v' => moves.push(Move::Down),
-                '<' => moves.push(Move::Left),
-                '>' => moves.push(Move::Right),
-                _ => panic!("Unknown move"),
+                '^' => moves.push(Direction::N),
+                'v' => moves.push(Direction::S),
+                '<' => moves.push(Direction::W),
+                '>' => moves.push(Direction::E),
+                _ => errors.push(ParseError::new(
+                    first_line_index + line_index,
+                    col_index,
+                    "one of '^', 'v', '<' or '>'",
+                    c.to_string(),
+                )),
             };
-        });
+        }
+    }
+    if errors.is_empty() {
+        Ok(moves)
+    } else {
+        Err(errors)
     }
-    moves
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    let parts: Vec<_> = lines.split(|line| line.trim().is_empty()).collect();
-    let warehouse_part: Vec<&str> = parts
-        .first()
-        .map(|v| v.iter().map(|s| s.as_str()).collect())
-        .unwrap_or(vec![]);
-    let moves_part: Vec<&str> = parts
-        .get(1)
-        .map(|v| v.iter().map(|s| s.as_str()).collect())
-        .unwrap_or(vec![]);
-
-    let warehouse = parse_warehouse(&warehouse_part);
+/// There must be exactly one robot, and walls all the way round so it can't leave
+fn warehouse_errors(warehouse: &Grid<char>) -> Vec<ParseError> {
+    let mut errors = vec![];
+    let robots: Vec<(usize, usize)> = warehouse.positions_of(&'@').collect();
+    if robots.is_empty() {
+        errors.push(ParseError::new(0, 0, "a robot '@'", ""));
+    }
+    for &(row, col) in robots.iter().skip(1) {
+        errors.push(ParseError::new(row, col, "only one robot", "@"));
+    }
+    for ((row, col), &tile) in warehouse.iter() {
+        let edge =
+            row == 0 || col == 0 || row == warehouse.height() - 1 || col == warehouse.width() - 1;
+        // Anything which isn't a tile at all is reported as that instead
+        if edge && matches!(tile, '.' | 'O' | '@') {
+            errors.push(ParseError::new(
+                row,
+                col,
+                "a wall '#' round the edge",
+                tile.to_string(),
+            ));
+        }
+    }
+    errors
+}
 
-    let moves = parse_moves(&moves_part);
+/// Reads the warehouse map, a blank line, and then the robot's moves, which can be split
+/// over any number of lines
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    parse_all(input).map_err(|mut errors| errors.swap_remove(0))
+}
 
-    Input { warehouse, moves }
+/// Every tile or move which isn't known, a missing robot or any after the first, and every
+/// gap in the walls round the warehouse
+pub fn validate_input(input: &str) -> Vec<ParseError> {
+    parse_all(input).err().unwrap_or_default()
 }
 
-fn print_warehouse(warehouse: &[Vec<char>]) {
-    for row in warehouse {
-        for entry in row {
-            print!("{}", entry);
+fn parse_all(input: &str) -> Result<Input, Vec<ParseError>> {
+    let mut errors = vec![];
+    let warehouse = Grid::parse_all(input, "one of '#', '.', 'O' or '@'", |c| {
+        matches!(c, '#' | '.' | 'O' | '@').then_some(c)
+    })
+    .map_err(|tile_errors| errors.extend(tile_errors))
+    .ok();
+    // Where the robot and walls are can still be checked with tiles which aren't valid, as
+    // long as the rows are all the same length
+    if let Ok(tiles) = Grid::parse_all(input, "", Some) {
+        errors.extend(warehouse_errors(&tiles));
+    }
+
+    // The moves come after the warehouse and the blank line separating them
+    let first_line_index = input.lines().take_while(|line| !line.is_empty()).count() + 1;
+    let moves_part: Vec<&str> = input.lines().skip(first_line_index).collect();
+    let moves = parse_moves(first_line_index, &moves_part).unwrap_or_else(|move_errors| {
+        errors.extend(move_errors);
+        vec![]
+    });
+
+    match warehouse {
+        Some(warehouse) if errors.is_empty() => Ok(Input { warehouse, moves }),
+        _ => {
+            errors.sort_by_key(|error| (error.line, error.column));
+            Err(errors)
         }
-        println!();
     }
 }
 
-fn get_next_move(move_pos: (usize, usize), move_dir: &Move) -> (usize, usize) {
-    let (move_x, move_y) = move_pos;
-    match move_dir {
-        Move::Up => (move_x, move_y - 1),
-        Move::Down => (move_x, move_y + 1),
-        Move::Left => (move_x - 1, move_y),
-        Move::Right => (move_x + 1, move_y),
+/// A `size` by `size` warehouse with walls round the edge, a few inside and boxes scattered
+/// about, followed by `size * 10` moves
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(3);
+    let robot = (
+        rng.between(1, size as i64 - 2),
+        rng.between(1, size as i64 - 2),
+    );
+    let mut input = rng.grid(size, size, |rng, (row, col)| {
+        if row == 0 || col == 0 || row == size - 1 || col == size - 1 {
+            '#'
+        } else if (row as i64, col as i64) == robot {
+            '@'
+        } else {
+            match rng.below(10) {
+                0 => '#',
+                1..=3 => 'O',
+                _ => '.',
+            }
+        }
+    });
+
+    let moves: Vec<char> = (0..size * 10)
+        .map(|_| rng.pick(&['^', 'v', '<', '>']))
+        .collect();
+    for line in moves.chunks(70) {
+        input.push('\n');
+        input.extend(line);
     }
+    input.push('\n');
+    input
 }
 
-fn perform_move(warehouse: &mut [Vec<char>], robot_pos: &mut (usize, usize), move_dir: &Move) {
-    //println!("Move: {:?}", move_dir);
-    let (robot_x, robot_y) = robot_pos;
-    let mut maybe_next_move = Some(get_next_move((*robot_x, *robot_y), move_dir));
+fn perform_move(warehouse: &mut Grid<char>, robot_pos: &mut Point, move_dir: Direction) {
+    trace_decision!(Debug, "moving {:?}", move_dir);
+    let step = Vec2::from(move_dir);
+    let mut maybe_next_move = Some(*robot_pos + step);
     while let Some(next_move) = maybe_next_move {
-        let (next_x, next_y) = next_move;
-        match warehouse[next_y][next_x] {
+        match warehouse[next_move] {
             '.' => {
-                warehouse[*robot_y][*robot_x] = '.';
-                warehouse[next_y][next_x] = '@';
-                *robot_x = next_x;
-                *robot_y = next_y;
+                warehouse[*robot_pos] = '.';
+                warehouse[next_move] = '@';
+                *robot_pos = next_move;
                 maybe_next_move = None;
             }
             'O' => {
-                let (peek_x, peek_y) = get_next_move((next_x, next_y), move_dir);
-                match warehouse[peek_y][peek_x] {
+                let peek = next_move + step;
+                match warehouse[peek] {
                     '.' => {
-                        warehouse[next_y][next_x] = '.';
-                        warehouse[peek_y][peek_x] = 'O';
-                        maybe_next_move = Some(get_next_move((*robot_x, *robot_y), move_dir))
+                        warehouse[next_move] = '.';
+                        warehouse[peek] = 'O';
+                        maybe_next_move = Some(*robot_pos + step)
                     }
-                    'O' => maybe_next_move = Some((peek_x, peek_y)),
+                    'O' => maybe_next_move = Some(peek),
                     _ => maybe_next_move = None,
                 }
             }
@@ -116,316 +181,261 @@ fn perform_move(warehouse: &mut [Vec<char>], robot_pos: &mut (usize, usize), mov
         }
     }
 
-    //print_warehouse(warehouse);
-    //println!();
+    trace_state!(Trace, "warehouse", warehouse);
 }
 
-fn get_robot_pos(warehouse: &[Vec<char>]) -> (usize, usize) {
-    for (y, row) in warehouse.iter().enumerate() {
-        for (x, entry) in row.iter().enumerate() {
-            if *entry == '@' {
-                return (x, y);
-            }
-        }
-    }
-    panic!("Robot not found in the warehouse");
+fn get_robot_pos(warehouse: &Grid<char>) -> Point {
+    let pos = warehouse
+        .position(&'@')
+        .expect("Robot not found in the warehouse");
+    Point::from_row_col(pos)
 }
 
-fn get_sum_gps(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
+pub fn get_sum_gps(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_gps(&parse_input_file(input_file, parse_input)?))
+}
 
+/// Part one: the sum of every box's GPS coordinate, 100 times its row plus its column
+pub fn sum_gps(input: &Input) -> u32 {
     let mut warehouse = input.warehouse.clone();
 
-    let mut robot_pos: (usize, usize) = get_robot_pos(warehouse.as_slice());
+    let mut robot_pos = get_robot_pos(&warehouse);
 
-    /*println!("Initial robot pos: {:?}", robot_pos);
-    println!("Initial state:");
-    print_warehouse(&warehouse);
-    println!();*/
+    trace_decision!(Debug, "Initial robot pos: {}", robot_pos);
+    trace_state!(Debug, "initial state", warehouse);
 
     for move_dir in &input.moves {
-        perform_move(&mut warehouse, &mut robot_pos, move_dir);
+        perform_move(&mut warehouse, &mut robot_pos, *move_dir);
     }
 
-    warehouse.iter().enumerate().fold(0, |acc, (i, row)| {
-        acc + row
-            .iter()
-            .enumerate()
-            .fold(0, |acc, (j, entry)| match entry {
-                'O' => acc + (100 * i as u32 + j as u32),
-                _ => acc,
-            })
-    })
+    warehouse
+        .positions_of(&'O')
+        .map(|(row, col)| 100 * row as u32 + col as u32)
+        .sum()
 }
 
-fn widen_warehouse(warehouse: &[Vec<char>]) -> Vec<Vec<char>> {
-    warehouse.iter().fold(vec![], |mut acc, row| {
-        let mut new_row: Vec<char> = vec![];
-        for entry in row {
-            match entry {
-                '.' => {
-                    new_row.push('.');
-                    new_row.push('.');
-                }
-                '#' => {
-                    new_row.push('#');
-                    new_row.push('#');
-                }
-                'O' => {
-                    new_row.push('[');
-                    new_row.push(']');
-                }
-                '@' => {
-                    new_row.push('@');
-                    new_row.push('.');
+fn widen_warehouse(warehouse: &Grid<char>) -> Grid<char> {
+    let rows = warehouse
+        .rows()
+        .map(|row| {
+            let mut new_row: Vec<char> = vec![];
+            for entry in row {
+                match entry {
+                    '.' => {
+                        new_row.push('.');
+                        new_row.push('.');
+                    }
+                    '#' => {
+                        new_row.push('#');
+                        new_row.push('#');
+                    }
+                    'O' => {
+                        new_row.push('[');
+                        new_row.push(']');
+                    }
+                    '@' => {
+                        new_row.push('@');
+                        new_row.push('.');
+                    }
+                    _ => panic!("Unknown entry"),
                 }
-                _ => panic!("Unknown entry"),
             }
-        }
-        acc.push(new_row);
-        acc
-    })
+            new_row
+        })
+        .collect();
+    Grid::from_rows(rows).expect("every row is widened the same amount")
 }
 
-fn perform_move_wider(
-    warehouse: &mut [Vec<char>],
-    robot_pos: &mut (usize, usize),
-    move_dir: &Move,
-) -> Option<(usize, usize)> {
-    println!("Move: {:?}", move_dir);
-
-    // Stores indices of warehouse cells for BFS
-    let mut move_queue: VecDeque<(usize, usize)> = VecDeque::new();
-
-    // Stores visited cells for BFS
-    let mut visited_moves: HashSet<(usize, usize)> = HashSet::new();
-
-    // Mark the starting cell as visited and push it to the queue
-    visited_moves.insert(*robot_pos);
-    move_queue.push_front(*robot_pos);
-
-    while let Some(move_pos) = move_queue.pop_front() {
-        if let Some(adj_moves) = get_adj_moves(move_pos, move_dir) {
-            for adj_move_pos in &adj_moves {
-                if is_box_move_possible(warehouse, &visited_moves, *adj_move_pos) {
-                    let (adj_move_x, adj_move_y) = adj_move_pos;
-                    let adj_move = warehouse[*adj_move_y][*adj_move_x];
-                    println!(
-                        "Visiting box {:?} at {:?}, move_dir: {:?}",
-                        adj_move, adj_move_pos, move_dir
-                    );
-
-                    move_queue.push_front(*adj_move_pos);
-                    visited_moves.insert(*adj_move_pos);
-                }
-            }
-        } else {
-            continue;
+fn perform_move_wider(warehouse: &mut Grid<char>, robot_pos: &mut Point, move_dir: Direction) {
+    trace_decision!(Debug, "moving {:?}", move_dir);
+    let step = Vec2::from(move_dir);
+
+    // Everything the robot pushes, found by following what each thing pushes in turn
+    let pushes = |&pos: &Point| -> Vec<Point> {
+        let next_pos = pos + step;
+        match warehouse[next_pos] {
+            // Both halves of a box are pushed together
+            '[' => vec![next_pos, next_pos + Direction::E.into()],
+            ']' => vec![next_pos, next_pos + Direction::W.into()],
+            _ => vec![],
         }
-    }
-
-    print_warehouse(warehouse);
-    println!();
-
-    Some(*robot_pos)
-}
-
-fn is_box_move_possible(
-    warehouse: &[Vec<char>],
-    visited_moves: &HashSet<(usize, usize)>,
-    move_pos: (usize, usize),
-) -> bool {
-    let (move_x, move_y) = move_pos;
+    };
+    let visited_moves: Vec<Point> = bfs(&pushes, *robot_pos).map(|(pos, _)| pos).collect();
 
-    // If cell is out of bounds
-    if move_x >= warehouse[0].len() || move_y >= warehouse.len() {
-        return false;
+    // Anything pushing into a wall means nothing moves
+    if visited_moves
+        .iter()
+        .any(|&pos| warehouse[pos + step] == '#')
+    {
+        return;
     }
 
-    // If cell is visited
-    if visited_moves.contains(&move_pos) {
-        return false;
+    let moved_entries: Vec<(Point, char)> = visited_moves
+        .iter()
+        .map(|&pos| (pos, warehouse[pos]))
+        .collect();
+    for (pos, _) in &moved_entries {
+        warehouse[*pos] = '.';
     }
-
-    // If cell is a wall
-    if warehouse[move_y][move_x] == '#' {
-        return false;
+    for (pos, entry) in moved_entries {
+        warehouse[pos + step] = entry;
     }
+    *robot_pos += step;
 
-    // Otherwise can be visited
-    return true;
+    trace_state!(Trace, "warehouse", warehouse);
 }
 
-fn get_adj_moves(move_pos: (usize, usize), move_dir: &Move) -> Option<Vec<(usize, usize)>> {
-    let (move_x, move_y) = move_pos;
-    let mut adj_moves = vec![];
-
-    let potential_moves = match move_dir {
-        Move::Up => vec![
-            (move_x - 1, move_y - 1),
-            (move_x, move_y - 1),
-            (move_x + 1, move_y - 1),
-        ],
-        Move::Down => vec![
-            (move_x - 1, move_y + 1),
-            (move_x, move_y + 1),
-            (move_x + 1, move_y + 1),
-        ],
-        Move::Left => vec![(move_x - 1, move_y)],
-        Move::Right => vec![(move_x + 1, move_y)],
-    };
-
-    for potential_move in potential_moves {
-        adj_moves.push(potential_move);
-    }
-
-    if adj_moves.is_empty() {
-        None
-    } else {
-        Some(adj_moves)
-    }
+pub fn get_sum_gps_wider(input_file: &str) -> Result<u32, Error> {
+    Ok(sum_gps_wider(&parse_input_file(input_file, parse_input)?))
 }
 
-fn get_sum_gps_wider(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
-
+/// Part two: as for part one, in a warehouse where everything but the robot is twice as wide
+pub fn sum_gps_wider(input: &Input) -> u32 {
     let mut warehouse_wider = widen_warehouse(&input.warehouse);
 
-    let mut robot_pos: (usize, usize) = get_robot_pos(&warehouse_wider);
+    let mut robot_pos = get_robot_pos(&warehouse_wider);
 
-    println!("Initial robot pos: {:?}", robot_pos);
-    println!("Initial state:");
-    print_warehouse(&warehouse_wider);
-    println!();
+    trace_decision!(Debug, "Initial robot pos: {}", robot_pos);
+    trace_state!(Debug, "initial state", warehouse_wider);
 
     for move_dir in &input.moves {
-        perform_move_wider(&mut warehouse_wider, &mut robot_pos, move_dir);
+        perform_move_wider(&mut warehouse_wider, &mut robot_pos, *move_dir);
     }
 
-    warehouse_wider.iter().enumerate().fold(0, |acc, (i, row)| {
-        acc + row
-            .iter()
-            .enumerate()
-            .fold(0, |acc, (j, entry)| match entry {
-                '[' => acc + (100 * i as u32 + j as u32),
-                _ => acc,
-            })
-    })
+    warehouse_wider
+        .positions_of(&'[')
+        .map(|(row, col)| 100 * row as u32 + col as u32)
+        .sum()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_sum_gps_test01() {
-        assert_eq!(10092, get_sum_gps("input/day15_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test02() {
-        assert_eq!(2028, get_sum_gps("input/day15_test02.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test03() {
-        // Down
-        assert_eq!(1624, get_sum_gps("input/day15_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test04() {
-        // Right
-        assert_eq!(1626, get_sum_gps("input/day15_test04.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test05() {
-        // Down
-        assert_eq!(2024, get_sum_gps("input/day15_test05.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test06() {
-        // Left
-        assert_eq!(1621, get_sum_gps("input/day15_test06.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test07() {
-        // Up
-        assert_eq!(1224, get_sum_gps("input/day15_test07.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_test08() {
-        // Right
-        assert_eq!(1627, get_sum_gps("input/day15_test08.txt"));
-    }
+fn warehouse_image(warehouse: &Grid<char>, robot_pos: Point, trail: &[Point]) -> Image {
+    let mut image = Image::from_grid(warehouse, |entry| match entry {
+        '#' => Rgb::GREY,
+        'O' | '[' | ']' => Rgb::BROWN,
+        '@' => Rgb::RED,
+        _ => Rgb::WHITE,
+    });
+    image.path(trail.iter().filter_map(|pos| pos.to_row_col()), Rgb::YELLOW);
+    if let Some(pos) = robot_pos.to_row_col() {
+        image.highlight(pos, Rgb::RED);
+    }
+    image
+}
 
-    #[test]
-    fn test_get_sum_gps() {
-        assert_eq!(1517819, get_sum_gps("input/day15.txt"));
+// How many of the robot's last positions are drawn behind it
+const TRAIL_LEN: usize = 8;
+
+/// The [`Solution`] for day 15: [`sum_gps`] and [`sum_gps_wider`]
+pub struct Day15;
+
+impl Day15 {
+    /// Saves the warehouse before the robot moves and after every move, with the robot's
+    /// last few steps drawn behind it. Part two moves around the wider warehouse.
+    pub fn render(&self, part: Part, input: &Input, frames: &mut Frames) -> Result<(), Error> {
+        let mut warehouse = match part {
+            Part::One => input.warehouse.clone(),
+            Part::Two => widen_warehouse(&input.warehouse),
+        };
+        let mut robot_pos = get_robot_pos(&warehouse);
+        let mut trail: VecDeque<Point> = VecDeque::from([robot_pos]);
+
+        frames.push(&warehouse_image(
+            &warehouse,
+            robot_pos,
+            trail.make_contiguous(),
+        ))?;
+        for &move_dir in &input.moves {
+            match part {
+                Part::One => perform_move(&mut warehouse, &mut robot_pos, move_dir),
+                Part::Two => perform_move_wider(&mut warehouse, &mut robot_pos, move_dir),
+            }
+            if trail.back() != Some(&robot_pos) {
+                trail.push_back(robot_pos);
+                if trail.len() > TRAIL_LEN {
+                    trail.pop_front();
+                }
+            }
+            frames.push(&warehouse_image(
+                &warehouse,
+                robot_pos,
+                trail.make_contiguous(),
+            ))?;
+        }
+        Ok(())
     }
+}
 
-    #[test]
-    fn test_get_sum_gps_wider_test01() {
-        assert_eq!(0, get_sum_gps_wider("input/day15_test01.txt"));
-    }
+impl Solution for Day15 {
+    type Input = Input;
 
-    #[test]
-    fn test_get_sum_gps_wider_test02() {
-        assert_eq!(0, get_sum_gps_wider("input/day15_test02.txt"));
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
     }
 
-    #[test]
-    fn test_get_sum_gps_wider_test03() {
-        // Down
-        assert_eq!(0, get_sum_gps_wider("input/day15_test03.txt"));
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        validate_input(input)
     }
 
-    #[test]
-    fn test_get_sum_gps_wider_test04() {
-        // Right
-        assert_eq!(0, get_sum_gps_wider("input/day15_test04.txt"));
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_gps(input))
     }
 
-    #[test]
-    fn test_get_sum_gps_wider_test05() {
-        // Down
-        assert_eq!(0, get_sum_gps_wider("input/day15_test05.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_wider_test06() {
-        // Left
-        assert_eq!(0, get_sum_gps_wider("input/day15_test06.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_gps_wider_test07() {
-        // Up
-        assert_eq!(0, get_sum_gps_wider("input/day15_test07.txt"));
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(sum_gps_wider(input))
     }
+}
 
-    #[test]
-    fn test_get_sum_gps_wider_test08() {
-        // Right
-        assert_eq!(0, get_sum_gps_wider("input/day15_test08.txt"));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
-    fn test_get_sum_gps_wider_test09() {
-        assert_eq!(9021, get_sum_gps_wider("input/day15_test09.txt"));
+    fn test_get_sum_gps_invalid01() {
+        match get_sum_gps("input/day15_invalid01.txt") {
+            Err(Error::Parse { source, .. }) => assert_eq!(
+                ParseError::new(6, 2, "one of '^', 'v', '<' or '>'", "X"),
+                source
+            ),
+            _ => panic!("expected a parse error"),
+        }
     }
 
     #[test]
-    fn test_get_sum_gps_wider_test10() {
-        assert_eq!(0, get_sum_gps_wider("input/day15_test10.txt"));
+    fn test_get_sum_gps_invalid02() {
+        match get_sum_gps("input/day15_invalid02.txt") {
+            Err(Error::Parse { source, .. }) => assert_eq!(
+                ParseError::new(2, 3, "one of '#', '.', 'O' or '@'", "x"),
+                source
+            ),
+            _ => panic!("expected a parse error"),
+        }
     }
 
     #[test]
-    fn test_get_sum_gps_wider() {
-        assert_eq!(0, get_sum_gps_wider("input/day15.txt"));
+    fn test_validate_input() {
+        assert_eq!(
+            vec![
+                ParseError::new(0, 0, "a robot '@'", ""),
+                ParseError::new(4, 2, "one of '^', 'v', '<' or '>'", "x"),
+            ],
+            validate_input("####\n#O.#\n####\n\n<^x>\n")
+        );
+        assert_eq!(
+            vec![
+                ParseError::new(1, 3, "a wall '#' round the edge", "."),
+                ParseError::new(2, 2, "only one robot", "@"),
+            ],
+            validate_input("####\n#@..\n#.@#\n####\n\n<\n")
+        );
+        assert_eq!(
+            vec![
+                ParseError::new(0, 0, "a robot '@'", ""),
+                ParseError::new(1, 2, "one of '#', '.', 'O' or '@'", "x"),
+                ParseError::new(2, 0, "a wall '#' round the edge", "."),
+                ParseError::new(2, 3, "one of '#', '.', 'O' or '@'", "y"),
+                ParseError::new(5, 0, "one of '^', 'v', '<' or '>'", "z"),
+            ],
+            validate_input("####\n#Ox#\n...y\n####\n\nz\n")
+        );
     }
 }
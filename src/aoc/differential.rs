@@ -0,0 +1,271 @@
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+
+use super::error::ParseError;
+use super::generate::generate;
+use super::solution::Part;
+use super::{day11, day12, day13};
+
+/// Parses an input and answers it
+type Solve = Box<dyn Fn(&str) -> Result<String, ParseError>>;
+
+/// One way of working out a part's answer, to be checked against the others
+pub struct Implementation {
+    pub name: &'static str,
+    solve: Solve,
+}
+
+impl Implementation {
+    /// Parses the input with `parse` then answers with `solve`
+    pub fn new<I: 'static, A: Display>(
+        name: &'static str,
+        parse: fn(&str) -> Result<I, ParseError>,
+        solve: impl Fn(&I) -> A + 'static,
+    ) -> Implementation {
+        Implementation {
+            name,
+            solve: Box::new(move |input| Ok(solve(&parse(input)?).to_string())),
+        }
+    }
+
+    pub fn solve(&self, input: &str) -> Result<String, ParseError> {
+        (self.solve)(input)
+    }
+}
+
+/// The implementations registered for a day's part, usually the solution itself and a
+/// slower but more obviously right one. Empty if there's nothing to compare.
+pub fn implementations(day: u8, part: Part) -> Vec<Implementation> {
+    match day {
+        11 => day11::implementations(part),
+        12 => day12::implementations(part),
+        13 => day13::implementations(part),
+        _ => vec![],
+    }
+}
+
+/// An input the implementations disagree on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The size and seed of the generated input which first showed the disagreement
+    pub size: usize,
+    pub seed: u64,
+    /// The smallest input found which still shows it, cut down from the generated one
+    pub input: String,
+    /// Each implementation's name and answer for `input`
+    pub answers: Vec<(&'static str, String)>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "implementations disagree (found with size {}, seed {}) on:",
+            self.size, self.seed
+        )?;
+        writeln!(f, "{}", self.input.trim_end())?;
+        for (name, answer) in &self.answers {
+            writeln!(f, "  {}: {}", name, answer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `implementations` agree on `day`'s generated inputs, trying seeds `0..seeds`
+/// for each size from 1 up to `max_size` so that the first disagreement is on as small an
+/// input as possible. That input is then cut down further with [`shrink`]. Returns how
+/// many inputs were checked.
+pub fn cross_check(
+    day: u8,
+    implementations: &[Implementation],
+    max_size: usize,
+    seeds: u64,
+) -> Result<usize, Mismatch> {
+    let mut checked = 0;
+    for size in 1..=max_size {
+        for seed in 0..seeds {
+            let Some(input) = generate(day, size, seed) else {
+                return Ok(checked);
+            };
+            if disagreement(implementations, &input).is_some() {
+                let (input, answers) = shrink(implementations, &input).unwrap();
+                return Err(Mismatch {
+                    size,
+                    seed,
+                    input,
+                    answers,
+                });
+            }
+            checked += 1;
+        }
+    }
+    Ok(checked)
+}
+
+/// Each implementation's answer to `input`, if they don't all agree. Inputs which don't
+/// parse say nothing about the implementations, so aren't disagreements.
+fn disagreement(
+    implementations: &[Implementation],
+    input: &str,
+) -> Option<Vec<(&'static str, String)>> {
+    let answers: Vec<(&'static str, String)> = implementations
+        .iter()
+        .map(|implementation| Some((implementation.name, implementation.solve(input).ok()?)))
+        .collect::<Option<_>>()?;
+    if answers.iter().map(|(_, answer)| answer).all_equal() {
+        None
+    } else {
+        Some(answers)
+    }
+}
+
+/// Cuts `input` down to one which the implementations still disagree on, by removing one
+/// paragraph, line or word at a time for as long as that keeps them disagreeing. `None`
+/// if they agree on `input` to begin with.
+pub fn shrink(
+    implementations: &[Implementation],
+    input: &str,
+) -> Option<(String, Vec<(&'static str, String)>)> {
+    let mut answers = disagreement(implementations, input)?;
+    let mut input = input.to_string();
+
+    'shrink: loop {
+        for smaller in smaller_inputs(&input) {
+            if let Some(smaller_answers) = disagreement(implementations, &smaller) {
+                input = smaller;
+                answers = smaller_answers;
+                continue 'shrink;
+            }
+        }
+        return Some((input, answers));
+    }
+}
+
+/// Every input made by removing one paragraph, line or word from `input`, biggest cuts first
+fn smaller_inputs(input: &str) -> Vec<String> {
+    let mut smaller = vec![];
+
+    let paragraphs: Vec<&str> = input.trim_end().split("\n\n").collect();
+    if paragraphs.len() > 1 {
+        for i in 0..paragraphs.len() {
+            let mut rest = paragraphs.clone();
+            rest.remove(i);
+            smaller.push(format!("{}\n", rest.join("\n\n")));
+        }
+    }
+
+    let lines: Vec<&str> = input.lines().collect();
+    if lines.len() > 1 {
+        for i in 0..lines.len() {
+            let mut rest = lines.clone();
+            rest.remove(i);
+            smaller.push(format!("{}\n", rest.join("\n")));
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() > 1 {
+            for j in 0..words.len() {
+                let mut rest = words.clone();
+                rest.remove(j);
+                let line = rest.join(" ");
+                let mut changed = lines.clone();
+                changed[i] = &line;
+                smaller.push(format!("{}\n", changed.join("\n")));
+            }
+        }
+    }
+    smaller
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_implementations_agree() {
+        for (day, part) in [
+            (11, Part::One),
+            (12, Part::One),
+            (12, Part::Two),
+            (13, Part::One),
+        ] {
+            let implementations = implementations(day, part);
+            assert!(implementations.len() > 1, "day {} part {}", day, part);
+
+            let checked = cross_check(day, &implementations, 6, 5);
+            assert_eq!(Ok(30), checked, "day {} part {}", day, part);
+        }
+    }
+
+    #[test]
+    fn test_cross_check_shrinks() {
+        let stones = |input: &day11::Input| input.stones.len();
+        let implementations = [
+            Implementation::new("all stones", day11::parse_input, stones),
+            // Wrong whenever there's more than one stone with an even number
+            Implementation::new("odd stones", day11::parse_input, |input: &day11::Input| {
+                input.stones.iter().filter(|&stone| stone % 2 == 1).count()
+                    + input.stones.iter().any(|&stone| stone % 2 == 0) as usize
+            }),
+        ];
+
+        let mismatch = cross_check(11, &implementations, 10, 10).unwrap_err();
+        assert_eq!(2, mismatch.size);
+
+        // Cut down to just the two even stones
+        let shrunk: Vec<usize> = day11::parse_input(&mismatch.input).unwrap().stones;
+        assert_eq!(2, shrunk.len());
+        assert!(shrunk.iter().all(|stone| stone % 2 == 0));
+        assert_eq!(
+            vec![
+                ("all stones", String::from("2")),
+                ("odd stones", String::from("1"))
+            ],
+            mismatch.answers
+        );
+    }
+
+    #[test]
+    fn test_shrink() {
+        let implementations = [
+            Implementation::new("all stones", day11::parse_input, |input: &day11::Input| {
+                input.stones.len()
+            }),
+            Implementation::new("no zeros", day11::parse_input, |input: &day11::Input| {
+                input.stones.iter().filter(|&&stone| stone != 0).count()
+            }),
+        ];
+
+        assert_eq!(None, shrink(&implementations, "1 2 3\n"));
+        assert_eq!(
+            Some((
+                String::from("0\n"),
+                vec![
+                    ("all stones", String::from("1")),
+                    ("no zeros", String::from("0"))
+                ]
+            )),
+            shrink(&implementations, "1 2\n3 0 4\n5\n")
+        );
+    }
+
+    #[test]
+    fn test_smaller_inputs() {
+        assert_eq!(
+            vec![
+                "c d\n",
+                "a\nb\n",
+                "b\n\nc d\n",
+                "a\n\nc d\n",
+                "a\nb\nc d\n",
+                "a\nb\n\n",
+                "a\nb\n\nd\n",
+                "a\nb\n\nc\n"
+            ],
+            smaller_inputs("a\nb\n\nc d\n")
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::{error, fmt, io};
+
+use super::cancel::TimedOut;
+
+/// Where and why an input couldn't be parsed. Lines and columns are one based, to
+/// match what editors show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    /// Creates an error from the zero based line and column indices used when parsing
+    pub fn new(
+        line_index: usize,
+        column_index: usize,
+        expected: impl Into<String>,
+        found: impl Into<String>,
+    ) -> Self {
+        ParseError {
+            line: line_index + 1,
+            column: column_index + 1,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: expected {}, ",
+            self.line, self.column, self.expected
+        )?;
+        if self.found.is_empty() {
+            write!(f, "found nothing")
+        } else {
+            write!(f, "found {:?}", self.found)
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input file couldn't be read
+    Io { file: String, source: io::Error },
+    /// The input file was read, but isn't valid input for the puzzle
+    Parse { file: String, source: ParseError },
+    /// The solution gave up on the input before finding an answer
+    TimedOut { file: String, source: TimedOut },
+}
+
+impl Error {
+    pub fn io(file: &str, source: io::Error) -> Self {
+        Error::Io {
+            file: file.to_string(),
+            source,
+        }
+    }
+
+    pub fn parse(file: &str, source: ParseError) -> Self {
+        Error::Parse {
+            file: file.to_string(),
+            source,
+        }
+    }
+
+    pub fn timed_out(file: &str, source: TimedOut) -> Self {
+        Error::TimedOut {
+            file: file.to_string(),
+            source,
+        }
+    }
+
+    /// The error for a run of `file` which didn't give an answer
+    pub fn run(file: &str, source: RunError) -> Self {
+        match source {
+            RunError::Parse(source) => Error::parse(file, source),
+            RunError::TimedOut(source) => Error::timed_out(file, source),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { file, source } => write!(f, "couldn't open {}: {}", file, source),
+            Error::Parse { file, source } => write!(f, "{}:{}", file, source),
+            Error::TimedOut { file, source } => write!(f, "{}: {}", file, source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+            Error::TimedOut { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Why a solution didn't give an answer for an input it was handed directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    Parse(ParseError),
+    TimedOut(TimedOut),
+}
+
+impl From<ParseError> for RunError {
+    fn from(error: ParseError) -> Self {
+        RunError::Parse(error)
+    }
+}
+
+impl From<TimedOut> for RunError {
+    fn from(error: TimedOut) -> Self {
+        RunError::TimedOut(error)
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Parse(error) => write!(f, "{}", error),
+            RunError::TimedOut(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for RunError {}
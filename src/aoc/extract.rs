@@ -0,0 +1,290 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use super::manifest::{Answer, Manifest};
+use super::solution::Part;
+
+lazy_static! {
+    static ref ARTICLE: Regex =
+        Regex::new(r#"(?s)<article class="day-desc">(.*?)</article>"#).unwrap();
+    static ref EXAMPLE_OR_ANSWER: Regex =
+        Regex::new(r"(?s)<pre><code>(.*?)</code></pre>|<code><em>(.*?)</em></code>").unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+    static ref TITLE: Regex = Regex::new(r"--- Day (\d+): (.*?) ---").unwrap();
+}
+
+/// An example input from a puzzle page, with the answers highlighted after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    /// Possible answers to part one for the example, in the order they appear. The
+    /// answer itself is usually the last, the others being steps along the way.
+    pub part1: Vec<Answer>,
+    pub part2: Vec<Answer>,
+}
+
+impl Example {
+    fn answers(&mut self, part: Part) -> &mut Vec<Answer> {
+        match part {
+            Part::One => &mut self.part1,
+            Part::Two => &mut self.part2,
+        }
+    }
+}
+
+/// An example saved by [`save_examples`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saved {
+    pub input_file: String,
+    /// False if the example was already saved in `input_file`
+    pub written: bool,
+    /// Whether an answer was added to the manifest for it
+    pub recorded: bool,
+}
+
+/// The day a saved puzzle page is for, going by its title
+pub fn page_day(html: &str) -> Option<u8> {
+    TITLE.captures(html)?[1].parse().ok()
+}
+
+/// Finds the `<pre><code>` examples in a saved puzzle page, and the `<code><em>` answers
+/// following each one. Part two's description often goes back to part one's example, so
+/// its answers go with the last example seen, and an example given in both parts is
+/// only returned once.
+pub fn extract_examples(html: &str) -> Vec<Example> {
+    let articles: Vec<&str> = ARTICLE
+        .captures_iter(html)
+        .map(|captures| captures.get(1).unwrap().as_str())
+        .collect();
+    // A page saved before solving part one has one article, and some pages aren't
+    // marked up as articles at all
+    let articles = if articles.is_empty() {
+        vec![html]
+    } else {
+        articles
+    };
+
+    let mut examples: Vec<Example> = vec![];
+    let mut current = None;
+    for (article, part) in articles.into_iter().zip(Part::ALL) {
+        for captures in EXAMPLE_OR_ANSWER.captures_iter(article) {
+            if let Some(example) = captures.get(1) {
+                let input = html_text(example.as_str());
+                current = Some(
+                    match examples.iter().position(|example| example.input == input) {
+                        Some(index) => index,
+                        None => {
+                            examples.push(Example {
+                                input,
+                                part1: vec![],
+                                part2: vec![],
+                            });
+                            examples.len() - 1
+                        }
+                    },
+                );
+            } else if let Some(answer) = captures.get(2)
+                && let Some(index) = current
+            {
+                let answer = html_text(answer.as_str());
+                let answer = match answer.parse() {
+                    Ok(number) => Answer::Number(number),
+                    Err(_) => Answer::Text(answer),
+                };
+                examples[index].answers(part).push(answer);
+            }
+        }
+    }
+    examples
+}
+
+/// The text an HTML fragment shows, without its tags
+fn html_text(fragment: &str) -> String {
+    TAG.replace_all(fragment, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Saves each example under `root` as the next free `input/dayNN_testNN.txt`, unless the
+/// same input is already saved, and appends an entry to `manifest_file` giving the most
+/// likely answer for each part. Examples the manifest already has an entry for are left
+/// out of it, so that extracting the same page again changes nothing.
+pub fn save_examples(
+    root: &Path,
+    day: u8,
+    examples: &[Example],
+    manifest_file: &str,
+) -> Result<Vec<Saved>, String> {
+    let manifest_path = root.join(manifest_file);
+    let manifest = match fs::read_to_string(&manifest_path) {
+        Ok(contents) => {
+            Manifest::parse(&contents).map_err(|error| format!("{}:{}", manifest_file, error))?
+        }
+        Err(_) => Manifest::default(),
+    };
+    let mut entries = String::new();
+    let mut saved = vec![];
+
+    for example in examples {
+        let (input_file, written) = save_example(root, day, &example.input)?;
+        let part1 = example.part1.last();
+        let part2 = example.part2.last();
+        let recorded = (part1.is_some() || part2.is_some())
+            && !manifest
+                .answers
+                .iter()
+                .any(|expected| expected.day == day && expected.input == input_file);
+
+        if recorded {
+            entries.push_str(&format!(
+                "\n[[answer]]\nday = {}\ninput = {:?}\n",
+                day, input_file
+            ));
+            for (part, answer) in [("part1", part1), ("part2", part2)] {
+                if let Some(answer) = answer {
+                    entries.push_str(&format!("{} = {}\n", part, toml_answer(answer)));
+                }
+            }
+        }
+        saved.push(Saved {
+            input_file,
+            written,
+            recorded,
+        });
+    }
+
+    if !entries.is_empty() {
+        let mut contents = fs::read_to_string(&manifest_path).unwrap_or_default();
+        contents.push_str(&entries);
+        fs::write(&manifest_path, contents)
+            .map_err(|why| format!("couldn't write {}: {}", manifest_file, why))?;
+    }
+    Ok(saved)
+}
+
+fn toml_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Number(number) => number.to_string(),
+        Answer::Text(text) => toml::Value::from(text.as_str()).to_string(),
+    }
+}
+
+/// Finds the test input already holding `input`, or writes it to the next free one.
+/// Returns the file relative to `root`, and whether it had to be written.
+fn save_example(root: &Path, day: u8, input: &str) -> Result<(String, bool), String> {
+    let test_file = |n: usize| format!("input/day{:02}_test{:02}.txt", day, n);
+
+    let mut n = 1;
+    loop {
+        let path: PathBuf = root.join(test_file(n));
+        match fs::read_to_string(&path) {
+            // Hand pasted inputs don't always end in a newline
+            Ok(existing) if existing.trim_end() == input.trim_end() => {
+                return Ok((test_file(n), false));
+            }
+            Ok(_) => n += 1,
+            Err(_) if path.exists() => {
+                return Err(format!("couldn't read {}", test_file(n)));
+            }
+            Err(_) => break,
+        }
+    }
+
+    fs::write(root.join(test_file(n)), input)
+        .map_err(|why| format!("couldn't write {}: {}", test_file(n), why))?;
+    Ok((test_file(n), true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_examples_day01() {
+        let html = fs::read_to_string("fixtures/day01.html").unwrap();
+        let examples = extract_examples(&html);
+
+        assert_eq!(Some(1), page_day(&html));
+        // Part two repeats part one's example, which is only returned once
+        assert_eq!(1, examples.len());
+        assert_eq!(
+            "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n",
+            examples[0].input
+        );
+        assert_eq!(
+            vec![Answer::Number(2), Answer::Number(1), Answer::Number(11)],
+            examples[0].part1
+        );
+        assert_eq!(vec![Answer::Number(31)], examples[0].part2);
+    }
+
+    #[test]
+    fn test_extract_examples_day03() {
+        let html = fs::read_to_string("fixtures/day03.html").unwrap();
+        let examples = extract_examples(&html);
+
+        assert_eq!(2, examples.len());
+        assert_eq!(
+            fs::read_to_string("input/day03_test01.txt").unwrap(),
+            examples[0].input
+        );
+        assert_eq!(vec![Answer::Number(161)], examples[0].part1);
+        assert_eq!(
+            fs::read_to_string("input/day03_test02.txt").unwrap(),
+            examples[1].input
+        );
+        assert_eq!(Vec::<Answer>::new(), examples[1].part1);
+        assert_eq!(vec![Answer::Number(48)], examples[1].part2);
+    }
+
+    #[test]
+    fn test_save_examples() {
+        let root = std::env::temp_dir().join(format!("aoc_extract_{}", std::process::id()));
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::copy(
+            "input/day03_test01.txt",
+            root.join("input/day03_test01.txt"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("answers.toml"),
+            "[[answer]]\nday = 3\ninput = \"input/day03_test01.txt\"\npart1 = 161\n",
+        )
+        .unwrap();
+
+        let html = fs::read_to_string("fixtures/day03.html").unwrap();
+        let examples = extract_examples(&html);
+        let saved = save_examples(&root, 3, &examples, "answers.toml").unwrap();
+
+        assert_eq!(
+            vec![
+                Saved {
+                    input_file: String::from("input/day03_test01.txt"),
+                    written: false,
+                    recorded: false,
+                },
+                Saved {
+                    input_file: String::from("input/day03_test02.txt"),
+                    written: true,
+                    recorded: true,
+                },
+            ],
+            saved
+        );
+        let manifest = Manifest::load(&root.join("answers.toml").to_string_lossy()).unwrap();
+        assert_eq!(2, manifest.answers.len());
+        assert_eq!(Some(Answer::Number(48)), manifest.answers[1].part2);
+
+        // Saving again finds everything already there
+        let saved = save_examples(&root, 3, &examples, "answers.toml").unwrap();
+        assert!(saved.iter().all(|saved| !saved.written && !saved.recorded));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
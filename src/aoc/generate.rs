@@ -0,0 +1,157 @@
+use super::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15,
+};
+
+/// A small seeded random number generator (SplitMix64). Unlike a library generator its
+/// output for a seed will never change, so a day, size and seed are all it takes to
+/// share an input.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including `n`, which mustn't be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "nothing is below 0");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// A number from `low` to `high` inclusive
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below((high - low + 1) as usize) as i64
+    }
+
+    /// True with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// A `width` by `height` grid of characters, one row per line
+    pub fn grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        mut tile: impl FnMut(&mut Rng, (usize, usize)) -> char,
+    ) -> String {
+        let mut grid = String::with_capacity((width + 1) * height);
+        for row in 0..height {
+            for col in 0..width {
+                grid.push(tile(self, (row, col)));
+            }
+            grid.push('\n');
+        }
+        grid
+    }
+}
+
+/// A valid input for `day`, the same every time for the same `size` and `seed`. What size
+/// means depends on the day: the number of lines, records or robots, or a grid's width.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let size = size.max(1);
+    let input = match day {
+        1 => day01::generate_input(rng, size),
+        2 => day02::generate_input(rng, size),
+        3 => day03::generate_input(rng, size),
+        4 => day04::generate_input(rng, size),
+        5 => day05::generate_input(rng, size),
+        6 => day06::generate_input(rng, size),
+        7 => day07::generate_input(rng, size),
+        8 => day08::generate_input(rng, size),
+        9 => day09::generate_input(rng, size),
+        10 => day10::generate_input(rng, size),
+        11 => day11::generate_input(rng, size),
+        12 => day12::generate_input(rng, size),
+        13 => day13::generate_input(rng, size),
+        14 => day14::generate_input(rng, size),
+        15 => day15::generate_input(rng, size),
+        _ => return None,
+    };
+    Some(input)
+}
+
+/// Days which have a generator
+pub fn generated_days() -> impl Iterator<Item = u8> {
+    (1..=25).filter(|&day| generate(day, 1, 0).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::aoc::{Cancel, Part, get_solution};
+
+    #[test]
+    fn test_rng_repeatable() {
+        let mut rng = Rng::new(42);
+        let first: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+
+        // These must never change, or shared seeds would stop giving the same inputs
+        assert_eq!(
+            vec![0xbdd732262feb6e95, 0x28efe333b266f103, 0x47526757130f9f52],
+            first
+        );
+        assert_eq!(generate(9, 20, 7), generate(9, 20, 7));
+        assert_ne!(generate(9, 20, 7), generate(9, 20, 8));
+    }
+
+    #[test]
+    fn test_rng_ranges() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.between(-3, 3)));
+            assert!(rng.below(5) < 5);
+        }
+        let mut items = [1, 2, 3, 4, 5];
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!([1, 2, 3, 4, 5], items);
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for day in generated_days() {
+            let solution = get_solution(day).unwrap();
+            for seed in 0..4 {
+                let input = generate(day, 12, seed).unwrap();
+                for part in Part::ALL {
+                    let cancel = Cancel::after(Duration::from_secs(30));
+                    assert!(
+                        solution.run_within(part, &input, &cancel).is_ok(),
+                        "day {} part {} seed {}:\n{}",
+                        day,
+                        part,
+                        seed,
+                        input
+                    );
+                }
+            }
+        }
+        assert_eq!(15, generated_days().count());
+    }
+}
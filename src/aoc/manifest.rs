@@ -0,0 +1,361 @@
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use super::cache::Cache;
+use super::cancel::Cancel;
+use super::error::{Error, ParseError, RunError};
+use super::solution::{Part, Runner};
+use super::utils::read_input_file;
+use super::{Params, get_solution_with_params, params_to_string};
+
+/// The expected answers for a set of inputs, loaded from a TOML file of `[[answer]]` tables
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, rename = "answer")]
+    pub answers: Vec<Expected>,
+}
+
+/// The answers one input should give, when solved with `params` overriding the defaults
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expected {
+    pub day: u8,
+    pub input: String,
+    #[serde(default)]
+    pub params: Params,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+    /// Takes too long to check every time
+    #[serde(default)]
+    pub slow: bool,
+}
+
+/// Answers are mostly numbers, but can be written as strings for those that aren't
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl Manifest {
+    pub fn load(manifest_file: &str) -> Result<Manifest, Error> {
+        let contents = read_input_file(manifest_file)?;
+        Manifest::parse(&contents).map_err(|error| Error::parse(manifest_file, error))
+    }
+
+    pub fn parse(input: &str) -> Result<Manifest, ParseError> {
+        toml::from_str(input).map_err(|error| {
+            // Point at the start of whatever toml couldn't make sense of
+            let offset = error.span().map_or(0, |span| span.start);
+            let before = &input[..offset];
+            let line_index = before.matches('\n').count();
+            let column_index = offset - before.rfind('\n').map_or(0, |i| i + 1);
+            ParseError::new(
+                line_index,
+                column_index,
+                "a valid answer manifest",
+                error.message().trim_end(),
+            )
+        })
+    }
+}
+
+impl Expected {
+    fn answer(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        }
+    }
+
+    /// The parameters written out the way they are in the manifest, e.g. `width = 11`
+    pub fn params_to_string(&self) -> String {
+        params_to_string(&self.params)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        actual: String,
+    },
+    /// The input or parameters were bad, or the solution panicked
+    Error(String),
+    /// The solution ran out of time before giving an answer
+    TimedOut,
+}
+
+/// The result of checking one part of an [`Expected`] against its answer
+#[derive(Debug)]
+pub struct Outcome<'a> {
+    pub expected: &'a Expected,
+    pub part: Part,
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+    /// The answer came from the cache rather than being solved
+    pub cached: bool,
+}
+
+impl Outcome<'_> {
+    pub fn answer(&self) -> &Answer {
+        self.expected
+            .answer(self.part)
+            .expect("outcomes are only made for parts with an answer")
+    }
+
+    pub fn passed(&self) -> bool {
+        self.verdict == Verdict::Pass
+    }
+}
+
+/// Solves every part which has an expected answer and says whether it matched, giving
+/// each part up to `budget` to find its answer. Answers in `cache` are used rather than
+/// solved again, and new ones are saved to it.
+pub fn verify<'a>(
+    answers: impl IntoIterator<Item = &'a Expected>,
+    budget: Option<Duration>,
+    cache: Option<&Cache>,
+) -> Vec<Outcome<'a>> {
+    let mut outcomes = vec![];
+
+    for expected in answers {
+        let parts = Part::ALL
+            .into_iter()
+            .filter(|&part| expected.answer(part).is_some());
+
+        let solution = get_solution_with_params(expected.day, &expected.params);
+        let input = read_input_file(&expected.input);
+
+        for part in parts {
+            let start = Instant::now();
+            let mut cached = false;
+            let actual = match (&solution, &input) {
+                (Err(error), _) => Err(Verdict::Error(error.clone())),
+                (_, Err(error)) => Err(Verdict::Error(error.to_string())),
+                (Ok(solution), Ok(input)) => {
+                    let saved = cache
+                        .and_then(|cache| cache.get(expected.day, part, &expected.params, input));
+                    cached = saved.is_some();
+                    match saved {
+                        Some(actual) => Ok(actual),
+                        None => {
+                            let actual = solve(solution.as_ref(), part, input, budget, expected);
+                            if let (Some(cache), Ok(actual)) = (cache, &actual) {
+                                // Failing to save only costs solving it again next time
+                                let _ =
+                                    cache.put(expected.day, part, &expected.params, input, actual);
+                            }
+                            actual
+                        }
+                    }
+                }
+            };
+            let verdict = match actual {
+                Ok(actual) if actual == expected.answer(part).unwrap().to_string() => Verdict::Pass,
+                Ok(actual) => Verdict::Fail { actual },
+                Err(verdict) => verdict,
+            };
+
+            outcomes.push(Outcome {
+                expected,
+                part,
+                verdict,
+                elapsed: start.elapsed(),
+                cached,
+            });
+        }
+    }
+
+    outcomes
+}
+
+/// The answer to one part, or the verdict if there isn't one
+fn solve(
+    solution: &dyn Runner,
+    part: Part,
+    input: &str,
+    budget: Option<Duration>,
+    expected: &Expected,
+) -> Result<String, Verdict> {
+    let cancel = Cancel::within(budget);
+    match panic::catch_unwind(AssertUnwindSafe(|| {
+        solution.run_within(part, input, &cancel)
+    })) {
+        Ok(Ok(actual)) => Ok(actual),
+        Ok(Err(RunError::Parse(error))) => Err(Verdict::Error(
+            Error::parse(&expected.input, error).to_string(),
+        )),
+        Ok(Err(RunError::TimedOut(_))) => Err(Verdict::TimedOut),
+        Err(_) => Err(Verdict::Error(String::from("panicked"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks every answer in the manifest which is or isn't slow, so that it's the one
+    /// place the answers are written down
+    fn verify_manifest(slow: bool) {
+        let manifest = Manifest::load("answers.toml").unwrap();
+        // Generous, so that only a solution which has started looping forever fails
+        let outcomes = verify(
+            manifest
+                .answers
+                .iter()
+                .filter(|expected| expected.slow == slow),
+            Some(Duration::from_secs(120)),
+            None,
+        );
+
+        let failures: Vec<String> = outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed())
+            .map(|outcome| {
+                format!(
+                    "day {} part {} on {}: {:?}",
+                    outcome.expected.day, outcome.part, outcome.expected.input, outcome.verdict
+                )
+            })
+            .collect();
+        assert!(!outcomes.is_empty());
+        assert_eq!(Vec::<String>::new(), failures);
+    }
+
+    #[test]
+    fn test_verify_manifest() {
+        verify_manifest(false);
+    }
+
+    // These take a while so ignore in CI
+    #[ignore]
+    #[test]
+    fn test_verify_manifest_slow() {
+        verify_manifest(true);
+    }
+
+    #[test]
+    fn test_verify_fail() {
+        let manifest = Manifest::parse(
+            r#"
+            [[answer]]
+            day = 1
+            input = "input/day01_test01.txt"
+            part1 = 11
+            part2 = 32
+            "#,
+        )
+        .unwrap();
+        let verdicts: Vec<Verdict> = verify(&manifest.answers, None, None)
+            .into_iter()
+            .map(|outcome| outcome.verdict)
+            .collect();
+
+        assert_eq!(
+            vec![
+                Verdict::Pass,
+                Verdict::Fail {
+                    actual: String::from("31")
+                }
+            ],
+            verdicts
+        );
+    }
+
+    #[test]
+    fn test_verify_unknown_param() {
+        let manifest = Manifest::parse(
+            r#"
+            [[answer]]
+            day = 1
+            input = "input/day01_test01.txt"
+            params = { width = 11 }
+            part1 = 11
+            "#,
+        )
+        .unwrap();
+        let outcomes = verify(&manifest.answers, None, None);
+
+        assert!(matches!(outcomes[0].verdict, Verdict::Error(_)));
+    }
+
+    #[test]
+    fn test_verify_timed_out() {
+        let manifest = Manifest::parse(
+            r#"
+            [[answer]]
+            day = 7
+            input = "input/day07_test01.txt"
+            part2 = 11387
+            "#,
+        )
+        .unwrap();
+        let outcomes = verify(&manifest.answers, Some(Duration::ZERO), None);
+
+        assert_eq!(Verdict::TimedOut, outcomes[0].verdict);
+    }
+
+    #[test]
+    fn test_verify_cached() {
+        let dir = std::env::temp_dir().join(format!("aoc_verify_cache_{}", std::process::id()));
+        let cache = Cache::new(&dir);
+        let manifest = Manifest::parse(
+            r#"
+            [[answer]]
+            day = 1
+            input = "input/day01_test01.txt"
+            part1 = 11
+            "#,
+        )
+        .unwrap();
+
+        let outcomes = verify(&manifest.answers, None, Some(&cache));
+        assert_eq!(
+            (Verdict::Pass, false),
+            (outcomes[0].verdict.clone(), outcomes[0].cached)
+        );
+        let outcomes = verify(&manifest.answers, None, Some(&cache));
+        assert_eq!(
+            (Verdict::Pass, true),
+            (outcomes[0].verdict.clone(), outcomes[0].cached)
+        );
+
+        // A wrong answer in the cache is believed, so it must only ever hold real answers
+        let input = read_input_file("input/day01_test01.txt").unwrap();
+        cache
+            .put(1, Part::One, &Params::new(), &input, "12")
+            .unwrap();
+        let outcomes = verify(&manifest.answers, None, Some(&cache));
+        assert_eq!(
+            Verdict::Fail {
+                actual: String::from("12")
+            },
+            outcomes[0].verdict
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_parse_invalid() {
+        let error = Manifest::parse("[[answer]]\nday = \"one\"\n").unwrap_err();
+
+        assert_eq!((2, 7), (error.line, error.column));
+    }
+}
@@ -1,16 +1,324 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod utils;
+#[macro_use]
+pub mod trace;
+
+mod bench;
+mod cache;
+mod cancel;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod differential;
+mod error;
+mod extract;
+pub mod generate;
+mod manifest;
+pub mod parallel;
+mod render;
+mod report;
+mod scaffold;
+mod solution;
+pub mod utils;
+
+pub use bench::{Baseline, BenchStats, CountingAllocator, bench};
+pub use cache::{Cache, DEFAULT_CACHE_DIR};
+pub use cancel::{Cancel, TimedOut};
+pub use error::{Error, ParseError, RunError};
+pub use extract::{Example, Saved, extract_examples, page_day, save_examples};
+pub use manifest::{Answer, Expected, Manifest, Outcome, Verdict, verify};
+pub use render::{Format, Frames, Image, Overlay, Rgb};
+pub use report::{OutputFormat, RunRecord, Status};
+pub use scaffold::scaffold;
+pub use solution::{Part, Runner, Solution};
+pub use utils::{read_input, read_input_file};
+
+use serde::de::DeserializeOwned;
+
+pub fn default_input_file(day: u8) -> String {
+    format!("input/day{:02}.txt", day)
+}
+
+/// Parameters overriding a solution's defaults, e.g. `width = 11` for day 14's example
+pub type Params = toml::Table;
+
+/// The parameters written out the way they are in the manifest, e.g. `width = 11`
+pub fn params_to_string(params: &Params) -> String {
+    params
+        .iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Looks up the solution for a day, set up with the parameters from the puzzle text
+pub fn get_solution(day: u8) -> Option<Box<dyn Runner>> {
+    get_solution_with_params(day, &Params::new()).ok()
+}
+
+/// Looks up the solution for a day, with any of its parameters overridden by `params`
+pub fn get_solution_with_params(day: u8, params: &Params) -> Result<Box<dyn Runner>, String> {
+    match day {
+        1 => without_params(day01::Day01, params),
+        2 => without_params(day02::Day02, params),
+        3 => without_params(day03::Day03, params),
+        4 => without_params(day04::Day04, params),
+        5 => without_params(day05::Day05, params),
+        6 => without_params(day06::Day06, params),
+        7 => without_params(day07::Day07, params),
+        8 => without_params(day08::Day08, params),
+        9 => without_params(day09::Day09, params),
+        10 => without_params(day10::Day10, params),
+        11 => with_params::<day11::Day11>(params),
+        12 => without_params(day12::Day12, params),
+        13 => without_params(day13::Day13, params),
+        14 => with_params::<day14::Day14>(params),
+        15 => without_params(day15::Day15, params),
+        _ => Err(format!("Day {} has no solution", day)),
+    }
+}
+
+fn without_params(
+    solution: impl Runner + 'static,
+    params: &Params,
+) -> Result<Box<dyn Runner>, String> {
+    no_params(params)?;
+    Ok(Box::new(solution))
+}
+
+fn no_params(params: &Params) -> Result<(), String> {
+    match params.keys().next() {
+        Some(name) => Err(format!("unknown parameter `{}`", name)),
+        None => Ok(()),
+    }
+}
+
+fn with_params<S: Solution + DeserializeOwned + 'static>(
+    params: &Params,
+) -> Result<Box<dyn Runner>, String> {
+    Ok(Box::new(from_params::<S>(params)?))
+}
+
+fn from_params<S: DeserializeOwned>(params: &Params) -> Result<S, String> {
+    toml::Value::Table(params.clone())
+        .try_into()
+        .map_err(|error: toml::de::Error| error.message().to_string())
+}
+
+/// Saves a frame for each step of a day's simulation, for the days which are simulations
+pub fn render(
+    day: u8,
+    part: Part,
+    input_file: &str,
+    params: &Params,
+    frames: &mut Frames,
+) -> Result<(), String> {
+    let rendered = match day {
+        14 => {
+            let day14: day14::Day14 = from_params(params)?;
+            utils::parse_input_file(input_file, |input| day14.parse(input))
+                .and_then(|input| day14.render(part, &input, frames))
+        }
+        15 => {
+            no_params(params)?;
+            utils::parse_input_file(input_file, |input| day15::Day15.parse(input))
+                .and_then(|input| day15::Day15.render(part, &input, frames))
+        }
+        _ => return Err(format!("Day {} can't be rendered", day)),
+    };
+    rendered.map_err(|error| error.to_string())
+}
+
+/// A day's puzzle structure as a Graphviz DOT digraph, for the days which have one: day 5's
+/// page ordering rules, with any pages whose rules go round in a circle boxed together, and
+/// day 10's map, with an edge for every step up a trail
+pub fn dot(day: u8, input_file: &str) -> Result<String, String> {
+    let dot = match day {
+        5 => utils::parse_input_file(input_file, day05::parse_input).map(|input| {
+            let cycles = day05::rule_cycles(&input);
+            let options = utils::dot::DotOptions::new().name("day05").cluster(|page| {
+                let cycle = cycles.iter().position(|cycle| cycle.contains(page))?;
+                Some(format!("cycle {}", cycle + 1))
+            });
+            day05::rules_to_dot(&input, &options)
+        }),
+        10 => utils::parse_input_file(input_file, day10::parse_input).map(|input| {
+            day10::top_map_to_dot(&input, &utils::dot::DotOptions::new().name("day10"))
+        }),
+        _ => return Err(format!("Day {} has no graph to show", day)),
+    };
+    dot.map_err(|error| error.to_string())
+}
+
+/// All days which have a solution, in order
+pub fn get_solutions() -> Vec<(u8, Box<dyn Runner>)> {
+    (1..=25)
+        .filter_map(|day| get_solution(day).map(|solution| (day, solution)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_solution() {
+        let solution = get_solution(1).unwrap();
+        assert_eq!(
+            "11",
+            solution
+                .run_file(Part::One, "input/day01_test01.txt")
+                .unwrap()
+        );
+        assert_eq!(
+            "31",
+            solution
+                .run_file(Part::Two, "input/day01_test01.txt")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manifest_inputs_validate() {
+        let manifest = Manifest::load("answers.toml").unwrap();
+        for expected in &manifest.answers {
+            let solution = get_solution_with_params(expected.day, &expected.params).unwrap();
+            let input = read_input_file(&expected.input).unwrap();
+            assert_eq!(
+                Vec::<ParseError>::new(),
+                solution.validate(&input),
+                "{}",
+                expected.input
+            );
+        }
+    }
+
+    #[test]
+    fn test_day_api() {
+        let input = day12::parse_input("AAAA\nBBCD\nBBCC\nEEEC\n").unwrap();
+        assert_eq!(140, day12::price_fencing_all_regions(&input, false));
+        assert_eq!(80, day12::price_fencing_all_regions(&input, true));
+    }
+
+    #[test]
+    fn test_get_solution_from_str() {
+        let solution = get_solution(1).unwrap();
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        assert_eq!("11", solution.run(Part::One, input).unwrap());
+        assert_eq!("31", solution.run(Part::Two, input).unwrap());
+    }
+
+    #[test]
+    fn test_get_solution_from_reader() {
+        let solution = get_solution(1).unwrap();
+        let input = read_input("<bytes>", "1   4\n4   3\n".as_bytes()).unwrap();
+        assert_eq!("2", solution.run(Part::One, &input).unwrap());
+    }
+
+    #[test]
+    fn test_get_solution_missing_input() {
+        let solution = get_solution(1).unwrap();
+        assert!(matches!(
+            solution.run_file(Part::One, "input/day01_missing.txt"),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_solution_invalid_input() {
+        let solution = get_solution(1).unwrap();
+        assert!(matches!(
+            solution.run_file(Part::One, "input/day01_invalid01.txt"),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_solution_with_params() {
+        let mut params = Params::new();
+        params.insert("width".to_string(), 11.into());
+        params.insert("height".to_string(), 7.into());
+        let solution = get_solution_with_params(14, &params).unwrap();
+        assert_eq!(
+            "12",
+            solution
+                .run_file(Part::One, "input/day14_test01.txt")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_get_solution_with_unknown_params() {
+        let mut params = Params::new();
+        params.insert("width".to_string(), 11.into());
+        assert!(get_solution_with_params(1, &params).is_err());
+        params.insert("depth".to_string(), 7.into());
+        assert!(get_solution_with_params(14, &params).is_err());
+    }
+
+    #[test]
+    fn test_get_solution_with_invalid_params() {
+        for (name, value) in [("width", 0), ("height", -7)] {
+            let mut params = Params::new();
+            params.insert(name.to_string(), value.into());
+            assert_eq!(
+                Some(format!(
+                    "invalid value: integer `{}`, expected a size greater than 0",
+                    value
+                )),
+                get_solution_with_params(14, &params).err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_solution_unsolved() {
+        assert!(get_solution(0).is_none());
+        assert!(get_solution(25).is_none());
+    }
+
+    #[test]
+    fn test_get_solutions() {
+        let days: Vec<u8> = get_solutions().into_iter().map(|(day, _)| day).collect();
+        assert_eq!((1..=15).collect::<Vec<u8>>(), days);
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = std::env::temp_dir().join(format!("aoc_render_{}", std::process::id()));
+        let mut frames = Frames::new(&dir, Format::Ppm, 1).unwrap();
+        let params: Params = toml::from_str("width = 11\nheight = 7\nnum_secs = 5").unwrap();
+
+        render(
+            14,
+            Part::One,
+            "input/day14_test01.txt",
+            &params,
+            &mut frames,
+        )
+        .unwrap();
+        assert_eq!(6, frames.count());
+        render(
+            15,
+            Part::Two,
+            "input/day15_test02.txt",
+            &Params::new(),
+            &mut frames,
+        )
+        .unwrap();
+        assert_eq!(6 + 16, frames.count());
+        assert!(render(3, Part::One, "input/day03.txt", &Params::new(), &mut frames).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use std::iter::Sum;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::solution::SOLVER_STACK_SIZE;
+
+/// Sets how many threads every solution shares, 1 solving everything in order and 0 using
+/// one per core. Can only be set once, before anything has been solved.
+#[cfg(feature = "parallel")]
+pub fn set_threads(threads: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        // The work handed to each thread can recurse as deeply as a whole solution
+        .stack_size(SOLVER_STACK_SIZE)
+        .build_global()
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn set_threads(threads: usize) -> Result<(), String> {
+    match threads {
+        1 => Ok(()),
+        _ => Err(String::from(
+            "can't use more than one thread without the `parallel` feature",
+        )),
+    }
+}
+
+/// Runs `f` with its solutions sharing `threads` threads rather than the global setting
+#[cfg(feature = "parallel")]
+pub fn with_threads<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        // The work handed to each thread can recurse as deeply as a whole solution
+        .stack_size(SOLVER_STACK_SIZE)
+        .build()
+        .expect("couldn't start the thread pool")
+        .install(f)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn with_threads<R: Send>(_threads: usize, f: impl FnOnce() -> R + Send) -> R {
+    f()
+}
+
+/// How many threads solutions are currently spread across
+pub fn threads() -> usize {
+    #[cfg(feature = "parallel")]
+    return rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    return 1;
+}
+
+/// Sums `f` of every item, spreading the items across threads if there's more than one.
+/// The items are independent, so the answer is the same however many threads there are.
+pub fn sum_by<T, S>(items: &[T], f: impl Fn(&T) -> S + Send + Sync) -> S
+where
+    T: Sync,
+    S: Send + Sum,
+{
+    // Unless the pool was started by `set_threads`, its threads have far less stack than
+    // a solution does, so give each item a solution's worth when it's running short
+    let f = |item: &T| stacker::maybe_grow(SOLVER_STACK_SIZE / 2, SOLVER_STACK_SIZE, || f(item));
+    #[cfg(feature = "parallel")]
+    if threads() > 1 {
+        return items.par_iter().map(f).sum();
+    }
+    items.iter().map(f).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aoc::{Part, get_solution};
+
+    #[test]
+    fn test_sum_by() {
+        let items: Vec<u64> = (1..=1000).collect();
+        assert_eq!(1001000, with_threads(4, || sum_by(&items, |n| n * 2)));
+        assert_eq!(0, sum_by(&[], |n: &u64| *n));
+    }
+
+    #[test]
+    fn test_with_threads() {
+        assert_eq!(1, with_threads(1, threads));
+        #[cfg(feature = "parallel")]
+        assert_eq!(3, with_threads(3, threads));
+    }
+
+    #[test]
+    fn test_sum_by_global_pool() {
+        // Recurses through well over the few MB a thread gets by default
+        fn depth(n: u64) -> u64 {
+            let frame = std::hint::black_box([0u8; 1024]);
+            if n == 0 {
+                u64::from(frame[0])
+            } else {
+                depth(std::hint::black_box(n - 1)) + 1
+            }
+        }
+        let items = vec![10_000; 16];
+        // On one of the global pool's threads, as it is when no thread count is given
+        #[cfg(feature = "parallel")]
+        let sum = rayon::scope(|_| sum_by(&items, |&n| depth(n)));
+        #[cfg(not(feature = "parallel"))]
+        let sum = sum_by(&items, |&n| depth(n));
+        assert_eq!(160_000, sum);
+    }
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let inputs = [
+            (2, "input/day02_test01.txt"),
+            (6, "input/day06_test01.txt"),
+            (7, "input/day07_test01.txt"),
+            (13, "input/day13_test01.txt"),
+        ];
+        for (day, input_file) in inputs {
+            for part in Part::ALL {
+                let solve = || {
+                    get_solution(day)
+                        .unwrap()
+                        .run_file(part, input_file)
+                        .unwrap()
+                };
+                assert_eq!(
+                    with_threads(1, solve),
+                    with_threads(4, solve),
+                    "day {} part {}",
+                    day,
+                    part
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::error::Error;
+use super::utils::Grid;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(96, 96, 96);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const GREEN: Rgb = Rgb(40, 180, 60);
+    pub const BLUE: Rgb = Rgb(50, 90, 220);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+    pub const BROWN: Rgb = Rgb(150, 100, 50);
+}
+
+/// Written the way SVG wants it, e.g. `#ff0000`
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Something drawn over the tiles of an [`Image`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overlay {
+    /// A box around a single (row, column) position
+    Highlight { pos: (usize, usize), colour: Rgb },
+    /// A line joining the centres of a run of (row, column) positions
+    Path {
+        positions: Vec<(usize, usize)>,
+        colour: Rgb,
+    },
+}
+
+/// The file formats an [`Image`] can be written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+            Format::Svg => "svg",
+        }
+    }
+
+    /// The format a file should be written in, going by its extension
+    pub fn of_file(file: &str) -> Option<Format> {
+        Path::new(file).extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            _ => Err(format!("unknown image format `{}`, use ppm, png or svg", s)),
+        }
+    }
+}
+
+/// A grid drawn as one square of colour per tile, with overlays on top. Each tile becomes
+/// a `scale` by `scale` square of pixels when it's written out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    tiles: Grid<Rgb>,
+    overlays: Vec<Overlay>,
+}
+
+impl Image {
+    /// Colours every tile of `grid` with `palette`
+    pub fn from_grid<T>(grid: &Grid<T>, palette: impl Fn(&T) -> Rgb) -> Self {
+        Image {
+            tiles: grid.map(palette),
+            overlays: vec![],
+        }
+    }
+
+    pub fn highlight(&mut self, pos: (usize, usize), colour: Rgb) -> &mut Self {
+        self.overlays.push(Overlay::Highlight { pos, colour });
+        self
+    }
+
+    pub fn path(
+        &mut self,
+        positions: impl IntoIterator<Item = (usize, usize)>,
+        colour: Rgb,
+    ) -> &mut Self {
+        self.overlays.push(Overlay::Path {
+            positions: positions.into_iter().collect(),
+            colour,
+        });
+        self
+    }
+
+    /// Width in tiles
+    pub fn width(&self) -> usize {
+        self.tiles.width()
+    }
+
+    /// Height in tiles
+    pub fn height(&self) -> usize {
+        self.tiles.height()
+    }
+
+    /// The colour of every tile once the overlays are painted on, highlights and paths
+    /// simply filling the tiles they cover
+    fn flatten(&self) -> Grid<Rgb> {
+        let mut tiles = self.tiles.clone();
+        for overlay in &self.overlays {
+            let (positions, colour) = match overlay {
+                Overlay::Highlight { pos, colour } => (std::slice::from_ref(pos), colour),
+                Overlay::Path { positions, colour } => (positions.as_slice(), colour),
+            };
+            for &pos in positions {
+                if let Some(tile) = tiles.get_mut(pos) {
+                    *tile = *colour;
+                }
+            }
+        }
+        tiles
+    }
+
+    /// Every pixel, row by row, as red, green and blue bytes
+    fn pixels(&self, scale: usize) -> Vec<u8> {
+        let tiles = self.flatten();
+        let mut pixels = Vec::with_capacity(self.width() * self.height() * scale * scale * 3);
+        for row in tiles.rows() {
+            for _ in 0..scale {
+                for &Rgb(r, g, b) in row {
+                    for _ in 0..scale {
+                        pixels.extend([r, g, b]);
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    /// Writes a binary (P6) portable pixmap
+    pub fn write_ppm(&self, mut writer: impl Write, scale: usize) -> io::Result<()> {
+        write!(
+            writer,
+            "P6\n{} {}\n255\n",
+            self.width() * scale,
+            self.height() * scale
+        )?;
+        writer.write_all(&self.pixels(scale))
+    }
+
+    pub fn write_png(&self, writer: impl Write, scale: usize) -> io::Result<()> {
+        let dimension = |size: usize| {
+            u32::try_from(size)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "image too large"))
+        };
+        let mut encoder = png::Encoder::new(
+            writer,
+            dimension(self.width() * scale)?,
+            dimension(self.height() * scale)?,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()?
+            .write_image_data(&self.pixels(scale))
+            .map_err(io::Error::other)
+    }
+
+    /// Writes an SVG with one rectangle per run of same coloured tiles, and the overlays
+    /// drawn as outlines and lines rather than filled tiles
+    pub fn write_svg(&self, mut writer: impl Write, scale: usize) -> io::Result<()> {
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+            self.width() * scale,
+            self.height() * scale,
+            self.width(),
+            self.height()
+        )?;
+
+        for (y, row) in self.tiles.rows().enumerate() {
+            let mut x = 0;
+            for run in row.chunk_by(|a, b| a == b) {
+                writeln!(
+                    writer,
+                    r#"<rect x="{}" y="{}" width="{}" height="1" fill="{}"/>"#,
+                    x,
+                    y,
+                    run.len(),
+                    run[0]
+                )?;
+                x += run.len();
+            }
+        }
+
+        for overlay in &self.overlays {
+            match overlay {
+                Overlay::Highlight {
+                    pos: (row, col),
+                    colour,
+                } => writeln!(
+                    writer,
+                    r#"<rect x="{}" y="{}" width="1" height="1" fill="none" stroke="{}" stroke-width="0.2"/>"#,
+                    col, row, colour
+                )?,
+                Overlay::Path { positions, colour } => {
+                    let points: Vec<String> = positions
+                        .iter()
+                        .map(|(row, col)| format!("{}.5,{}.5", col, row))
+                        .collect();
+                    writeln!(
+                        writer,
+                        r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="0.3"/>"#,
+                        points.join(" "),
+                        colour
+                    )?
+                }
+            }
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    pub fn write(&self, format: Format, writer: impl Write, scale: usize) -> io::Result<()> {
+        match format {
+            Format::Ppm => self.write_ppm(writer, scale),
+            Format::Png => self.write_png(writer, scale),
+            Format::Svg => self.write_svg(writer, scale),
+        }
+    }
+
+    /// Saves the image in the format its extension asks for
+    pub fn save(&self, image_file: &str, scale: usize) -> Result<(), Error> {
+        let format = Format::of_file(image_file).ok_or_else(|| {
+            Error::io(
+                image_file,
+                io::Error::new(io::ErrorKind::InvalidInput, "not a .ppm, .png or .svg file"),
+            )
+        })?;
+        self.save_as(image_file, format, scale)
+    }
+
+    fn save_as(&self, image_file: &str, format: Format, scale: usize) -> Result<(), Error> {
+        let file = File::create(image_file).map_err(|why| Error::io(image_file, why))?;
+        let mut writer = BufWriter::new(file);
+        self.write(format, &mut writer, scale)
+            .and_then(|_| writer.flush())
+            .map_err(|why| Error::io(image_file, why))
+    }
+}
+
+/// A numbered sequence of images, saved to `frame_00000.png`, `frame_00001.png` and so
+/// on as they're pushed, which can be stitched into an animation
+#[derive(Debug)]
+pub struct Frames {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+    count: usize,
+}
+
+impl Frames {
+    /// Creates `dir` if it isn't already there
+    pub fn new(dir: impl Into<PathBuf>, format: Format, scale: usize) -> Result<Self, Error> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|why| Error::io(&dir.to_string_lossy(), why))?;
+        Ok(Frames {
+            dir,
+            format,
+            scale,
+            count: 0,
+        })
+    }
+
+    pub fn push(&mut self, image: &Image) -> Result<(), Error> {
+        let frame_file = self.dir.join(format!(
+            "frame_{:05}.{}",
+            self.count,
+            self.format.extension()
+        ));
+        image.save_as(&frame_file.to_string_lossy(), self.format, self.scale)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// How many frames have been saved so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_image() -> Image {
+        let grid = Grid::parse("#.\n.#\n", "a tile", Some).unwrap();
+        Image::from_grid(&grid, |&tile| match tile {
+            '#' => Rgb::BLACK,
+            _ => Rgb::WHITE,
+        })
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = test_image();
+        image.highlight((0, 1), Rgb::RED);
+        let mut ppm = vec![];
+        image.write_ppm(&mut ppm, 2).unwrap();
+
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&header[..], &ppm[..header.len()]);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(4 * 4 * 3, pixels.len());
+        // Top left is black, and the highlight fills the top right tile
+        assert_eq!(&[0, 0, 0], &pixels[0..3]);
+        assert_eq!(&[220, 40, 40], &pixels[9..12]);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut png = vec![];
+        test_image().write_png(&mut png, 3).unwrap();
+
+        let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!((6, 6), (reader.info().width, reader.info().height));
+    }
+
+    #[test]
+    fn test_write_svg() {
+        let mut image = test_image();
+        image.path([(0, 1), (1, 1)], Rgb::BLUE);
+        let mut svg = vec![];
+        image.write_svg(&mut svg, 10).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.contains(r##"<rect x="1" y="0" width="1" height="1" fill="#ffffff"/>"##));
+        assert!(
+            svg.contains(r##"<polyline points="1.5,0.5 1.5,1.5" fill="none" stroke="#325adc""##)
+        );
+        assert!(svg.contains(r#"width="20" height="20" viewBox="0 0 2 2""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc_frames_{}", std::process::id()));
+        let mut frames = Frames::new(&dir, Format::Ppm, 1).unwrap();
+        frames.push(&test_image()).unwrap();
+        frames.push(&test_image()).unwrap();
+
+        assert_eq!(2, frames.count());
+        assert!(dir.join("frame_00001.ppm").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_of_file() {
+        assert_eq!(Some(Format::Png), Format::of_file("day14.PNG"));
+        assert_eq!(None, Format::of_file("day14.txt"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    time::Duration,
+};
+
+use super::solution::Part;
+use super::trace::json_string;
+
+/// How the output of a run is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A line per part for reading, with any errors going to stderr
+    Text,
+    /// One JSON object per part, per line
+    JsonLines,
+    /// A header line, then a line per part
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::JsonLines),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "unknown output format `{}`, use text, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+/// How running a part ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// The input couldn't be read
+    InputError,
+    ParseError,
+    TimedOut,
+    Panicked,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Status::Ok => "ok",
+            Status::InputError => "input_error",
+            Status::ParseError => "parse_error",
+            Status::TimedOut => "timeout",
+            Status::Panicked => "panic",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// What happened running one part of a day against one input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    /// The parameters the solution was given, written the way they are in the manifest
+    pub params: String,
+    pub status: Status,
+    /// Only there when the status is ok
+    pub answer: Option<String>,
+    /// Why there's no answer, when there isn't one
+    pub error: Option<String>,
+    /// How long solving took, which is nothing for a cached answer
+    pub elapsed: Duration,
+    pub cached: bool,
+}
+
+impl RunRecord {
+    /// The names of the fields in each line of [`to_csv`](RunRecord::to_csv)
+    pub const CSV_HEADER: &str = "day,part,input,params,status,answer,error,duration_ms,cached";
+
+    /// The record as a single line JSON object, with `null` for anything missing
+    pub fn to_json(&self) -> String {
+        let optional =
+            |value: &Option<String>| value.as_deref().map_or(String::from("null"), json_string);
+        format!(
+            r#"{{"day":{},"part":{},"input":{},"params":{},"status":"{}","answer":{},"error":{},"duration_ms":{},"cached":{}}}"#,
+            self.day,
+            self.part,
+            json_string(&self.input),
+            json_string(&self.params),
+            self.status,
+            optional(&self.answer),
+            optional(&self.error),
+            self.duration_ms(),
+            self.cached
+        )
+    }
+
+    /// The record as a line of CSV, with empty fields for anything missing
+    pub fn to_csv(&self) -> String {
+        [
+            self.day.to_string(),
+            self.part.to_string(),
+            csv_field(&self.input),
+            csv_field(&self.params),
+            self.status.to_string(),
+            csv_field(self.answer.as_deref().unwrap_or_default()),
+            csv_field(self.error.as_deref().unwrap_or_default()),
+            self.duration_ms(),
+            self.cached.to_string(),
+        ]
+        .join(",")
+    }
+
+    fn duration_ms(&self) -> String {
+        format!("{:.3}", self.elapsed.as_secs_f64() * 1000.0)
+    }
+}
+
+/// Human readable, e.g. `Day 6 part 1: 5564 (8.53ms)`
+impl Display for RunRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Day {} part {}: ", self.day, self.part)?;
+        match (&self.answer, &self.error) {
+            (Some(answer), _) if self.cached => write!(f, "{} (cached)", answer),
+            (Some(answer), _) => write!(f, "{} ({:?})", answer, self.elapsed),
+            (None, Some(error)) => write!(f, "{}", error),
+            (None, None) => write!(f, "{}", self.status),
+        }
+    }
+}
+
+/// Quotes a CSV field if it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_record_formats() {
+        let ok = RunRecord {
+            day: 14,
+            part: Part::One,
+            input: String::from("input/day14_test01.txt"),
+            params: String::from("width = 11, height = 7"),
+            status: Status::Ok,
+            answer: Some(String::from("12")),
+            error: None,
+            elapsed: Duration::from_micros(1500),
+            cached: false,
+        };
+        let failed = RunRecord {
+            part: Part::Two,
+            params: String::new(),
+            status: Status::ParseError,
+            answer: None,
+            error: Some(String::from("line 1, column 3: expected \"p=<x>,<y>\"")),
+            ..ok.clone()
+        };
+
+        assert_eq!(
+            r#"{"day":14,"part":1,"input":"input/day14_test01.txt","params":"width = 11, height = 7","status":"ok","answer":"12","error":null,"duration_ms":1.500,"cached":false}"#,
+            ok.to_json()
+        );
+        assert_eq!(
+            r#"14,1,input/day14_test01.txt,"width = 11, height = 7",ok,12,,1.500,false"#,
+            ok.to_csv()
+        );
+        assert_eq!(
+            r#"14,2,input/day14_test01.txt,,parse_error,,"line 1, column 3: expected ""p=<x>,<y>""",1.500,false"#,
+            failed.to_csv()
+        );
+        assert_eq!(9, RunRecord::CSV_HEADER.split(',').count());
+        assert_eq!("Day 14 part 1: 12 (1.5ms)", ok.to_string());
+    }
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!(Ok(OutputFormat::JsonLines), "JSON".parse());
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
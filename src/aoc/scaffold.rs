@@ -0,0 +1,249 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+const DAY_TEMPLATE: &str = include_str!("../../templates/day.rs");
+
+/// Everything the scaffold for a day writes, worked out before anything is written so
+/// that a day which is already registered is left untouched
+#[derive(Debug)]
+struct Plan {
+    /// New files and their contents
+    create: Vec<(PathBuf, String)>,
+    /// Existing files and their new contents
+    update: Vec<(PathBuf, String)>,
+}
+
+/// Starts a new day under `root`: its solution module from the template, an empty real and
+/// example input, the lines registering it in `src/aoc/mod.rs` and `src/lib.rs`, and an
+/// entry in `answers.toml` for the example's answers to be filled in.
+/// Refuses to do anything if any of the files are already there or the day is already
+/// registered. Returns the files created and changed, in that order.
+pub fn scaffold(root: &Path, day: u8, title: &str) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no day {}, only 1 to 25", day));
+    }
+    let plan = plan(root, day, title)?;
+
+    let mut written = vec![];
+    for (file, contents) in &plan.create {
+        if let Err(why) = create_new(&root.join(file), contents) {
+            // Take away what was created, so that trying again doesn't find it there
+            for file in &written {
+                let _ = fs::remove_file(root.join(file));
+            }
+            return Err(match why.kind() {
+                io::ErrorKind::AlreadyExists => format!("{} already exists", file.display()),
+                _ => format!("couldn't write {}: {}", file.display(), why),
+            });
+        }
+        written.push(file.clone());
+    }
+    // Only registered once everything it registers is there
+    for (file, contents) in &plan.update {
+        fs::write(root.join(file), contents)
+            .map_err(|why| format!("couldn't write {}: {}", file.display(), why))?;
+        written.push(file.clone());
+    }
+    Ok(written)
+}
+
+/// Writes `contents` to a new file, failing rather than overwriting one that's already there
+fn create_new(file: &Path, contents: &str) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(file)?
+        .write_all(contents.as_bytes())
+}
+
+fn plan(root: &Path, day: u8, title: &str) -> Result<Plan, String> {
+    let dd = format!("{:02}", day);
+    let source = DAY_TEMPLATE
+        .replace("{{day}}", &day.to_string())
+        .replace("{{dd}}", &dd)
+        .replace("{{title}}", title);
+
+    let create = vec![
+        (PathBuf::from(format!("src/aoc/day{}.rs", dd)), source),
+        (PathBuf::from(format!("input/day{}.txt", dd)), String::new()),
+        (
+            PathBuf::from(format!("input/day{}_test01.txt", dd)),
+            String::new(),
+        ),
+    ];
+    let mod_file = PathBuf::from("src/aoc/mod.rs");
+    let lib_file = PathBuf::from("src/lib.rs");
+    let read = |file: &Path| {
+        fs::read_to_string(root.join(file))
+            .map_err(|why| format!("couldn't open {}: {}", file.display(), why))
+    };
+    let mut registry = read(&mod_file)?;
+    let mut exports = read(&lib_file)?;
+    // As with extracted examples, a missing manifest is started afresh
+    let manifest_file = PathBuf::from("answers.toml");
+    let mut manifest = fs::read_to_string(root.join(&manifest_file)).unwrap_or_default();
+
+    let module = Regex::new(r"(?m)^pub mod day(\d+);\n").unwrap();
+    registry = insert_in_order(&registry, &module, day, &format!("pub mod day{};\n", dd))
+        .ok_or_else(|| format!("day {} is already in {}", day, mod_file.display()))?;
+
+    let solution = Regex::new(r"(?m)^ +(\d+) => with(?:out)?_params.*\n").unwrap();
+    registry = insert_in_order(
+        &registry,
+        &solution,
+        day,
+        &format!(
+            "        {} => without_params(day{}::Day{}, params),\n",
+            day, dd, dd
+        ),
+    )
+    .ok_or_else(|| format!("day {} already has a solution", day))?;
+
+    let export = Regex::new(r"day(\d+), ").unwrap();
+    exports = insert_in_order(&exports, &export, day, &format!("day{}, ", dd))
+        .ok_or_else(|| format!("day {} is already in {}", day, lib_file.display()))?;
+
+    // No answers until they're known, as the template's would pass without solving anything
+    manifest.push_str(&format!(
+        "\n[[answer]]\nday = {}\ninput = \"input/day{}_test01.txt\"\n\
+         # Add the example's answers from the puzzle\n# part1 =\n# part2 =\n",
+        day, dd
+    ));
+
+    Ok(Plan {
+        create,
+        update: vec![
+            (mod_file, registry),
+            (lib_file, exports),
+            (manifest_file, manifest),
+        ],
+    })
+}
+
+/// Inserts `line` amongst the matches of `pattern`, whose first group is a day, so that
+/// they stay in order of day. `None` if `day` is already there, and `text` is returned as
+/// it is if there are no matches to go amongst.
+fn insert_in_order(text: &str, pattern: &Regex, day: u8, line: &str) -> Option<String> {
+    let mut insert_at = None;
+    for captures in pattern.captures_iter(text) {
+        let matched = captures.get(0).unwrap();
+        let Ok(other) = captures[1].parse::<u8>() else {
+            continue;
+        };
+        if other == day {
+            return None;
+        }
+        if other > day {
+            insert_at = Some(matched.start());
+            break;
+        }
+        insert_at = Some(matched.end());
+    }
+
+    let Some(insert_at) = insert_at else {
+        return Some(text.to_string());
+    };
+    Some(format!(
+        "{}{}{}",
+        &text[..insert_at],
+        line,
+        &text[insert_at..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aoc::Manifest;
+
+    #[test]
+    fn test_insert_in_order() {
+        let pattern = Regex::new(r"day(\d+), ").unwrap();
+
+        assert_eq!(
+            Some(String::from("day01, day02, day03, ")),
+            insert_in_order("day01, day03, ", &pattern, 2, "day02, ")
+        );
+        assert_eq!(
+            Some(String::from("day01, day03, day04, ")),
+            insert_in_order("day01, day03, ", &pattern, 4, "day04, ")
+        );
+        assert_eq!(
+            None,
+            insert_in_order("day01, day03, ", &pattern, 3, "day03, ")
+        );
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let root = std::env::temp_dir().join(format!("aoc_scaffold_{}", std::process::id()));
+        fs::create_dir_all(root.join("src/aoc")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::copy("src/aoc/mod.rs", root.join("src/aoc/mod.rs")).unwrap();
+        fs::copy("src/lib.rs", root.join("src/lib.rs")).unwrap();
+        fs::copy("answers.toml", root.join("answers.toml")).unwrap();
+
+        let written = scaffold(&root, 25, "Code Chronicle").unwrap();
+        let registry = fs::read_to_string(root.join("src/aoc/mod.rs")).unwrap();
+        let exports = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        let source = fs::read_to_string(root.join("src/aoc/day25.rs")).unwrap();
+        let manifest = Manifest::load(&root.join("answers.toml").to_string_lossy()).unwrap();
+
+        assert_eq!(6, written.len());
+        assert!(registry.contains("pub mod day15;\npub mod day25;\n"));
+        assert!(registry.contains("        25 => without_params(day25::Day25, params),\n"));
+        assert!(exports.contains("day15, day25, "));
+        assert!(source.starts_with("// https://adventofcode.com/2024/day/25\n"));
+        assert!(source.contains("//! Day 25: Code Chronicle."));
+        assert!(source.contains("pub struct Day25;"));
+        assert!(source.contains("#[cfg(test)]\nmod tests {"));
+        assert!(!source.contains("input/day25_test01.txt"));
+        let expected = manifest.answers.last().unwrap();
+        assert_eq!(25, expected.day);
+        assert_eq!("input/day25_test01.txt", expected.input);
+        assert_eq!(None, expected.part1);
+        assert_eq!(None, expected.part2);
+
+        // Nothing is overwritten, even the files which weren't there the first time
+        fs::remove_file(root.join("input/day25.txt")).unwrap();
+        assert_eq!(
+            Err(String::from("day 25 is already in src/aoc/mod.rs")),
+            scaffold(&root, 25, "Code Chronicle")
+        );
+        assert!(!root.join("input/day25.txt").exists());
+
+        // Nor is anything registered or left behind when a file is already there
+        fs::copy("src/aoc/mod.rs", root.join("src/aoc/mod.rs")).unwrap();
+        fs::copy("src/lib.rs", root.join("src/lib.rs")).unwrap();
+        assert_eq!(
+            Err(String::from("src/aoc/day25.rs already exists")),
+            scaffold(&root, 25, "Code Chronicle")
+        );
+        fs::remove_file(root.join("src/aoc/day25.rs")).unwrap();
+        assert_eq!(
+            Err(String::from("input/day25_test01.txt already exists")),
+            scaffold(&root, 25, "Code Chronicle")
+        );
+        assert!(!root.join("src/aoc/day25.rs").exists());
+        assert!(!root.join("input/day25.txt").exists());
+        assert_eq!(
+            fs::read_to_string("src/aoc/mod.rs").unwrap(),
+            fs::read_to_string(root.join("src/aoc/mod.rs")).unwrap()
+        );
+        assert_eq!(
+            1,
+            Manifest::load(&root.join("answers.toml").to_string_lossy())
+                .unwrap()
+                .answers
+                .iter()
+                .filter(|expected| expected.day == 25)
+                .count()
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt::{self, Display};
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError, RunError};
+use super::utils::read_input_file;
+
+// Some solvers recurse once per step, so give them plenty of stack
+pub(super) const SOLVER_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl TryFrom<u8> for Part {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// A day's puzzle, split into parsing the input once and solving each part from it.
+/// Any extra parameters the puzzle needs (grid size, number of blinks, etc.) are
+/// fields on the implementing type. Parts which could run for a long time check
+/// `cancel` as they go, giving up with [`TimedOut`] when it says to.
+pub trait Solution {
+    type Input;
+
+    /// Turns the puzzle input into the day's own [`Solution::Input`], stopping at the first
+    /// problem and saying which line and column it's on
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(&self, input: &Self::Input, cancel: &Cancel) -> Result<impl Display, TimedOut>;
+
+    fn part2(&self, input: &Self::Input, cancel: &Cancel) -> Result<impl Display, TimedOut>;
+
+    /// Every way `input` breaks the puzzle's rules, found without solving it. Days whose
+    /// parser stops at the first problem override this to report them all.
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        self.parse(input).err().into_iter().collect()
+    }
+}
+
+/// Object safe view of a [`Solution`], so days with different input types can sit
+/// together in the registry
+pub trait Runner {
+    /// Solves one part for the puzzle input held in `input`, giving up if `cancel` says to
+    fn run_within(&self, part: Part, input: &str, cancel: &Cancel) -> Result<String, RunError>;
+
+    /// Every way `input` breaks the puzzle's rules, see [`Solution::validate`]
+    fn validate(&self, input: &str) -> Vec<ParseError>;
+
+    /// Solves one part for the puzzle input held in `input`, however long it takes
+    fn run(&self, part: Part, input: &str) -> Result<String, ParseError> {
+        self.run_within(part, input, &Cancel::never())
+            .map_err(|error| match error {
+                RunError::Parse(error) => error,
+                RunError::TimedOut(_) => unreachable!("nothing else can cancel a new Cancel"),
+            })
+    }
+
+    fn run_file(&self, part: Part, input_file: &str) -> Result<String, Error> {
+        self.run_file_within(part, input_file, &Cancel::never())
+    }
+
+    fn run_file_within(
+        &self,
+        part: Part,
+        input_file: &str,
+        cancel: &Cancel,
+    ) -> Result<String, Error> {
+        let input = read_input_file(input_file)?;
+        self.run_within(part, &input, cancel)
+            .map_err(|error| Error::run(input_file, error))
+    }
+}
+
+impl<S: Solution> Runner for S {
+    fn validate(&self, input: &str) -> Vec<ParseError> {
+        Solution::validate(self, input)
+    }
+
+    fn run_within(&self, part: Part, input: &str, cancel: &Cancel) -> Result<String, RunError> {
+        stacker::grow(SOLVER_STACK_SIZE, || {
+            let input = self.parse(input)?;
+            Ok(match part {
+                Part::One => self.part1(&input, cancel)?.to_string(),
+                Part::Two => self.part2(&input, cancel)?.to_string(),
+            })
+        })
+    }
+}
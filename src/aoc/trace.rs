@@ -0,0 +1,332 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::{
+        Mutex,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+/// Emits a snapshot of some state, such as a grid, e.g. `trace_state!(Debug, "map", map)`
+macro_rules! trace_state {
+    ($level:ident, $name:expr, $value:expr) => {
+        if $crate::aoc::trace::enabled($crate::aoc::trace::Level::$level) {
+            $crate::aoc::trace::emit(
+                module_path!(),
+                $crate::aoc::trace::Level::$level,
+                $crate::aoc::trace::Event::State {
+                    name: $name,
+                    value: $value.to_string(),
+                },
+            );
+        }
+    };
+}
+
+/// Emits something the solver decided to do, formatted like `format!`
+macro_rules! trace_decision {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::aoc::trace::enabled($crate::aoc::trace::Level::$level) {
+            $crate::aoc::trace::emit(
+                module_path!(),
+                $crate::aoc::trace::Level::$level,
+                $crate::aoc::trace::Event::Decision(format!($($arg)+)),
+            );
+        }
+    };
+}
+
+/// Emits the current value of something being counted, e.g. `trace_counter!(Info, "loops", n)`
+macro_rules! trace_counter {
+    ($level:ident, $name:expr, $value:expr) => {
+        if $crate::aoc::trace::enabled($crate::aoc::trace::Level::$level) {
+            $crate::aoc::trace::emit(
+                module_path!(),
+                $crate::aoc::trace::Level::$level,
+                $crate::aoc::trace::Event::Counter {
+                    name: $name,
+                    value: $value as i64,
+                },
+            );
+        }
+    };
+}
+
+/// How much detail to trace, each level including those before it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    #[default]
+    Off,
+    /// A handful of events per run, such as totals
+    Info,
+    /// Events per step of a solution, such as each move of a robot
+    Debug,
+    /// Events from the innermost loops, which can be very many
+    Trace,
+}
+
+impl Level {
+    /// The level asked for by repeating a `-v` flag `count` times
+    pub fn from_verbosity(count: u8) -> Level {
+        match count {
+            0 => Level::Off,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A snapshot of some state, which may span several lines
+    State {
+        name: &'static str,
+        value: String,
+    },
+    /// Something the solver chose to do, and why
+    Decision(String),
+    Counter {
+        name: &'static str,
+        value: i64,
+    },
+}
+
+/// An event along with where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The day whose solution emitted the event, if it came from one
+    pub day: Option<u8>,
+    pub level: Level,
+    pub event: Event,
+}
+
+impl Record {
+    /// The record as a single line JSON object
+    pub fn to_json(&self) -> String {
+        let day = self.day.map_or(String::from("null"), |day| day.to_string());
+        let event = match &self.event {
+            Event::State { name, value } => format!(
+                r#""event":"state","name":{},"value":{}"#,
+                json_string(name),
+                json_string(value)
+            ),
+            Event::Decision(decision) => {
+                format!(r#""event":"decision","value":{}"#, json_string(decision))
+            }
+            Event::Counter { name, value } => format!(
+                r#""event":"counter","name":{},"value":{}"#,
+                json_string(name),
+                value
+            ),
+        };
+        format!(r#"{{"day":{},"level":"{}",{}}}"#, day, self.level, event)
+    }
+}
+
+/// Human readable, e.g. `[day06 debug] turning right at (6, 4)`
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.day {
+            Some(day) => write!(f, "[day{:02} {}] ", day, self.level)?,
+            None => write!(f, "[{}] ", self.level)?,
+        }
+        match &self.event {
+            Event::State { name, value } => write!(f, "{}:\n{}", name, value.trim_end()),
+            Event::Decision(decision) => write!(f, "{}", decision),
+            Event::Counter { name, value } => write!(f, "{} = {}", name, value),
+        }
+    }
+}
+
+/// Where traced events are written
+pub enum Sink {
+    /// Human readable lines
+    Text(Box<dyn Write + Send>),
+    /// One JSON object per line
+    JsonLines(Box<dyn Write + Send>),
+    /// Kept in memory, mostly for tests
+    Memory(Vec<Record>),
+}
+
+impl Sink {
+    fn record(&mut self, record: Record) -> io::Result<()> {
+        match self {
+            Sink::Text(writer) => writeln!(writer, "{}", record),
+            Sink::JsonLines(writer) => writeln!(writer, "{}", record.to_json()),
+            Sink::Memory(records) => {
+                records.push(record);
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Text(writer) | Sink::JsonLines(writer) => writer.flush(),
+            Sink::Memory(_) => Ok(()),
+        }
+    }
+}
+
+/// Quotes and escapes `s` as a JSON string
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Which events to keep and where to write them
+pub struct Tracer {
+    pub level: Level,
+    /// Only trace these days, or every day if empty
+    pub days: Vec<u8>,
+    pub sink: Sink,
+}
+
+// Checked before anything else so that tracing costs next to nothing when it's off
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static TRACER: Mutex<Option<Tracer>> = Mutex::new(None);
+
+/// Starts sending events to `tracer`, returning whichever tracer it replaces
+pub fn install(tracer: Tracer) -> Option<Tracer> {
+    let mut current = TRACER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    MAX_LEVEL.store(tracer.level as u8, Ordering::Relaxed);
+    current.replace(tracer)
+}
+
+/// Stops tracing, flushing and returning the tracer that was installed
+pub fn uninstall() -> Option<Tracer> {
+    let mut current = TRACER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    MAX_LEVEL.store(Level::Off as u8, Ordering::Relaxed);
+    let mut tracer = current.take();
+    if let Some(tracer) = &mut tracer {
+        let _ = tracer.sink.flush();
+    }
+    tracer
+}
+
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// The day a module such as `aoc::aoc::day06` holds the solution for
+fn day_of(module_path: &str) -> Option<u8> {
+    module_path
+        .rsplit("::")
+        .next()?
+        .strip_prefix("day")?
+        .parse()
+        .ok()
+}
+
+/// Hands an event to the installed tracer. Use the `trace_*` macros rather than calling
+/// this, as they skip building the event when its level is off.
+pub fn emit(module_path: &str, level: Level, event: Event) {
+    let mut current = TRACER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(tracer) = current.as_mut() else {
+        return;
+    };
+    let day = day_of(module_path);
+    if level > tracer.level
+        || !(tracer.days.is_empty() || day.is_some_and(|day| tracer.days.contains(&day)))
+    {
+        return;
+    }
+    // Tracing is only for debugging, so a full disk shouldn't stop the solution
+    let _ = tracer.sink.record(Record { day, level, event });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aoc::{Part, get_solution};
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(r#""a \"b\"\n\\c\u0001""#, json_string("a \"b\"\n\\c\u{1}"));
+    }
+
+    #[test]
+    fn test_day_of() {
+        assert_eq!(Some(6), day_of("aoc::aoc::day06"));
+        assert_eq!(None, day_of("aoc::aoc::utils"));
+    }
+
+    #[test]
+    fn test_record_format() {
+        let record = Record {
+            day: Some(6),
+            level: Level::Info,
+            event: Event::Counter {
+                name: "loops",
+                value: 6,
+            },
+        };
+
+        assert_eq!("[day06 info] loops = 6", record.to_string());
+        assert_eq!(
+            r#"{"day":6,"level":"info","event":"counter","name":"loops","value":6}"#,
+            record.to_json()
+        );
+    }
+
+    #[test]
+    fn test_trace_day() {
+        install(Tracer {
+            level: Level::Debug,
+            days: vec![15],
+            sink: Sink::Memory(vec![]),
+        });
+        get_solution(15)
+            .unwrap()
+            .run(Part::One, "#####\n#@O.#\n#####\n\n>>\n")
+            .unwrap();
+        let Some(Tracer {
+            sink: Sink::Memory(records),
+            ..
+        }) = uninstall()
+        else {
+            panic!("expected the memory sink back");
+        };
+
+        // Other days' tests may be running at the same time, but only day 15 is traced
+        assert!(records.iter().all(|record| record.day == Some(15)));
+        assert!(records.contains(&Record {
+            day: Some(15),
+            level: Level::Debug,
+            event: Event::Decision(String::from("moving E")),
+        }));
+        assert!(!records.iter().any(|record| record.level == Level::Trace));
+    }
+}
@@ -1,32 +1,92 @@
+pub mod dot;
+pub mod graph;
+pub mod memo;
+
 use std::{
-    error::Error,
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
     fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
+    hash::Hash,
+    io::{BufRead, BufReader, Read},
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
 };
 
-pub fn get_lines(input_file: &str) -> Vec<String> {
-    let path = Path::new(input_file);
-    let display = path.display();
+use super::error::{Error, ParseError};
+use dot::{DotOptions, write_dot};
+use graph::{bfs, dfs};
 
-    let file = match File::open(input_file) {
-        // The `description` method of `io::Error` returns a string that describes the error
-        Err(why) => panic!(
-            "couldn't open {}: {}",
-            display,
-            <dyn Error>::to_string(&why)
-        ),
-        Ok(file) => file,
-    };
+pub fn get_lines(input_file: &str) -> Result<Vec<String>, Error> {
+    let file = File::open(input_file).map_err(|why| Error::io(input_file, why))?;
 
     let reader = BufReader::new(file);
     reader
         .lines()
-        .map(|l| l.expect("Could not parse line"))
+        .collect::<Result<_, _>>()
+        .map_err(|why| Error::io(input_file, why))
+}
+
+/// Reads all of an input into memory. `name` is only used to say where a failed read
+/// came from, e.g. `"<stdin>"`
+pub fn read_input(name: &str, mut reader: impl Read) -> Result<String, Error> {
+    let mut input = String::new();
+    reader
+        .read_to_string(&mut input)
+        .map_err(|why| Error::io(name, why))?;
+    Ok(input)
+}
+
+pub fn read_input_file(input_file: &str) -> Result<String, Error> {
+    let file = File::open(input_file).map_err(|why| Error::io(input_file, why))?;
+    read_input(input_file, file)
+}
+
+/// Reads an input file and parses it, tagging any error with the file name
+pub fn parse_input_file<T>(
+    input_file: &str,
+    parse: impl FnOnce(&str) -> Result<T, ParseError>,
+) -> Result<T, Error> {
+    let input = read_input_file(input_file)?;
+    parse(&input).map_err(|error| Error::parse(input_file, error))
+}
+
+/// Parses each line of `input` on its own with `parse`, for inputs where every line stands
+/// alone, returning every line's error rather than just the first
+pub fn validate_lines<T>(
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, ParseError>,
+) -> Vec<ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(line_index, line)| {
+            parse(line).err().map(|error| ParseError {
+                line: error.line + line_index,
+                ..error
+            })
+        })
         .collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+/// Zero based column of `field` within `line`, where `field` is a slice of `line`
+pub fn column_of(line: &str, field: &str) -> usize {
+    field.as_ptr() as usize - line.as_ptr() as usize
+}
+
+/// Parses `field`, a slice of `line`, reporting where it is if it isn't a valid `T`
+pub fn parse_field<T: FromStr>(
+    line_index: usize,
+    line: &str,
+    field: &str,
+    expected: &str,
+) -> Result<T, ParseError> {
+    field
+        .parse::<T>()
+        .map_err(|_| ParseError::new(line_index, column_of(line, field), expected, field))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum Direction {
     N,
     S,
@@ -34,6 +94,34 @@ pub enum Direction {
     W,
 }
 
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+    /// The direction a quarter turn anticlockwise from this one
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::N => Direction::W,
+            Direction::W => Direction::S,
+            Direction::S => Direction::E,
+            Direction::E => Direction::N,
+        }
+    }
+
+    /// The direction a quarter turn clockwise from this one
+    pub fn turn_right(self) -> Direction {
+        self.turn_left().opposite()
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::N => Direction::S,
+            Direction::S => Direction::N,
+            Direction::E => Direction::W,
+            Direction::W => Direction::E,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Compass {
     #[default]
@@ -47,6 +135,475 @@ pub enum Compass {
     NorthWest,
 }
 
+impl Compass {
+    pub const ALL: [Compass; 8] = [
+        Compass::North,
+        Compass::NorthEast,
+        Compass::East,
+        Compass::SouthEast,
+        Compass::South,
+        Compass::SouthWest,
+        Compass::West,
+        Compass::NorthWest,
+    ];
+
+    pub const CARDINALS: [Compass; 4] =
+        [Compass::North, Compass::East, Compass::South, Compass::West];
+
+    /// The (row, column) step taken when moving this way, with north being up
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Compass::North => (-1, 0),
+            Compass::NorthEast => (-1, 1),
+            Compass::East => (0, 1),
+            Compass::SouthEast => (1, 1),
+            Compass::South => (1, 0),
+            Compass::SouthWest => (1, -1),
+            Compass::West => (0, -1),
+            Compass::NorthWest => (-1, -1),
+        }
+    }
+
+    /// The point a quarter turn anticlockwise from this one, e.g. north east to north west
+    pub fn turn_left(self) -> Compass {
+        self.rotate(6)
+    }
+
+    /// The point a quarter turn clockwise from this one, e.g. north east to south east
+    pub fn turn_right(self) -> Compass {
+        self.rotate(2)
+    }
+
+    pub fn opposite(self) -> Compass {
+        self.rotate(4)
+    }
+
+    /// Turns clockwise by `eighths` of a full turn
+    fn rotate(self, eighths: usize) -> Compass {
+        let index = Compass::ALL.iter().position(|&c| c == self).unwrap();
+        Compass::ALL[(index + eighths) % Compass::ALL.len()]
+    }
+}
+
+impl From<Direction> for Compass {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::N => Compass::North,
+            Direction::S => Compass::South,
+            Direction::E => Compass::East,
+            Direction::W => Compass::West,
+        }
+    }
+}
+
+/// A position on an unbounded plane, with x increasing to the east and y to the south,
+/// so it lines up with a grid's columns and rows
+#[derive(Debug, Default, Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+/// The difference between two [`Point`]s, e.g. a step or a velocity
+pub type Vec2 = Point;
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Point { x, y }
+    }
+
+    /// The point at a grid's (row, column) position
+    pub fn from_row_col((row, col): (usize, usize)) -> Self {
+        Point::new(col as isize, row as isize)
+    }
+
+    /// The grid (row, column) position of this point, if it isn't left of or above the grid
+    pub fn to_row_col(self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.y).ok()?, usize::try_from(self.x).ok()?))
+    }
+
+    pub fn manhattan(self, other: Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Wraps this point around onto the `width` by `height` rectangle at the origin
+    pub fn wrap(self, width: isize, height: isize) -> Point {
+        Point::new(self.x.rem_euclid(width), self.y.rem_euclid(height))
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Vec2) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vec2;
+
+    fn sub(self, other: Point) -> Vec2 {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: isize) -> Point {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+/// A single step in the direction
+impl From<Direction> for Vec2 {
+    fn from(direction: Direction) -> Self {
+        Compass::from(direction).into()
+    }
+}
+
+/// A single step in the direction, diagonal steps moving along both axes
+impl From<Compass> for Vec2 {
+    fn from(compass: Compass) -> Self {
+        let (d_row, d_col) = compass.offset();
+        Point::new(d_col, d_row)
+    }
+}
+
+/// A rectangular grid of tiles, stored row by row and indexed by (row, column)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    tiles: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, tile: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            tiles: vec![tile; width * height],
+        }
+    }
+
+    /// Builds a grid from its rows, which must all be the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height: rows.len(),
+            tiles: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a grid from the lines of `input` up to the first blank line, turning each
+    /// character into a tile with `tile`. `expected` describes the characters `tile` accepts.
+    pub fn parse(
+        input: &str,
+        expected: &str,
+        tile: impl Fn(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        Grid::parse_all(input, expected, tile).map_err(|mut errors| errors.swap_remove(0))
+    }
+
+    /// Like [`parse`](Grid::parse), but reports every character `tile` doesn't accept and
+    /// every row of the wrong length rather than stopping at the first
+    pub fn parse_all(
+        input: &str,
+        expected: &str,
+        tile: impl Fn(char) -> Option<T>,
+    ) -> Result<Self, Vec<ParseError>> {
+        let mut width = None;
+        let mut height = 0;
+        let mut tiles = vec![];
+        let mut errors = vec![];
+
+        for (line_index, line) in input
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .enumerate()
+        {
+            let mut row_width = 0;
+            for (col_index, c) in line.char_indices() {
+                match tile(c) {
+                    Some(t) => tiles.push(t),
+                    None => errors.push(ParseError::new(
+                        line_index,
+                        col_index,
+                        expected,
+                        c.to_string(),
+                    )),
+                }
+                row_width += 1;
+            }
+            match width {
+                Some(width) if width != row_width => {
+                    errors.push(ParseError::new(
+                        line_index,
+                        line.len(),
+                        format!("a row of {} tiles", width),
+                        format!("{} tiles", row_width),
+                    ));
+                }
+                _ => width = Some(row_width),
+            }
+            height += 1;
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            tiles,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width
+    }
+
+    /// Whether `point` lies on the grid
+    pub fn contains(&self, point: Point) -> bool {
+        point.to_row_col().is_some_and(|pos| self.in_bounds(pos))
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        if self.in_bounds(pos) {
+            Some(&self.tiles[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            Some(&mut self.tiles[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// The position `(d_row, d_col)` away from `pos`, if it's on the grid
+    pub fn offset(
+        &self,
+        (row, col): (usize, usize),
+        (d_row, d_col): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (
+            row.checked_add_signed(d_row)?,
+            col.checked_add_signed(d_col)?,
+        );
+        self.in_bounds(pos).then_some(pos)
+    }
+
+    /// The position one step from `pos` towards `dir`, if it's on the grid
+    pub fn step(&self, pos: (usize, usize), dir: Compass) -> Option<(usize, usize)> {
+        self.offset(pos, dir.offset())
+    }
+
+    /// Every position from `pos` towards `dir` until the edge of the grid, not including `pos`
+    pub fn ray(&self, pos: (usize, usize), dir: Compass) -> impl Iterator<Item = (usize, usize)> {
+        std::iter::successors(self.step(pos, dir), move |&pos| self.step(pos, dir))
+    }
+
+    /// The positions to the north, east, south and west of `pos` which are on the grid
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        Compass::CARDINALS
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir))
+    }
+
+    /// The positions around `pos`, including diagonally, which are on the grid
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        Compass::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.tiles[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        self.tiles.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    /// Every position on the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every tile along with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.tiles.iter())
+    }
+
+    /// The position of the first tile, row by row, which matches `predicate`
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, tile)| predicate(tile))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The position of the first tile equal to `tile`
+    pub fn position(&self, tile: &T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.find(|t| t == tile)
+    }
+
+    /// The positions of every tile equal to `tile`
+    pub fn positions_of<'a>(&'a self, tile: &'a T) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, t)| *t == tile)
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.iter().map(f).collect(),
+        }
+    }
+
+    /// Every position as a DOT digraph, with an edge to each neighbour to the north, east,
+    /// south or west which `connected` allows. Positions are labelled with their tile and
+    /// (row, column) unless the options say otherwise.
+    pub fn to_dot(
+        &self,
+        connected: impl Fn((usize, usize), (usize, usize)) -> bool,
+        options: &DotOptions<(usize, usize)>,
+    ) -> String
+    where
+        T: fmt::Display,
+    {
+        let neighbours = |&pos: &(usize, usize)| -> Vec<(usize, usize)> {
+            self.neighbours4(pos)
+                .filter(|&next| connected(pos, next))
+                .collect()
+        };
+        write_dot(&neighbours, self.positions(), options, |&(row, col)| {
+            format!("{} ({},{})", self[(row, col)], row, col)
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos).unwrap_or_else(|| {
+            panic!(
+                "{:?} is outside the {}x{} grid",
+                pos, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside the {}x{} grid", pos, width, height))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        match point.to_row_col() {
+            Some(pos) => &self[pos],
+            None => panic!(
+                "{} is outside the {}x{} grid",
+                point, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        match point.to_row_col() {
+            Some(pos) => &mut self[pos],
+            None => panic!(
+                "{} is outside the {}x{} grid",
+                point, self.width, self.height
+            ),
+        }
+    }
+}
+
+/// Draws the grid a row per line, with each tile drawn by its own `Display`
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 pub struct ListNode<T> {
     pub val: T,
     pub next: Option<Box<ListNode<T>>>,
@@ -58,54 +615,134 @@ impl<T> ListNode<T> {
     }
 }
 
-#[derive(Debug)]
-pub struct TreeNode<T>
-where
-    T: PartialEq,
-{
-    pub idx: usize,
+/// A node of an [`ArenaTree`], linked to the others by their indices in the arena
+#[derive(Debug, Clone)]
+pub struct TreeNode<K, T> {
+    idx: usize,
+    key: K,
     pub val: T,
-    pub parent: Option<usize>,
-    pub children: Vec<usize>,
+    parents: Vec<usize>,
+    children: Vec<usize>,
 }
 
-impl<T> TreeNode<T>
+impl<K, T> TreeNode<K, T> {
+    /// Where the node is in the arena
+    pub fn idx(&self) -> usize {
+        self.idx
+    }
+
+    /// What the node was added under, such as a grid position
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The nodes with an edge to this one, in the order the edges were added
+    pub fn parents(&self) -> &[usize] {
+        &self.parents
+    }
+
+    /// The nodes this one has an edge to, in the order the edges were added
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+}
+
+/// Nodes kept together in a `Vec` and linked by index, each added under a key which finds it
+/// again straight away. A node can have any number of parents as well as children, so
+/// DAGs and graphs with cycles can be held as well as trees.
+#[derive(Debug, Clone)]
+pub struct ArenaTree<K, T> {
+    arena: Vec<TreeNode<K, T>>,
+    index: HashMap<K, usize>,
+}
+
+impl<K, T> Default for ArenaTree<K, T> {
+    fn default() -> Self {
+        ArenaTree {
+            arena: vec![],
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<K, T> ArenaTree<K, T>
 where
-    T: PartialEq,
+    K: Eq + Hash + Clone,
 {
-    pub fn new(idx: usize, val: T) -> Self {
-        Self {
+    /// Adds a node for `key` holding `val`, unless there's one already. Returns the node's
+    /// index either way, and whether it was added.
+    pub fn add_node(&mut self, key: K, val: T) -> (usize, bool) {
+        if let Some(&idx) = self.index.get(&key) {
+            return (idx, false);
+        }
+        let idx = self.arena.len();
+        self.index.insert(key.clone(), idx);
+        self.arena.push(TreeNode {
             idx,
+            key,
             val,
-            parent: None,
+            parents: vec![],
             children: vec![],
+        });
+        (idx, true)
+    }
+
+    /// The index of the node added for `key`
+    pub fn find(&self, key: &K) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// Adds an edge from `parent` to `child`, unless there's one already. Returns whether it
+    /// was added.
+    pub fn add_edge(&mut self, parent: usize, child: usize) -> bool {
+        if self.has_edge(parent, child) {
+            return false;
         }
+        self.arena[parent].children.push(child);
+        self.arena[child].parents.push(parent);
+        true
     }
-}
 
-#[derive(Debug, Default)]
-pub struct ArenaTree<T>
-where
-    T: PartialEq,
-{
-    pub arena: Vec<TreeNode<T>>,
+    pub fn has_edge(&self, parent: usize, child: usize) -> bool {
+        self.arena[parent].children.contains(&child)
+    }
 }
 
-impl<T> ArenaTree<T>
-where
-    T: PartialEq,
-{
-    pub fn add_node(&mut self, val: T) -> usize {
-        // First see if it exists
-        for node in &self.arena {
-            if node.val == val {
-                return node.idx;
-            }
-        }
-        // Otherwise, add new node
-        let idx = self.arena.len();
-        self.arena.push(TreeNode::new(idx, val));
-        idx
+impl<K, T> ArenaTree<K, T> {
+    /// Every node, in the order they were added
+    pub fn nodes(&self) -> &[TreeNode<K, T>] {
+        &self.arena
+    }
+
+    pub fn parents(&self, idx: usize) -> &[usize] {
+        &self.arena[idx].parents
+    }
+
+    pub fn children(&self, idx: usize) -> &[usize] {
+        &self.arena[idx].children
+    }
+
+    /// Every edge as `(parent, child)`, grouped by parent
+    pub fn edge_list(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.arena
+            .iter()
+            .flat_map(|node| node.children.iter().map(move |&child| (node.idx, child)))
+    }
+
+    /// The nodes without parents
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.arena
+            .iter()
+            .filter(|node| node.parents.is_empty())
+            .map(|node| node.idx)
+    }
+
+    /// The nodes without children
+    pub fn leaves(&self) -> impl Iterator<Item = usize> + '_ {
+        self.arena
+            .iter()
+            .filter(|node| node.children.is_empty())
+            .map(|node| node.idx)
     }
 
     pub fn size(&self) -> usize {
@@ -118,11 +755,160 @@ where
             .fold(0, |acc, node| acc + node.children.len())
     }
 
+    /// The fewest edges between a root and `idx`, found by searching up through the parents
+    /// so that deep trees can't run out of stack. A node which no root leads to, because
+    /// it's on a cycle, counts as a root.
     pub fn depth(&self, idx: usize) -> usize {
-        match self.arena[idx].parent {
-            Some(id) => 1 + self.depth(id),
-            None => 0,
+        let up = |&idx: &usize| self.parents(idx).to_vec();
+        bfs(&up, idx)
+            .find(|&(node, _)| self.arena[node].parents.is_empty())
+            .map_or(0, |(_, depth)| depth)
+    }
+
+    /// The depth of every node, `None` for nodes which no root leads to
+    fn depths(&self) -> Vec<Option<usize>> {
+        let mut depths = vec![None; self.arena.len()];
+        let mut queue: VecDeque<usize> = self.roots().collect();
+        for &root in &queue {
+            depths[root] = Some(0);
+        }
+        while let Some(idx) = queue.pop_front() {
+            for &child in &self.arena[idx].children {
+                if depths[child].is_none() {
+                    depths[child] = depths[idx].map(|depth| depth + 1);
+                    queue.push_back(child);
+                }
+            }
+        }
+        depths
+    }
+
+    /// `root` and every node below it, each before its children. A node with more than
+    /// one parent is only visited the first time it's reached.
+    pub fn pre_order(&self, root: usize) -> impl Iterator<Item = usize> + '_ {
+        dfs(self, root)
+    }
+
+    /// `root` and every node below it, each after its children
+    pub fn post_order(&self, root: usize) -> PostOrder<'_, K, T> {
+        PostOrder {
+            tree: self,
+            stack: vec![(root, 0)],
+            seen: HashSet::from([root]),
+        }
+    }
+
+    /// `root` and every node below it, a level at a time
+    pub fn level_order(&self, root: usize) -> impl Iterator<Item = usize> + '_ {
+        bfs(self, root).map(|(idx, _)| idx)
+    }
+
+    /// Every path from `root` down to a leaf. A node with more than one parent is on a
+    /// path through each of them. In a graph with cycles a path also ends where every
+    /// child is already on it, rather than going round forever.
+    pub fn paths(&self, root: usize) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        let mut stack = vec![vec![root]];
+        while let Some(path) = stack.pop() {
+            let children: Vec<usize> = self.arena[*path.last().unwrap()]
+                .children
+                .iter()
+                .copied()
+                .filter(|child| !path.contains(child))
+                .collect();
+            if children.is_empty() {
+                paths.push(path);
+                continue;
+            }
+            for &child in children.iter().rev() {
+                let mut longer = path.clone();
+                longer.push(child);
+                stack.push(longer);
+            }
+        }
+        paths
+    }
+
+    /// How many nodes there are in the subtree under `idx`, including `idx`
+    pub fn subtree_size(&self, idx: usize) -> usize {
+        self.pre_order(idx).count()
+    }
+
+    /// How many leaves there are `depth` edges below a root
+    pub fn leaves_at_depth(&self, depth: usize) -> usize {
+        let depths = self.depths();
+        self.leaves()
+            .filter(|&leaf| depths[leaf] == Some(depth))
+            .count()
+    }
+
+    /// The deepest node which both `a` and `b` are under, counting each as under itself.
+    /// When nodes have several parents there may be more than one as deep, in which case
+    /// it's the one added first. `None` if they have nothing in common.
+    pub fn lowest_common_ancestor(&self, a: usize, b: usize) -> Option<usize> {
+        let up = |&idx: &usize| self.parents(idx).to_vec();
+        let ancestors: HashSet<usize> = bfs(&up, a).map(|(idx, _)| idx).collect();
+        let depths = self.depths();
+        bfs(&up, b)
+            .map(|(idx, _)| idx)
+            .filter(|idx| ancestors.contains(idx))
+            .max_by_key(|&idx| (depths[idx], Reverse(idx)))
+    }
+
+    /// Every node and edge as a DOT digraph, with nodes labelled by their value and index
+    /// unless the options say otherwise
+    pub fn to_dot(&self, options: &DotOptions<usize>) -> String
+    where
+        T: fmt::Display,
+    {
+        write_dot(self, 0..self.arena.len(), options, |&idx| {
+            format!("{} #{}", self.arena[idx].val, idx)
+        })
+    }
+}
+
+/// Post-order traversal of an [`ArenaTree`], made by [`ArenaTree::post_order`]
+pub struct PostOrder<'a, K, T> {
+    tree: &'a ArenaTree<K, T>,
+    /// The nodes on the way down, and how many of each one's children have been looked at
+    stack: Vec<(usize, usize)>,
+    seen: HashSet<usize>,
+}
+
+impl<K, T> Iterator for PostOrder<'_, K, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some((idx, looked_at)) = self.stack.last_mut() {
+            let idx = *idx;
+            match self.tree.arena[idx].children.get(*looked_at) {
+                Some(&child) => {
+                    *looked_at += 1;
+                    if self.seen.insert(child) {
+                        self.stack.push((child, 0));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    return Some(idx);
+                }
+            }
         }
+        None
+    }
+}
+
+impl<K, T> Index<usize> for ArenaTree<K, T> {
+    type Output = TreeNode<K, T>;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.arena[idx]
+    }
+}
+
+impl<K, T> IndexMut<usize> for ArenaTree<K, T> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.arena[idx]
     }
 }
 
@@ -132,25 +918,345 @@ mod tests {
 
     #[test]
     fn test_arena_tree() {
-        let mut tree: ArenaTree<u32> = ArenaTree::default();
-
-        let tree_node_1 = tree.add_node(1);
-        let tree_node_2 = tree.add_node(2);
-        let tree_node_3 = tree.add_node(3);
-        let tree_node_4 = tree.add_node(4);
-        let tree_node_5 = tree.add_node(5);
-
-        tree.arena[tree_node_1].children.push(tree_node_2);
-        tree.arena[tree_node_2].parent = Some(tree_node_1);
-        tree.arena[tree_node_2].children.push(tree_node_3);
-        tree.arena[tree_node_3].parent = Some(tree_node_2);
-        tree.arena[tree_node_3].children.push(tree_node_4);
-        tree.arena[tree_node_4].parent = Some(tree_node_3);
-        tree.arena[tree_node_4].children.push(tree_node_5);
-        tree.arena[tree_node_5].parent = Some(tree_node_4);
+        let mut tree: ArenaTree<u32, &str> = ArenaTree::default();
+
+        let (tree_node_1, _) = tree.add_node(1, "a");
+        let (tree_node_2, _) = tree.add_node(2, "b");
+        let (tree_node_3, _) = tree.add_node(3, "c");
+        let (tree_node_4, _) = tree.add_node(4, "d");
+        let (tree_node_5, _) = tree.add_node(5, "e");
+
+        tree.add_edge(tree_node_1, tree_node_2);
+        tree.add_edge(tree_node_2, tree_node_3);
+        tree.add_edge(tree_node_3, tree_node_4);
+        tree.add_edge(tree_node_4, tree_node_5);
 
         assert_eq!(tree.size(), 5);
         assert_eq!(tree.edges(), 4);
         assert_eq!(tree.depth(tree_node_5), 4);
     }
+
+    #[test]
+    fn test_arena_tree_keyed() {
+        let mut graph: ArenaTree<(usize, usize), u32> = ArenaTree::default();
+
+        let (top, added) = graph.add_node((0, 0), 0);
+        assert!(added);
+        assert_eq!((top, false), graph.add_node((0, 0), 9));
+        assert_eq!(0, graph[top].val);
+        assert_eq!(Some(top), graph.find(&(0, 0)));
+        assert_eq!(None, graph.find(&(5, 5)));
+
+        // A diamond, where the bottom has two parents
+        let (left, _) = graph.add_node((1, 0), 1);
+        let (right, _) = graph.add_node((0, 1), 1);
+        let (bottom, _) = graph.add_node((1, 1), 2);
+        for (parent, child) in [(top, left), (top, right), (left, bottom), (right, bottom)] {
+            assert!(graph.add_edge(parent, child));
+        }
+        assert!(!graph.add_edge(top, left));
+
+        assert_eq!(&[left, right], graph.parents(bottom));
+        assert_eq!(&[left, right], graph[top].children());
+        assert_eq!(&(1, 1), graph[bottom].key());
+        assert_eq!(4, graph.edges());
+        assert_eq!(
+            vec![(top, left), (top, right), (left, bottom), (right, bottom)],
+            graph.edge_list().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![top], graph.roots().collect::<Vec<_>>());
+        assert_eq!(vec![bottom], graph.leaves().collect::<Vec<_>>());
+        assert_eq!(2, graph.depth(bottom));
+        assert!(graph.has_edge(right, bottom) && !graph.has_edge(bottom, right));
+    }
+
+    /// a
+    /// ├── b
+    /// │   ├── d
+    /// │   └── e ─┐
+    /// └── c      │
+    ///     └──── f
+    fn sample_tree() -> (ArenaTree<char, ()>, [usize; 6]) {
+        let mut tree = ArenaTree::default();
+        let nodes = ['a', 'b', 'c', 'd', 'e', 'f'].map(|key| tree.add_node(key, ()).0);
+        let [a, b, c, d, e, f] = nodes;
+        for (parent, child) in [(a, b), (a, c), (b, d), (b, e), (e, f), (c, f)] {
+            tree.add_edge(parent, child);
+        }
+        (tree, nodes)
+    }
+
+    #[test]
+    fn test_arena_tree_traversals() {
+        let (tree, [a, b, c, ..]) = sample_tree();
+        let keys = |nodes: &mut dyn Iterator<Item = usize>| -> String {
+            nodes.map(|idx| *tree[idx].key()).collect()
+        };
+
+        assert_eq!("abdefc", keys(&mut tree.pre_order(a)));
+        assert_eq!("dfebca", keys(&mut tree.post_order(a)));
+        assert_eq!("abcdef", keys(&mut tree.level_order(a)));
+        assert_eq!("fc", keys(&mut tree.post_order(c)));
+        assert_eq!(
+            vec!["abd", "abef", "acf"],
+            tree.paths(a)
+                .iter()
+                .map(|path| keys(&mut path.iter().copied()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(6, tree.subtree_size(a));
+        assert_eq!(4, tree.subtree_size(b));
+        assert_eq!(2, tree.leaves_at_depth(2));
+        assert_eq!(0, tree.leaves_at_depth(3));
+        assert_eq!(0, tree.leaves_at_depth(1));
+    }
+
+    #[test]
+    fn test_arena_tree_paths_cyclic() {
+        let mut tree: ArenaTree<char, ()> = ArenaTree::default();
+        let [a, b, c] = ['a', 'b', 'c'].map(|key| tree.add_node(key, ()).0);
+        tree.add_edge(a, b);
+        tree.add_edge(b, a);
+        tree.add_edge(b, c);
+        tree.add_edge(c, c);
+
+        assert_eq!(vec![vec![a, b, c]], tree.paths(a));
+        assert_eq!(vec![vec![c]], tree.paths(c));
+    }
+
+    #[test]
+    fn test_arena_tree_lowest_common_ancestor() {
+        let (mut tree, [a, b, c, d, e, f]) = sample_tree();
+
+        assert_eq!(Some(b), tree.lowest_common_ancestor(d, e));
+        assert_eq!(Some(b), tree.lowest_common_ancestor(d, f));
+        assert_eq!(Some(a), tree.lowest_common_ancestor(d, c));
+        assert_eq!(Some(e), tree.lowest_common_ancestor(e, f));
+        assert_eq!(Some(d), tree.lowest_common_ancestor(d, d));
+
+        let (g, _) = tree.add_node('g', ());
+        assert_eq!(None, tree.lowest_common_ancestor(a, g));
+        assert_eq!(2, tree.depth(f));
+    }
+
+    #[test]
+    fn test_arena_tree_deep() {
+        let mut tree: ArenaTree<usize, ()> = ArenaTree::default();
+        let mut parent = tree.add_node(0, ()).0;
+        for key in 1..100_000 {
+            let (child, _) = tree.add_node(key, ());
+            tree.add_edge(parent, child);
+            parent = child;
+        }
+
+        assert_eq!(99_999, tree.depth(parent));
+        assert_eq!(Some(0), tree.post_order(0).last());
+        assert_eq!(1, tree.leaves_at_depth(99_999));
+        assert_eq!(Some(50_000), tree.lowest_common_ancestor(50_000, parent));
+    }
+
+    #[test]
+    fn test_arena_tree_to_dot() {
+        let mut tree: ArenaTree<char, u32> = ArenaTree::default();
+        let (a, _) = tree.add_node('a', 10);
+        let (b, _) = tree.add_node('b', 20);
+        let (c, _) = tree.add_node('c', 30);
+        tree.add_edge(a, b);
+        tree.add_edge(a, c);
+
+        assert_eq!(
+            "digraph \"G\" {
+    n0 [label=\"10 #0\"];
+    n1 [label=\"20 #1\"];
+    n2 [label=\"30 #2\"];
+    n0 -> n1;
+    n0 -> n2;
+}
+",
+            tree.to_dot(&DotOptions::new())
+        );
+        assert_eq!(
+            "digraph \"G\" {
+    n0 [label=\"a\", color=red, penwidth=2];
+    n1 [label=\"b\"];
+    n2 [label=\"c\", color=red, penwidth=2];
+    n0 -> n1;
+    n0 -> n2 [color=red, penwidth=2];
+}
+",
+            tree.to_dot(
+                &DotOptions::new()
+                    .label(|&idx| tree[idx].key().to_string())
+                    .highlight(vec![a, c])
+            )
+        );
+    }
+
+    #[test]
+    fn test_grid_to_dot() {
+        let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 2]]).unwrap();
+        let climbs = |from, to| grid[to] == grid[from] + 1;
+
+        assert_eq!(
+            "digraph \"G\" {
+    subgraph cluster_0 {
+        label=\"row 0\";
+        n0 [label=\"1 (0,0)\"];
+        n1 [label=\"2 (0,1)\"];
+    }
+    subgraph cluster_1 {
+        label=\"row 1\";
+        n2 [label=\"3 (1,0)\"];
+        n3 [label=\"2 (1,1)\"];
+    }
+    n0 -> n1;
+    n3 -> n2;
+}
+",
+            grid.to_dot(
+                climbs,
+                &DotOptions::new().cluster(|&(row, _)| Some(format!("row {}", row)))
+            )
+        );
+    }
+
+    #[test]
+    fn test_get_lines_missing_file() {
+        assert!(matches!(
+            get_lines("input/missing.txt"),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    fn digit_grid() -> Grid<u32> {
+        Grid::parse("123\n456\n789\n", "a digit", |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn test_grid_parse() {
+        let grid = digit_grid();
+
+        assert_eq!((3, 3), (grid.width(), grid.height()));
+        assert_eq!(6, grid[(1, 2)]);
+        assert_eq!(None, grid.get((3, 0)));
+        assert_eq!("123\n456\n789\n", grid.to_string());
+    }
+
+    #[test]
+    fn test_grid_parse_stops_at_blank_line() {
+        let grid = Grid::parse("ab\ncd\n\nnot a row", "a letter", Some).unwrap();
+
+        assert_eq!((2, 2), (grid.width(), grid.height()));
+    }
+
+    #[test]
+    fn test_grid_parse_invalid() {
+        assert_eq!(
+            Err(ParseError::new(1, 1, "a digit", "x")),
+            Grid::parse("12\n3x\n", "a digit", |c| c.to_digit(10))
+        );
+        assert_eq!(
+            Err(ParseError::new(1, 3, "a row of 2 tiles", "3 tiles")),
+            Grid::parse("12\n345\n", "a digit", |c| c.to_digit(10))
+        );
+    }
+
+    #[test]
+    fn test_grid_parse_all_invalid() {
+        assert_eq!(
+            Err(vec![
+                ParseError::new(0, 1, "a digit", "x"),
+                ParseError::new(1, 3, "a row of 2 tiles", "3 tiles"),
+                ParseError::new(2, 0, "a digit", "y"),
+            ]),
+            Grid::parse_all("1x\n345\ny6\n", "a digit", |c| c.to_digit(10))
+        );
+    }
+
+    #[test]
+    fn test_validate_lines() {
+        let parse = |line: &str| parse_field::<u32>(0, line, line.trim(), "a number");
+
+        assert_eq!(
+            vec![
+                ParseError::new(1, 1, "a number", "x"),
+                ParseError::new(3, 0, "a number", "-2")
+            ],
+            validate_lines("1\n x\n3\n-2\n", parse)
+        );
+    }
+
+    #[test]
+    fn test_grid_neighbours() {
+        let grid = digit_grid();
+
+        let corner: Vec<u32> = grid.neighbours4((0, 0)).map(|pos| grid[pos]).collect();
+        assert_eq!(vec![2, 4], corner);
+
+        let centre: Vec<u32> = grid.neighbours8((1, 1)).map(|pos| grid[pos]).collect();
+        assert_eq!(vec![2, 3, 6, 9, 8, 7, 4, 1], centre);
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        let grid = digit_grid();
+
+        assert_eq!(&[4, 5, 6], grid.row(1));
+        assert_eq!(vec![&2, &5, &8], grid.column(1).collect::<Vec<_>>());
+
+        let diagonal: Vec<u32> = grid
+            .ray((0, 0), Compass::SouthEast)
+            .map(|pos| grid[pos])
+            .collect();
+        assert_eq!(vec![5, 9], diagonal);
+    }
+
+    #[test]
+    fn test_grid_find() {
+        let mut grid = digit_grid();
+        grid[(2, 0)] = 5;
+
+        assert_eq!(Some((1, 1)), grid.position(&5));
+        assert_eq!(
+            vec![(1, 1), (2, 0)],
+            grid.positions_of(&5).collect::<Vec<_>>()
+        );
+        assert_eq!(Some((2, 1)), grid.find(|&tile| tile > 7));
+    }
+
+    #[test]
+    fn test_point_arithmetic() {
+        let point = Point::new(3, -2);
+        let step = Vec2::from(Direction::N);
+
+        assert_eq!(Point::new(3, -3), point + step);
+        assert_eq!(Point::new(3, 2), point + step * -4);
+        assert_eq!(Vec2::new(2, -3), point - Point::new(1, 1));
+        assert_eq!(Point::new(-3, 2), -point);
+        assert_eq!(7, point.manhattan(Point::new(0, 2)));
+        assert_eq!(Point::new(3, 5), point.wrap(11, 7));
+    }
+
+    #[test]
+    fn test_point_row_col() {
+        let grid = digit_grid();
+        let point = Point::from_row_col((2, 1));
+
+        assert_eq!(Point::new(1, 2), point);
+        assert_eq!(Some((2, 1)), point.to_row_col());
+        assert_eq!(None, Point::new(-1, 0).to_row_col());
+        assert_eq!(8, grid[point]);
+        assert!(!grid.contains(Point::new(3, 0)));
+    }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::E, Direction::N.turn_right());
+        assert_eq!(Direction::W, Direction::N.turn_left());
+        assert_eq!(Direction::S, Direction::N.opposite());
+        assert_eq!(Compass::SouthEast, Compass::NorthEast.turn_right());
+        assert_eq!(Compass::NorthWest, Compass::NorthEast.turn_left());
+        assert_eq!(Compass::SouthWest, Compass::NorthEast.opposite());
+        assert_eq!(Vec2::new(1, 1), Compass::SouthEast.into());
+    }
 }
@@ -0,0 +1,213 @@
+//! Writing graphs in Graphviz's DOT language, to look at with `dot -Tsvg`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Debug, Write},
+    hash::Hash,
+};
+
+use super::graph::Neighbours;
+
+/// Gives each node a label
+type Label<'a, N> = Box<dyn Fn(&N) -> String + 'a>;
+
+/// Gives each node the name of the cluster it's drawn in, if any
+type Cluster<'a, N> = Box<dyn Fn(&N) -> Option<String> + 'a>;
+
+/// What to write besides the nodes and edges themselves
+pub struct DotOptions<'a, N> {
+    name: String,
+    label: Option<Label<'a, N>>,
+    cluster: Option<Cluster<'a, N>>,
+    highlights: Vec<Vec<N>>,
+}
+
+impl<N> Default for DotOptions<'_, N> {
+    fn default() -> Self {
+        DotOptions {
+            name: String::from("G"),
+            label: None,
+            cluster: None,
+            highlights: vec![],
+        }
+    }
+}
+
+impl<'a, N> DotOptions<'a, N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What the graph is called, `G` unless set
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Labels each node with `label`, in place of whatever it would have been labelled with
+    pub fn label(mut self, label: impl Fn(&N) -> String + 'a) -> Self {
+        self.label = Some(Box::new(label));
+        self
+    }
+
+    /// Draws nodes together in a box when `cluster` gives them the same name. Nodes it
+    /// gives no name are drawn outside any box.
+    pub fn cluster(mut self, cluster: impl Fn(&N) -> Option<String> + 'a) -> Self {
+        self.cluster = Some(Box::new(cluster));
+        self
+    }
+
+    /// Draws the nodes of `path`, and the edges between one and the next, in red
+    pub fn highlight(mut self, path: Vec<N>) -> Self {
+        self.highlights.push(path);
+        self
+    }
+}
+
+const HIGHLIGHT: &str = "color=red, penwidth=2";
+
+/// `nodes` and the edges between them as a DOT digraph, labelled by their debug formatting
+/// unless the options say otherwise. Neighbours which aren't in `nodes` are left out.
+pub fn to_dot<N, G>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = N>,
+    options: &DotOptions<N>,
+) -> String
+where
+    N: Clone + Eq + Hash + Debug,
+    G: Neighbours<N>,
+{
+    write_dot(graph, nodes, options, |node| format!("{:?}", node))
+}
+
+/// As for [`to_dot`], with `default_label` for labelling nodes when the options don't
+pub(crate) fn write_dot<N, G>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = N>,
+    options: &DotOptions<N>,
+    default_label: impl Fn(&N) -> String,
+) -> String
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    let nodes: Vec<N> = nodes.into_iter().collect();
+    let ids: HashMap<&N, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(id, node)| (node, id))
+        .collect();
+    let highlighted_nodes: HashSet<&N> = options.highlights.iter().flatten().collect();
+    let highlighted_edges: HashSet<(&N, &N)> = options
+        .highlights
+        .iter()
+        .flat_map(|path| path.iter().zip(path.iter().skip(1)))
+        .collect();
+
+    let node_line = |node: &N| {
+        let label = match &options.label {
+            Some(label) => label(node),
+            None => default_label(node),
+        };
+        let mut line = format!("n{} [label={}", ids[node], quote(&label));
+        if highlighted_nodes.contains(node) {
+            line.push_str(", ");
+            line.push_str(HIGHLIGHT);
+        }
+        line.push_str("];");
+        line
+    };
+
+    // Clusters in the order their first node comes
+    let mut clusters: Vec<(String, Vec<&N>)> = vec![];
+    let mut unclustered = vec![];
+    for node in &nodes {
+        match options.cluster.as_ref().and_then(|cluster| cluster(node)) {
+            Some(name) => match clusters.iter_mut().find(|(cluster, _)| *cluster == name) {
+                Some((_, members)) => members.push(node),
+                None => clusters.push((name, vec![node])),
+            },
+            None => unclustered.push(node),
+        }
+    }
+
+    let mut dot = format!("digraph {} {{\n", quote(&options.name));
+    for (i, (name, members)) in clusters.iter().enumerate() {
+        writeln!(dot, "    subgraph cluster_{} {{", i).unwrap();
+        writeln!(dot, "        label={};", quote(name)).unwrap();
+        for node in members {
+            writeln!(dot, "        {}", node_line(node)).unwrap();
+        }
+        writeln!(dot, "    }}").unwrap();
+    }
+    for node in unclustered {
+        writeln!(dot, "    {}", node_line(node)).unwrap();
+    }
+    for node in &nodes {
+        for next in graph.neighbours(node) {
+            let Some(next_id) = ids.get(&next) else {
+                continue;
+            };
+            write!(dot, "    n{} -> n{}", ids[node], next_id).unwrap();
+            if highlighted_edges.contains(&(node, &next)) {
+                write!(dot, " [{}]", HIGHLIGHT).unwrap();
+            }
+            writeln!(dot, ";").unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// A DOT string, quoted and escaped
+fn quote(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_dot() {
+        let next = |&node: &u32| -> Vec<u32> { vec![node * 2, node * 3] };
+        let options = DotOptions::new()
+            .name("multiples")
+            .label(|node: &u32| format!("x{}", node))
+            .cluster(|&node| (node % 2 == 0).then(|| String::from("even")))
+            .highlight(vec![1, 3, 6]);
+
+        assert_eq!(
+            "digraph \"multiples\" {
+    subgraph cluster_0 {
+        label=\"even\";
+        n1 [label=\"x2\"];
+        n3 [label=\"x6\", color=red, penwidth=2];
+    }
+    n0 [label=\"x1\", color=red, penwidth=2];
+    n2 [label=\"x3\", color=red, penwidth=2];
+    n0 -> n1;
+    n0 -> n2 [color=red, penwidth=2];
+    n1 -> n3;
+    n2 -> n3 [color=red, penwidth=2];
+}
+",
+            to_dot(&next, [1, 2, 3, 6], &options)
+        );
+    }
+
+    #[test]
+    fn test_to_dot_default_label() {
+        let next = |_: &(usize, usize)| vec![];
+        assert_eq!(
+            "digraph \"G\" {\n    n0 [label=\"(0, 1)\"];\n}\n",
+            to_dot(&next, [(0, 1)], &DotOptions::new())
+        );
+        assert_eq!("\"say \\\"hi\\\"\\n\"", quote("say \"hi\"\n"));
+    }
+}
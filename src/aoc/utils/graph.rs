@@ -0,0 +1,722 @@
+//! Searches over anything whose nodes lead on to other nodes, whether that's positions on a
+//! [`Grid`], nodes in an [`ArenaTree`] or a closure working them out as it goes.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+use itertools::Itertools;
+
+use super::{ArenaTree, Grid};
+
+/// The nodes which can be reached in one step from another
+pub trait Neighbours<N> {
+    fn neighbours(&self, node: &N) -> Vec<N>;
+}
+
+impl<N, F> Neighbours<N> for F
+where
+    F: Fn(&N) -> Vec<N>,
+{
+    fn neighbours(&self, node: &N) -> Vec<N> {
+        self(node)
+    }
+}
+
+/// Every position to the north, east, south and west which is on the grid
+impl<T> Neighbours<(usize, usize)> for Grid<T> {
+    fn neighbours(&self, &pos: &(usize, usize)) -> Vec<(usize, usize)> {
+        self.neighbours4(pos).collect()
+    }
+}
+
+/// A node's children
+impl<K, T> Neighbours<usize> for ArenaTree<K, T> {
+    fn neighbours(&self, &idx: &usize) -> Vec<usize> {
+        self.children(idx).to_vec()
+    }
+}
+
+/// The nodes which can be reached in one step from another, and what each step costs
+pub trait WeightedNeighbours<N> {
+    fn weighted_neighbours(&self, node: &N) -> Vec<(N, usize)>;
+}
+
+impl<N, F> WeightedNeighbours<N> for F
+where
+    F: Fn(&N) -> Vec<(N, usize)>,
+{
+    fn weighted_neighbours(&self, node: &N) -> Vec<(N, usize)> {
+        self(node)
+    }
+}
+
+/// Breadth first search, made by [`bfs`]
+pub struct Bfs<'g, N, G> {
+    graph: &'g G,
+    queue: VecDeque<(N, usize)>,
+    seen: HashSet<N>,
+}
+
+impl<N, G> Iterator for Bfs<'_, N, G>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    /// A node, and the fewest steps it takes to get there
+    type Item = (N, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, steps) = self.queue.pop_front()?;
+        for next in self.graph.neighbours(&node) {
+            if self.seen.insert(next.clone()) {
+                self.queue.push_back((next, steps + 1));
+            }
+        }
+        Some((node, steps))
+    }
+}
+
+/// Every node which can be reached from `start`, nearest first
+pub fn bfs<N, G>(graph: &G, start: N) -> Bfs<'_, N, G>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    Bfs {
+        graph,
+        seen: HashSet::from([start.clone()]),
+        queue: VecDeque::from([(start, 0)]),
+    }
+}
+
+/// Depth first search, made by [`dfs`]
+pub struct Dfs<'g, N, G> {
+    graph: &'g G,
+    stack: Vec<N>,
+    seen: HashSet<N>,
+}
+
+impl<N, G> Iterator for Dfs<'_, N, G>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        while let Some(node) = self.stack.pop() {
+            if !self.seen.insert(node.clone()) {
+                continue;
+            }
+            // Reversed, so that the first neighbour is the first followed
+            let mut neighbours = self.graph.neighbours(&node);
+            neighbours.retain(|next| !self.seen.contains(next));
+            self.stack.extend(neighbours.into_iter().rev());
+            return Some(node);
+        }
+        None
+    }
+}
+
+/// Every node which can be reached from `start`, following each path as far as it goes
+/// before trying the next. Each node comes before the ones found through it.
+pub fn dfs<N, G>(graph: &G, start: N) -> Dfs<'_, N, G>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    Dfs {
+        graph,
+        stack: vec![start],
+        seen: HashSet::new(),
+    }
+}
+
+/// A way from one node to another, and what it costs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N> {
+    /// From the start to the end, including both
+    pub nodes: Vec<N>,
+    pub cost: usize,
+}
+
+/// A node waiting to be searched from, the one with the lowest estimate first
+struct State<N> {
+    estimate: usize,
+    cost: usize,
+    node: N,
+}
+
+impl<N> Ord for State<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as the heap gives the greatest first. Of equal estimates, the one
+        // furthest along is likely nearer the goal.
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then(self.cost.cmp(&other.cost))
+    }
+}
+
+impl<N> PartialOrd for State<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> PartialEq for State<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for State<N> {}
+
+/// The cheapest path from `start` to a node which `is_goal`, if there is one
+pub fn dijkstra<N, G>(graph: &G, start: N, is_goal: impl Fn(&N) -> bool) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    G: WeightedNeighbours<N>,
+{
+    astar(graph, start, is_goal, |_| 0)
+}
+
+/// The cheapest path from `start` to a node which `is_goal`, searching first where
+/// `heuristic` suggests. The heuristic must never be more than the cheapest cost to a goal,
+/// otherwise the path found may not be the cheapest.
+pub fn astar<N, G>(
+    graph: &G,
+    start: N,
+    is_goal: impl Fn(&N) -> bool,
+    heuristic: impl Fn(&N) -> usize,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    G: WeightedNeighbours<N>,
+{
+    let mut costs: HashMap<N, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut previous: HashMap<N, N> = HashMap::new();
+    let mut open = BinaryHeap::from([State {
+        estimate: heuristic(&start),
+        cost: 0,
+        node: start,
+    }]);
+
+    while let Some(State { cost, node, .. }) = open.pop() {
+        if cost > costs[&node] {
+            // Already reached more cheaply
+            continue;
+        }
+        if is_goal(&node) {
+            let mut nodes = vec![node];
+            while let Some(prev) = previous.get(nodes.last().unwrap()) {
+                nodes.push(prev.clone());
+            }
+            nodes.reverse();
+            return Some(Path { nodes, cost });
+        }
+        for (next, step) in graph.weighted_neighbours(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|&best| next_cost < best) {
+                costs.insert(next.clone(), next_cost);
+                previous.insert(next.clone(), node.clone());
+                open.push(State {
+                    estimate: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// The cheapest cost to every node reachable from a start, and every way of getting
+/// there at that cost, made by [`all_shortest_paths`]
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N> {
+    start: N,
+    costs: HashMap<N, usize>,
+    /// The nodes each node is reached from on a cheapest path, in the order they were found
+    previous: HashMap<N, Vec<N>>,
+}
+
+impl<N> ShortestPaths<N>
+where
+    N: Clone + Eq + Hash,
+{
+    /// The cheapest cost to `node`, if it can be reached
+    pub fn cost(&self, node: &N) -> Option<usize> {
+        self.costs.get(node).copied()
+    }
+
+    /// One of the cheapest paths to `node`, if it can be reached
+    pub fn path_to(&self, node: &N) -> Option<Path<N>> {
+        let cost = self.cost(node)?;
+        let mut nodes = vec![node.clone()];
+        while let Some(prev) = self.previous.get(nodes.last().unwrap()) {
+            nodes.push(prev[0].clone());
+        }
+        nodes.reverse();
+        Some(Path { nodes, cost })
+    }
+
+    /// Every one of the cheapest paths to `node`, which may be a great many
+    pub fn paths_to(&self, node: &N) -> Vec<Vec<N>> {
+        if self.cost(node).is_none() {
+            return vec![];
+        }
+        if *node == self.start {
+            return vec![vec![node.clone()]];
+        }
+        self.previous[node]
+            .iter()
+            .flat_map(|prev| self.paths_to(prev))
+            .map(|mut path| {
+                path.push(node.clone());
+                path
+            })
+            .collect()
+    }
+}
+
+/// The cheapest paths from `start` to every node it can reach, keeping each way of
+/// reaching a node when several cost the same
+pub fn all_shortest_paths<N, G>(graph: &G, start: N) -> ShortestPaths<N>
+where
+    N: Clone + Eq + Hash,
+    G: WeightedNeighbours<N>,
+{
+    let mut costs: HashMap<N, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut previous: HashMap<N, Vec<N>> = HashMap::new();
+    let mut open = BinaryHeap::from([State {
+        estimate: 0,
+        cost: 0,
+        node: start.clone(),
+    }]);
+
+    while let Some(State { cost, node, .. }) = open.pop() {
+        if cost > costs[&node] {
+            continue;
+        }
+        for (next, step) in graph.weighted_neighbours(&node) {
+            let next_cost = cost + step;
+            match costs.get(&next) {
+                Some(&best) if next_cost > best => continue,
+                Some(&best) if next_cost == best => {
+                    // A step costing nothing could lead back to a node `node` is reached
+                    // from, which would make the paths go round in circles. The start is
+                    // always one of them, so it never has a previous node.
+                    if step == 0 && reached_from(&previous, &node, &next) {
+                        continue;
+                    }
+                    let prev = previous.entry(next).or_default();
+                    if !prev.contains(&node) {
+                        prev.push(node.clone());
+                    }
+                }
+                _ => {
+                    costs.insert(next.clone(), next_cost);
+                    previous.insert(next.clone(), vec![node.clone()]);
+                    open.push(State {
+                        estimate: next_cost,
+                        cost: next_cost,
+                        node: next,
+                    });
+                }
+            }
+        }
+    }
+    ShortestPaths {
+        start,
+        costs,
+        previous,
+    }
+}
+
+/// Whether `node` is `other`, or is reached from it on one of the cheapest paths found so far
+fn reached_from<N>(previous: &HashMap<N, Vec<N>>, node: &N, other: &N) -> bool
+where
+    N: Clone + Eq + Hash,
+{
+    let mut seen = HashSet::from([node]);
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node == other {
+            return true;
+        }
+        for prev in previous.get(node).into_iter().flatten() {
+            if seen.insert(prev) {
+                stack.push(prev);
+            }
+        }
+    }
+    false
+}
+
+/// Nodes which lead round in a circle, each leading to the next and the last to the first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N> {
+    pub nodes: Vec<N>,
+}
+
+impl<N: fmt::Display> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle through ")?;
+        for node in &self.nodes {
+            write!(f, "{} -> ", node)?;
+        }
+        match self.nodes.first() {
+            Some(first) => write!(f, "{}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `nodes` ordered so that each comes before every node it leads to, the same order every
+/// time they're given in the same order. A node given more than once is only sorted once,
+/// and neighbours which aren't in `nodes` are ignored. Fails with one of the cycles if
+/// there are any, as then there's no such order.
+pub fn topological_sort<N, G>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = N>,
+) -> Result<Vec<N>, Cycle<N>>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    let nodes: Vec<N> = nodes.into_iter().unique().collect();
+    let mut edges: HashMap<N, Vec<N>> = nodes.iter().map(|node| (node.clone(), vec![])).collect();
+    for node in &nodes {
+        let next: Vec<N> = graph
+            .neighbours(node)
+            .into_iter()
+            .filter(|next| edges.contains_key(next))
+            .collect();
+        edges.insert(node.clone(), next);
+    }
+
+    let mut in_degree: HashMap<&N, usize> = nodes.iter().map(|node| (node, 0)).collect();
+    for next in edges.values().flatten() {
+        *in_degree.get_mut(next).unwrap() += 1;
+    }
+
+    let mut ready: VecDeque<&N> = nodes.iter().filter(|node| in_degree[node] == 0).collect();
+    let mut sorted = Vec::with_capacity(nodes.len());
+    while let Some(node) = ready.pop_front() {
+        sorted.push(node.clone());
+        for next in &edges[node] {
+            let degree = in_degree.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+    if sorted.len() == nodes.len() {
+        return Ok(sorted);
+    }
+
+    // Everything left is led to by something else left, so going backwards from any of
+    // them has to come round in a circle
+    let mut previous: HashMap<&N, &N> = HashMap::new();
+    for node in nodes.iter().filter(|node| in_degree[node] > 0) {
+        for next in edges[node].iter().filter(|next| in_degree[next] > 0) {
+            previous.entry(next).or_insert(node);
+        }
+    }
+    let mut node = nodes.iter().find(|node| in_degree[node] > 0).unwrap();
+    let mut walked: Vec<&N> = vec![];
+    while !walked.contains(&node) {
+        walked.push(node);
+        node = previous[node];
+    }
+    let start = walked.iter().position(|&walked| walked == node).unwrap();
+    let mut cycle: Vec<N> = walked[start..].iter().map(|&node| node.clone()).collect();
+    cycle.reverse();
+    Err(Cycle { nodes: cycle })
+}
+
+/// What Tarjan's algorithm keeps track of while searching
+struct Tarjan<N> {
+    /// The order each node was found in
+    order: HashMap<N, usize>,
+    /// The earliest found node each node can get back to
+    low: HashMap<N, usize>,
+    stack: Vec<N>,
+    on_stack: HashSet<N>,
+    /// Each node being searched from, with its neighbours and how many of them have been
+    /// looked at, so that deep graphs don't run out of stack
+    calls: Vec<(N, Vec<N>, usize)>,
+}
+
+impl<N: Clone + Eq + Hash> Tarjan<N> {
+    fn visit(&mut self, node: &N, neighbours: Vec<N>) {
+        let order = self.order.len();
+        self.order.insert(node.clone(), order);
+        self.low.insert(node.clone(), order);
+        self.stack.push(node.clone());
+        self.on_stack.insert(node.clone());
+        self.calls.push((node.clone(), neighbours, 0));
+    }
+
+    fn lower(&mut self, node: &N, low: usize) {
+        let lowest = self.low[node].min(low);
+        self.low.insert(node.clone(), lowest);
+    }
+}
+
+/// Groups `nodes` so that each node can reach every other in its group, using Tarjan's
+/// algorithm. Every group comes after the groups it leads to. Neighbours which aren't in
+/// `nodes` are ignored.
+pub fn strongly_connected_components<N, G>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = N>,
+) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    G: Neighbours<N>,
+{
+    let nodes: Vec<N> = nodes.into_iter().unique().collect();
+    let included: HashSet<&N> = nodes.iter().collect();
+    let neighbours = |node: &N| -> Vec<N> {
+        graph
+            .neighbours(node)
+            .into_iter()
+            .filter(|next| included.contains(next))
+            .collect()
+    };
+
+    let mut tarjan = Tarjan {
+        order: HashMap::new(),
+        low: HashMap::new(),
+        stack: vec![],
+        on_stack: HashSet::new(),
+        calls: vec![],
+    };
+    let mut components = vec![];
+
+    for root in &nodes {
+        if tarjan.order.contains_key(root) {
+            continue;
+        }
+        tarjan.visit(root, neighbours(root));
+
+        while let Some((node, next, looked_at)) = tarjan.calls.last_mut() {
+            if let Some(next) = next.get(*looked_at).cloned() {
+                *looked_at += 1;
+                let node = node.clone();
+                if !tarjan.order.contains_key(&next) {
+                    tarjan.visit(&next, neighbours(&next));
+                } else if tarjan.on_stack.contains(&next) {
+                    tarjan.lower(&node, tarjan.order[&next]);
+                }
+                continue;
+            }
+
+            let node = node.clone();
+            tarjan.calls.pop();
+            if tarjan.low[&node] == tarjan.order[&node] {
+                let mut component = vec![];
+                loop {
+                    let member = tarjan.stack.pop().unwrap();
+                    tarjan.on_stack.remove(&member);
+                    let done = member == node;
+                    component.push(member);
+                    if done {
+                        break;
+                    }
+                }
+                component.reverse();
+                components.push(component);
+            }
+            if let Some((parent, _, _)) = tarjan.calls.last() {
+                let parent = parent.clone();
+                tarjan.lower(&parent, tarjan.low[&node]);
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A maze where `#` is a wall
+    fn maze(rows: &[&str]) -> Grid<char> {
+        Grid::from_rows(rows.iter().map(|row| row.chars().collect()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze(&["..#", ".##", "..."]);
+        let open = |&pos: &(usize, usize)| -> Vec<(usize, usize)> {
+            maze.neighbours4(pos)
+                .filter(|&next| maze[next] != '#')
+                .collect()
+        };
+
+        let steps: HashMap<(usize, usize), usize> = bfs(&open, (0, 0)).collect();
+        assert_eq!(6, steps.len());
+        assert_eq!(Some(&4), steps.get(&(2, 2)));
+        assert_eq!(None, steps.get(&(0, 2)));
+
+        // Everything on an open grid, nearest first
+        let grid = Grid::new(3, 3, 0);
+        let steps: Vec<usize> = bfs(&grid, (1, 1)).map(|(_, steps)| steps).collect();
+        assert_eq!(vec![0, 1, 1, 1, 1, 2, 2, 2, 2], steps);
+    }
+
+    #[test]
+    fn test_dfs() {
+        let mut tree: ArenaTree<char, ()> = ArenaTree::default();
+        let [a, b, c, d, e] = ['a', 'b', 'c', 'd', 'e'].map(|key| tree.add_node(key, ()).0);
+        for (parent, child) in [(a, b), (a, c), (b, d), (c, d), (d, e), (e, a)] {
+            tree.add_edge(parent, child);
+        }
+
+        let keys: String = dfs(&tree, a).map(|idx| *tree[idx].key()).collect();
+        assert_eq!("abdec", keys);
+        let keys: String = dfs(&tree, c).map(|idx| *tree[idx].key()).collect();
+        assert_eq!("cdeab", keys);
+    }
+
+    /// Each step onto a digit costs that digit
+    fn weighted(grid: &Grid<char>) -> impl WeightedNeighbours<(usize, usize)> + '_ {
+        |&pos: &(usize, usize)| {
+            grid.neighbours4(pos)
+                .filter_map(|next| Some((next, grid[next].to_digit(10)? as usize)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_dijkstra_astar() {
+        let grid = maze(&["1163", "1381", "2136"]);
+        let goal = (2, 3);
+        let path = dijkstra(&weighted(&grid), (0, 0), |&pos| pos == goal).unwrap();
+        assert_eq!(13, path.cost);
+        assert_eq!(
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (2, 3)],
+            path.nodes
+        );
+
+        let manhattan = |&(row, col): &(usize, usize)| goal.0.abs_diff(row) + goal.1.abs_diff(col);
+        assert_eq!(
+            Some(path),
+            astar(&weighted(&grid), (0, 0), |&pos| pos == goal, manhattan)
+        );
+
+        let walled = maze(&["1#1"]);
+        assert_eq!(
+            None,
+            dijkstra(&weighted(&walled), (0, 0), |&pos| pos == (0, 2))
+        );
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let grid = maze(&["111", "1#1", "111"]);
+        let paths = all_shortest_paths(&weighted(&grid), (0, 0));
+
+        assert_eq!(Some(4), paths.cost(&(2, 2)));
+        assert_eq!(None, paths.cost(&(1, 1)));
+        assert_eq!(Some(0), paths.path_to(&(0, 0)).map(|path| path.cost));
+        assert_eq!(
+            vec![
+                vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)],
+                vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            ],
+            paths.paths_to(&(2, 2))
+        );
+        assert!(paths.paths_to(&(1, 1)).is_empty());
+        assert_eq!(5, paths.path_to(&(2, 2)).unwrap().nodes.len());
+    }
+
+    #[test]
+    fn test_all_shortest_paths_free_steps() {
+        // Steps between 0 and 1, and from 2 to itself, cost nothing
+        let graph = |&node: &u32| -> Vec<(u32, usize)> {
+            match node {
+                0 => vec![(1, 0), (2, 1)],
+                1 => vec![(0, 0), (2, 1)],
+                _ => vec![(2, 0)],
+            }
+        };
+        let paths = all_shortest_paths(&graph, 0);
+
+        assert_eq!(Some(1), paths.cost(&2));
+        assert_eq!(vec![0], paths.path_to(&0).unwrap().nodes);
+        assert_eq!(vec![vec![0]], paths.paths_to(&0));
+        assert_eq!(vec![vec![0, 1]], paths.paths_to(&1));
+        assert_eq!(vec![vec![0, 2], vec![0, 1, 2]], paths.paths_to(&2));
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let rules = [(47, 53), (97, 13), (97, 47), (75, 53), (53, 13), (75, 47)];
+        let before = |page: &u32| -> Vec<u32> {
+            rules
+                .iter()
+                .filter(|(left, _)| left == page)
+                .map(|&(_, right)| right)
+                .collect()
+        };
+
+        assert_eq!(
+            Ok(vec![75, 97, 47, 53, 13]),
+            topological_sort(&before, [75, 97, 47, 13, 53])
+        );
+        // Pages which aren't being sorted don't matter
+        assert_eq!(Ok(vec![13, 75]), topological_sort(&before, [13, 75]));
+        // Nor does a page being given twice
+        assert_eq!(
+            Ok(vec![97, 13, 47]),
+            topological_sort(&before, [13, 47, 97, 13, 47])
+        );
+
+        // 0, 1 and 2 lead round in a circle, and all lead to 3
+        let cyclic = |&page: &u32| match page {
+            3 => vec![],
+            _ => vec![(page + 1) % 3, 3],
+        };
+        let cycle = topological_sort(&cyclic, [3, 2, 1, 0]).unwrap_err();
+        assert_eq!(vec![0, 1, 2], cycle.nodes);
+        assert_eq!("cycle through 0 -> 1 -> 2 -> 0", cycle.to_string());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let edges: HashMap<u32, Vec<u32>> = HashMap::from([
+            (1, vec![2]),
+            (2, vec![3, 4]),
+            (3, vec![1]),
+            (4, vec![5]),
+            (5, vec![6]),
+            (6, vec![4, 7]),
+            (7, vec![]),
+        ]);
+        let next = |node: &u32| edges[node].clone();
+
+        assert_eq!(
+            vec![vec![7], vec![4, 5, 6], vec![1, 2, 3]],
+            strongly_connected_components(&next, 1..=7)
+        );
+        assert_eq!(
+            vec![vec![5], vec![4]],
+            strongly_connected_components(&next, [4, 5])
+        );
+
+        // A long chain doesn't run out of stack
+        let chain = |&node: &u32| vec![node + 1];
+        assert_eq!(
+            100_000,
+            strongly_connected_components(&chain, 0..100_000).len()
+        );
+    }
+}
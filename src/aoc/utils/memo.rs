@@ -0,0 +1,275 @@
+//! Remembering what a recursive function returned for each argument, so that it's only
+//! worked out once.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+/// How well a [`Memo`] has been doing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Calls answered from the cache, not counting those made again by
+    /// [`call_iterative`](Memo::call_iterative) once what they were missing is known
+    pub hits: usize,
+    /// Calls which had to be worked out
+    pub misses: usize,
+    /// How many answers are cached
+    pub size: usize,
+}
+
+/// The function a [`Memo`] remembers the answers of
+type Function<'a, A, R> = Rc<dyn Fn(&mut Memo<'a, A, R>, &A) -> R + 'a>;
+
+/// A recursive function and the answers it has given. The function is passed the memo
+/// so that it can recurse through [`call`](Memo::call) rather than calling itself, and can
+/// be a closure borrowing whatever else it needs.
+pub struct Memo<'a, A, R> {
+    f: Function<'a, A, R>,
+    cache: HashMap<A, R>,
+    /// The cached arguments, oldest first, for knowing which to forget when full
+    order: VecDeque<A>,
+    capacity: Option<usize>,
+    hits: usize,
+    misses: usize,
+    /// Whether answers found in the cache count as hits, which they don't while the
+    /// function's being called again by `call_iterative`
+    count_hits: bool,
+    /// While evaluating on an explicit stack, the arguments the function has asked for
+    /// which it will have to be called again once they're known, and the answer it's
+    /// given for them in the meantime
+    missing: Option<(Vec<A>, R)>,
+    /// Everything worked out so far on an explicit stack, kept whatever the capacity until
+    /// the evaluation is finished so that nothing's forgotten before it's needed
+    pinned: HashMap<A, R>,
+}
+
+impl<'a, A, R> Memo<'a, A, R>
+where
+    A: Clone + Eq + Hash,
+    R: Clone,
+{
+    pub fn new(f: impl Fn(&mut Memo<'a, A, R>, &A) -> R + 'a) -> Self {
+        Memo {
+            f: Rc::new(f),
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: None,
+            hits: 0,
+            misses: 0,
+            count_hits: true,
+            missing: None,
+            pinned: HashMap::new(),
+        }
+    }
+
+    /// As for [`new`](Memo::new), keeping no more than `capacity` answers by forgetting
+    /// the oldest
+    pub fn bounded(f: impl Fn(&mut Memo<'a, A, R>, &A) -> R + 'a, capacity: usize) -> Self {
+        Memo {
+            capacity: Some(capacity),
+            ..Self::new(f)
+        }
+    }
+
+    /// What the function returns for `arg`, from the cache if it's been worked out before
+    pub fn call(&mut self, arg: A) -> R {
+        if let Some(answer) = self.lookup(&arg) {
+            return answer;
+        }
+        if let Some((missing, placeholder)) = &mut self.missing {
+            // Worked out later by `call_iterative`, this call's answer being thrown away
+            missing.push(arg);
+            return placeholder.clone();
+        }
+        self.misses += 1;
+        let answer = Rc::clone(&self.f)(self, &arg);
+        self.insert(arg, answer.clone());
+        answer
+    }
+
+    /// As for [`call`](Memo::call), keeping the arguments still to be worked out on a
+    /// stack rather than recursing, so that it can go as deep as memory allows. When the
+    /// function asks for an answer which isn't known yet it's given a default, and is
+    /// called again once everything it asked for is known, so it mustn't have side effects.
+    pub fn call_iterative(&mut self, arg: A) -> R
+    where
+        R: Default,
+    {
+        if let Some(answer) = self.lookup(&arg) {
+            return answer;
+        }
+        let mut stack = vec![arg.clone()];
+        let mut evaluated = HashSet::new();
+        while let Some(top) = stack.last().cloned() {
+            if self.pinned.contains_key(&top) {
+                stack.pop();
+                continue;
+            }
+            self.count_hits = evaluated.insert(top.clone());
+            self.missing = Some((vec![], R::default()));
+            let answer = panic::catch_unwind(AssertUnwindSafe(|| Rc::clone(&self.f)(self, &top)));
+            let (missing, _) = self.missing.take().unwrap();
+            self.count_hits = true;
+            // Should the function panic, put the memo back as it was, so that later calls
+            // aren't still handed placeholders
+            let answer = answer.unwrap_or_else(|payload| {
+                self.pinned.clear();
+                panic::resume_unwind(payload)
+            });
+            if missing.is_empty() {
+                self.misses += 1;
+                self.pinned.insert(top.clone(), answer.clone());
+                self.insert(top, answer);
+                stack.pop();
+            } else {
+                stack.extend(missing);
+            }
+        }
+        let answer = self.pinned[&arg].clone();
+        self.pinned.clear();
+        answer
+    }
+
+    fn lookup(&mut self, arg: &A) -> Option<R> {
+        let answer = self.cache.get(arg).or_else(|| self.pinned.get(arg))?;
+        if self.count_hits {
+            self.hits += 1;
+        }
+        Some(answer.clone())
+    }
+
+    fn insert(&mut self, arg: A, answer: R) {
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return;
+            }
+            while self.cache.len() >= capacity {
+                let oldest = self.order.pop_front().unwrap();
+                self.cache.remove(&oldest);
+            }
+        }
+        self.order.push_back(arg.clone());
+        self.cache.insert(arg, answer);
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.cache.len(),
+        }
+    }
+
+    /// Forgets every answer, and the stats with them
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, &n: &u64) -> u64 {
+        if n < 2 {
+            n
+        } else {
+            memo.call(n - 1) + memo.call(n - 2)
+        }
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::new(fib);
+        assert_eq!(55, memo.call(10));
+        assert_eq!(
+            MemoStats {
+                hits: 8,
+                misses: 11,
+                size: 11
+            },
+            memo.stats()
+        );
+
+        assert_eq!(55, memo.call(10));
+        assert_eq!(9, memo.stats().hits);
+
+        memo.clear();
+        assert_eq!(MemoStats::default(), memo.stats());
+    }
+
+    #[test]
+    fn test_memo_bounded() {
+        let mut memo = Memo::bounded(fib, 3);
+        assert_eq!(12586269025, memo.call(50));
+        assert_eq!(3, memo.stats().size);
+
+        let mut memo = Memo::bounded(fib, 0);
+        assert_eq!(55, memo.call(10));
+        assert_eq!(0, memo.stats().size);
+        assert_eq!(177, memo.stats().misses);
+    }
+
+    #[test]
+    fn test_memo_closure() {
+        // The ways of climbing to each step, from the steps it can be reached from
+        let from: Vec<Vec<usize>> = vec![vec![], vec![0], vec![0, 1], vec![1, 2], vec![1, 3]];
+        let mut memo = Memo::new(|memo: &mut Memo<usize, u64>, &step: &usize| {
+            if step == 0 {
+                1
+            } else {
+                from[step].iter().map(|&prev| memo.call(prev)).sum()
+            }
+        });
+        assert_eq!(4, memo.call(4));
+        assert_eq!(4, memo.call_iterative(4));
+    }
+
+    #[test]
+    fn test_memo_iterative() {
+        let mut memo = Memo::new(fib);
+        assert_eq!(12586269025, memo.call_iterative(50));
+        assert_eq!(
+            MemoStats {
+                hits: 48,
+                misses: 51,
+                size: 51
+            },
+            memo.stats()
+        );
+
+        // Far deeper than recursing would go
+        fn depth(memo: &mut Memo<u64, u64>, &n: &u64) -> u64 {
+            if n == 0 { 0 } else { memo.call(n - 1) + 1 }
+        }
+        assert_eq!(1_000_000, Memo::new(depth).call_iterative(1_000_000));
+
+        let mut memo = Memo::bounded(fib, 2);
+        assert_eq!(12586269025, memo.call_iterative(50));
+        assert_eq!(2, memo.stats().size);
+        assert_eq!(51, memo.stats().misses);
+    }
+
+    #[test]
+    fn test_memo_iterative_panic() {
+        // Panics the first time it's asked about 5
+        let panicked = std::cell::Cell::new(false);
+        let mut memo = Memo::new(|memo: &mut Memo<u64, u64>, &n: &u64| {
+            if n == 5 && !panicked.replace(true) {
+                panic!("no answer for 5 yet");
+            }
+            if n == 0 { 0 } else { memo.call(n - 1) + 1 }
+        });
+        let result = panic::catch_unwind(AssertUnwindSafe(|| memo.call_iterative(10)));
+        assert!(result.is_err());
+
+        // Worked out properly rather than being handed placeholders
+        assert_eq!(10, memo.call(10));
+    }
+}
@@ -1,7 +1,24 @@
-#![feature(let_chains)]
+//! Solutions to Advent of Code 2024.
+//!
+//! Each day's module, such as [`day12`], has a `parse_input` function turning the puzzle
+//! input into that day's `Input`, a public function per part solving it, and a `DayNN`
+//! [`Solution`] tying them together. [`get_solution`] looks up a day's solution at runtime,
+//! and [`utils`] holds the grid and geometry types the days share.
 
 #[macro_use]
 extern crate lazy_static;
 
-#[allow(dead_code)]
 mod aoc;
+
+pub use aoc::{
+    Answer, Baseline, BenchStats, Cache, Cancel, CountingAllocator, DEFAULT_CACHE_DIR, Error,
+    Example, Expected, Format, Frames, Image, Manifest, Outcome, OutputFormat, Overlay, Params,
+    ParseError, Part, Rgb, RunError, RunRecord, Runner, Saved, Solution, Status, TimedOut, Verdict,
+    bench, default_input_file, dot, extract_examples, get_solution, get_solution_with_params,
+    get_solutions, page_day, params_to_string, read_input, read_input_file, render, save_examples,
+    scaffold, verify,
+};
+pub use aoc::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, differential, generate, parallel, trace, utils,
+};
@@ -0,0 +1,917 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use aoc::{
+    Baseline, Cache, Cancel, CountingAllocator, Error, Format, Frames, Manifest, OutputFormat,
+    Params, Part, RunError, RunRecord, Runner, Status, Verdict, parallel, trace,
+};
+use clap::{Args, Parser, Subcommand};
+use itertools::Itertools;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code 2024 solutions", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Trace what the solutions are doing to stderr, more for each -v (up to -vvv)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only trace this day. Can be repeated, every day is traced if omitted
+    #[arg(long = "trace-day", global = true, value_name = "DAY")]
+    trace_days: Vec<u8>,
+
+    /// Write the trace to this file as JSON lines instead of to stderr
+    #[arg(long, global = true, value_name = "FILE")]
+    trace_json: Option<String>,
+
+    /// Spread the slower days across this many threads, 0 for one per core. Needs the
+    /// `parallel` feature to be more than 1
+    #[arg(short = 'j', long, global = true, value_name = "N")]
+    threads: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one day (or every day) and print the answers
+    Run(RunArgs),
+    /// Time each day and part against its real input
+    Bench(BenchArgs),
+    /// Check answers against those expected in the answer manifest
+    Verify(VerifyArgs),
+    /// Save each step of a day's simulation as a numbered image (days 14 and 15)
+    Render(RenderArgs),
+    /// Start a new day from the template, with empty inputs, and register it
+    New(NewArgs),
+    /// Save the examples from a saved puzzle page as test inputs, with their answers
+    Extract(ExtractArgs),
+    /// Print a random input for a day, the same every time for the same size and seed
+    Generate(GenerateArgs),
+    /// Check that a day's different implementations agree on generated inputs, showing the
+    /// smallest input they disagree on if they don't
+    Compare(CompareArgs),
+    /// Check inputs against each day's rules without solving them, listing every problem
+    Validate(ValidateArgs),
+    /// Print a day's puzzle as a Graphviz DOT graph (days 5 and 10)
+    Dot(DotArgs),
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Day to run
+    #[arg(short, long, required_unless_present = "all", conflicts_with = "all")]
+    day: Option<u8>,
+
+    /// Part to run, both parts are run if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Input file, or - to read from stdin. Defaults to input/dayNN.txt
+    #[arg(short, long, conflicts_with = "all")]
+    input: Option<String>,
+
+    /// Run every solved day against its default input
+    #[arg(short, long)]
+    all: bool,
+
+    /// Give up on any part which takes longer than this many seconds
+    #[arg(short, long, value_name = "SECS", value_parser = parse_secs)]
+    timeout: Option<Duration>,
+
+    /// Parameter overriding the solution's default, e.g. width=11. Can be repeated
+    #[arg(
+        short = 'P',
+        long = "param",
+        value_name = "NAME=VALUE",
+        conflicts_with = "all"
+    )]
+    params: Vec<String>,
+
+    /// How to write the results: text, or json or csv for other programs to read
+    #[arg(short, long, default_value = "text")]
+    format: OutputFormat,
+
+    #[command(flatten)]
+    cache: CacheArgs,
+}
+
+#[derive(Args)]
+struct CacheArgs {
+    /// Solve every part again rather than using cached answers, and don't cache them
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Throw away every cached answer before starting
+    #[arg(long)]
+    clear_cache: bool,
+}
+
+impl CacheArgs {
+    /// The answer cache these flags ask for, cleared first if they say so
+    fn open(&self) -> Result<Option<Cache>, ExitCode> {
+        let cache = Cache::new(aoc::DEFAULT_CACHE_DIR);
+        if self.clear_cache {
+            match cache.clear() {
+                Ok(cleared) => eprintln!("Cleared {} cached answers", cleared),
+                Err(why) => {
+                    eprintln!("Couldn't clear {}: {}", aoc::DEFAULT_CACHE_DIR, why);
+                    return Err(ExitCode::FAILURE);
+                }
+            }
+        }
+        Ok((!self.no_cache).then_some(cache))
+    }
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Day to benchmark, every solved day if omitted
+    #[arg(short, long)]
+    day: Option<u8>,
+
+    /// Part to benchmark, both parts are benchmarked if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Number of times to run each part
+    #[arg(short = 'n', long, default_value_t = 10)]
+    iterations: usize,
+
+    /// File holding the results to compare against
+    #[arg(short, long, default_value = "bench_baseline.txt")]
+    baseline: String,
+
+    /// Save these results as the new baseline instead of comparing against it
+    #[arg(short, long)]
+    save: bool,
+
+    /// Percentage a median may grow by over the baseline before it's a regression
+    #[arg(short, long, default_value_t = 10.0)]
+    threshold: f64,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Only check inputs for this day
+    #[arg(short, long)]
+    day: Option<u8>,
+
+    /// Answer manifest to check against
+    #[arg(short, long, default_value = "answers.toml")]
+    manifest: String,
+
+    /// Also check the answers marked as slow
+    #[arg(short, long)]
+    slow: bool,
+
+    /// Count any part which takes longer than this many seconds as a failure
+    #[arg(short, long, value_name = "SECS", value_parser = parse_secs)]
+    timeout: Option<Duration>,
+
+    #[command(flatten)]
+    cache: CacheArgs,
+}
+
+#[derive(Args)]
+struct RenderArgs {
+    /// Day to render
+    #[arg(short, long)]
+    day: u8,
+
+    /// Part to render
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// Input file. Defaults to input/dayNN.txt
+    #[arg(short, long)]
+    input: Option<String>,
+
+    /// Parameter overriding the solution's default, e.g. width=11. Can be repeated
+    #[arg(short = 'P', long = "param", value_name = "NAME=VALUE")]
+    params: Vec<String>,
+
+    /// Directory to save the frames in
+    #[arg(short, long, default_value = "frames")]
+    output: String,
+
+    /// Image format: ppm, png or svg
+    #[arg(short, long, default_value = "png")]
+    format: Format,
+
+    /// Width in pixels of each tile
+    #[arg(short, long, default_value_t = 4)]
+    scale: usize,
+}
+
+#[derive(Args)]
+struct NewArgs {
+    /// Day to start
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// The puzzle's title, for the module's doc comment
+    #[arg(short, long, default_value = "TODO")]
+    title: String,
+}
+
+#[derive(Args)]
+struct ExtractArgs {
+    /// Puzzle page saved from the browser
+    page: String,
+
+    /// Day the page is for, if its title doesn't say
+    #[arg(short, long)]
+    day: Option<u8>,
+
+    /// Answer manifest to record the examples' answers in
+    #[arg(short, long, default_value = "answers.toml")]
+    manifest: String,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Day to generate an input for
+    #[arg(short, long)]
+    day: u8,
+
+    /// How big an input to make. What this counts depends on the day, e.g. lines or a
+    /// grid's width
+    #[arg(short, long, default_value_t = 10)]
+    size: usize,
+
+    /// Seed for the random numbers
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Write the input to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct DotArgs {
+    /// Day to show the graph of
+    #[arg(short, long)]
+    day: u8,
+
+    /// Input file. Defaults to input/dayNN.txt
+    #[arg(short, long)]
+    input: Option<String>,
+
+    /// Write the graph to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct CompareArgs {
+    /// Day to compare the implementations of
+    #[arg(short, long)]
+    day: u8,
+
+    /// Part to compare, both parts are compared if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Largest input to generate, as for the generate command
+    #[arg(short, long, default_value_t = 10)]
+    size: usize,
+
+    /// How many seeds to try for each size
+    #[arg(long, default_value_t = 20)]
+    seeds: u64,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    /// Day to check the input of
+    #[arg(short, long, required_unless_present = "all", conflicts_with = "all")]
+    day: Option<u8>,
+
+    /// Input file, or - to read from stdin. Defaults to input/dayNN.txt
+    #[arg(short, long, conflicts_with = "all")]
+    input: Option<String>,
+
+    /// Check every solved day's default input
+    #[arg(short, long)]
+    all: bool,
+}
+
+fn select_solutions(day: Option<u8>) -> Option<Vec<(u8, Box<dyn Runner>)>> {
+    match day {
+        Some(day) => match aoc::get_solution(day) {
+            Some(solution) => Some(vec![(day, solution)]),
+            None => {
+                eprintln!("Day {} has no solution", day);
+                None
+            }
+        },
+        None => Some(aoc::get_solutions()),
+    }
+}
+
+fn select_parts(part: Option<u8>) -> Vec<Part> {
+    match part.map(Part::try_from) {
+        Some(Ok(part)) => vec![part],
+        _ => Part::ALL.to_vec(),
+    }
+}
+
+fn run(args: &RunArgs) -> ExitCode {
+    let params = match parse_params(&args.params) {
+        Ok(params) => params,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let solutions = match (args.day, params.is_empty()) {
+        (Some(day), false) => match aoc::get_solution_with_params(day, &params) {
+            Ok(solution) => vec![(day, solution)],
+            Err(error) => {
+                eprintln!("Day {}: {}", day, error);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => match select_solutions(args.day) {
+            Some(solutions) => solutions,
+            None => return ExitCode::FAILURE,
+        },
+    };
+    let parts = select_parts(args.part);
+    let cache = match args.cache.open() {
+        Ok(cache) => cache,
+        Err(exit_code) => return exit_code,
+    };
+
+    if args.format == OutputFormat::Csv {
+        println!("{}", RunRecord::CSV_HEADER);
+    }
+    let mut failed = false;
+
+    for (day, solution) in solutions {
+        let input_file = args
+            .input
+            .clone()
+            .unwrap_or_else(|| aoc::default_input_file(day));
+        let (input_name, input) = if input_file == "-" {
+            ("<stdin>", aoc::read_input("<stdin>", io::stdin().lock()))
+        } else {
+            (input_file.as_str(), aoc::read_input_file(&input_file))
+        };
+
+        for &part in &parts {
+            let record = RunRecord {
+                day,
+                part,
+                input: input_name.to_string(),
+                params: aoc::params_to_string(&params),
+                status: Status::Ok,
+                answer: None,
+                error: None,
+                elapsed: Duration::ZERO,
+                cached: false,
+            };
+            let record = run_part(
+                record,
+                solution.as_ref(),
+                &input,
+                &params,
+                args.timeout,
+                cache.as_ref(),
+            );
+            failed |= record.status != Status::Ok;
+
+            match args.format {
+                OutputFormat::Text if record.status == Status::Ok => println!("{}", record),
+                OutputFormat::Text => eprintln!("{}", record),
+                OutputFormat::JsonLines => println!("{}", record.to_json()),
+                OutputFormat::Csv => println!("{}", record.to_csv()),
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Solves the part `record` is for, or finds its answer in `cache`, and fills in how that
+/// went
+fn run_part(
+    mut record: RunRecord,
+    solution: &dyn Runner,
+    input: &Result<String, Error>,
+    params: &Params,
+    timeout: Option<Duration>,
+    cache: Option<&Cache>,
+) -> RunRecord {
+    let (day, part) = (record.day, record.part);
+    let input = match input {
+        Ok(input) => input,
+        Err(error) => {
+            record.status = Status::InputError;
+            record.error = Some(error.to_string());
+            return record;
+        }
+    };
+
+    if let Some(answer) = cache.and_then(|cache| cache.get(day, part, params, input)) {
+        record.answer = Some(answer);
+        record.cached = true;
+        return record;
+    }
+
+    let start = Instant::now();
+    let cancel = Cancel::within(timeout);
+    let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+        solution.run_within(part, input, &cancel)
+    }));
+    record.elapsed = start.elapsed();
+
+    match answer {
+        Ok(Ok(answer)) => {
+            if let Some(cache) = cache
+                && let Err(why) = cache.put(day, part, params, input, &answer)
+            {
+                eprintln!("Couldn't cache the answer: {}", why);
+            }
+            record.answer = Some(answer);
+        }
+        Ok(Err(error)) => {
+            record.status = match error {
+                RunError::Parse(_) => Status::ParseError,
+                RunError::TimedOut(_) => Status::TimedOut,
+            };
+            record.error = Some(Error::run(&record.input, error).to_string());
+        }
+        Err(_) => {
+            record.status = Status::Panicked;
+            record.error = Some(format!("failed on {}", record.input));
+        }
+    }
+    record
+}
+
+fn bench(args: &BenchArgs) -> ExitCode {
+    let Some(solutions) = select_solutions(args.day) else {
+        return ExitCode::FAILURE;
+    };
+    let parts = select_parts(args.part);
+
+    // Only a missing baseline is expected, so anything else wrong with it is an error
+    let saved = match Baseline::load(&args.baseline) {
+        Ok(saved) => Some(saved),
+        Err(Error::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let baseline = if args.save {
+        None
+    } else {
+        if saved.is_none() {
+            eprintln!(
+                "No baseline in {}, run with --save to make one",
+                args.baseline
+            );
+        }
+        saved.as_ref()
+    };
+
+    let mut results = Baseline::default();
+    let mut failed = false;
+
+    println!(
+        "{:>3} {:>4} {:>12} {:>12} {:>12} {:>12} {:>9}",
+        "day", "part", "min", "median", "p95", "allocations", "change"
+    );
+
+    for (day, solution) in solutions {
+        let input_file = aoc::default_input_file(day);
+        let input = match aoc::read_input_file(&input_file) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("Day {}: {}", day, error);
+                failed = true;
+                continue;
+            }
+        };
+
+        for &part in &parts {
+            let stats = panic::catch_unwind(AssertUnwindSafe(|| {
+                aoc::bench(solution.as_ref(), part, &input, args.iterations)
+            }));
+            let stats = match stats {
+                Ok(Ok(stats)) => stats,
+                Ok(Err(error)) => {
+                    eprintln!(
+                        "Day {} part {}: {}",
+                        day,
+                        part,
+                        Error::parse(&input_file, error)
+                    );
+                    failed = true;
+                    continue;
+                }
+                Err(_) => {
+                    eprintln!("Day {} part {}: failed on {}", day, part, input_file);
+                    failed = true;
+                    continue;
+                }
+            };
+
+            let change = match baseline.and_then(|baseline| baseline.stats.get(&(day, part))) {
+                Some(baseline) => {
+                    let slowdown = stats.slowdown(baseline);
+                    if slowdown > args.threshold {
+                        failed = true;
+                        format!("{:+.1}% REGRESSED", slowdown)
+                    } else {
+                        format!("{:+.1}%", slowdown)
+                    }
+                }
+                None => String::from("-"),
+            };
+            println!(
+                "{:>3} {:>4} {:>12} {:>12} {:>12} {:>12} {:>9}",
+                day,
+                part,
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.p95),
+                stats.allocations,
+                change
+            );
+            results.stats.insert((day, part), stats);
+        }
+    }
+
+    if args.save {
+        // Keep the entries for any days and parts which weren't benchmarked this time
+        let mut saved = saved.unwrap_or_default();
+        saved.stats.extend(results.stats);
+        if let Err(error) = saved.save(&args.baseline) {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+        println!("Saved baseline to {}", args.baseline);
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn verify(args: &VerifyArgs) -> ExitCode {
+    let manifest = match Manifest::load(&args.manifest) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let answers = manifest.answers.iter().filter(|expected| {
+        args.day.is_none_or(|day| day == expected.day) && (args.slow || !expected.slow)
+    });
+    let cache = match args.cache.open() {
+        Ok(cache) => cache,
+        Err(exit_code) => return exit_code,
+    };
+    let outcomes = aoc::verify(answers, args.timeout, cache.as_ref());
+
+    println!(
+        "{:>3} {:>4} {:<26} {:<36} {:>16} {:>10}  result",
+        "day", "part", "input", "params", "expected", "time"
+    );
+    for outcome in &outcomes {
+        let result = match &outcome.verdict {
+            Verdict::Pass => String::from("pass"),
+            Verdict::Fail { actual } => format!("FAIL, got {}", actual),
+            Verdict::Error(error) => format!("ERROR, {}", error),
+            Verdict::TimedOut => String::from("TIMED OUT"),
+        };
+        println!(
+            "{:>3} {:>4} {:<26} {:<36} {:>16} {:>10}  {}",
+            outcome.expected.day,
+            outcome.part,
+            outcome.expected.input,
+            outcome.expected.params_to_string(),
+            outcome.answer().to_string(),
+            if outcome.cached {
+                String::from("cached")
+            } else {
+                format!("{:.2?}", outcome.elapsed)
+            },
+            result
+        );
+    }
+
+    let passed = outcomes.iter().filter(|outcome| outcome.passed()).count();
+    println!("{} of {} passed", passed, outcomes.len());
+
+    if passed == outcomes.len() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn parse_secs(secs: &str) -> Result<Duration, String> {
+    secs.parse()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("expected a number of seconds, found `{}`", secs))
+}
+
+/// Reads `name=value` pairs, where each value is written as it would be in the manifest.
+/// Anything which isn't valid there is taken to be a string.
+fn parse_params(params: &[String]) -> Result<Params, String> {
+    let mut table = Params::new();
+    for param in params {
+        let (name, value) = param
+            .split_once('=')
+            .ok_or_else(|| format!("expected NAME=VALUE, found `{}`", param))?;
+        let parsed: Params = toml::from_str(param).unwrap_or_else(|_| {
+            Params::from_iter([(name.trim().to_string(), toml::Value::from(value))])
+        });
+        table.extend(parsed);
+    }
+    Ok(table)
+}
+
+fn render(args: &RenderArgs) -> ExitCode {
+    let params = match parse_params(&args.params) {
+        Ok(params) => params,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let part = Part::try_from(args.part).unwrap_or(Part::One);
+    let input_file = args
+        .input
+        .clone()
+        .unwrap_or_else(|| aoc::default_input_file(args.day));
+
+    let mut frames = match Frames::new(&args.output, args.format, args.scale) {
+        Ok(frames) => frames,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    match aoc::render(args.day, part, &input_file, &params, &mut frames) {
+        Ok(()) => {
+            println!("Saved {} frames to {}", frames.count(), args.output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Day {} part {}: {}", args.day, part, error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn new(args: &NewArgs) -> ExitCode {
+    match aoc::scaffold(Path::new("."), args.day, &args.title) {
+        Ok(files) => {
+            for file in files {
+                println!("Wrote {}", file.display());
+            }
+            println!("Run `cargo fmt` to tidy the registration, then fill in the puzzle");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn extract(args: &ExtractArgs) -> ExitCode {
+    let html = match aoc::read_input_file(&args.page) {
+        Ok(html) => html,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(day) = args.day.or_else(|| aoc::page_day(&html)) else {
+        eprintln!("Can't tell which day {} is for, use --day", args.page);
+        return ExitCode::FAILURE;
+    };
+
+    let examples = aoc::extract_examples(&html);
+    match aoc::save_examples(Path::new("."), day, &examples, &args.manifest) {
+        Ok(saved) => {
+            for (example, saved) in examples.iter().zip(saved) {
+                let status = if saved.written { "wrote" } else { "already in" };
+                let candidates = |answers: &[aoc::Answer]| match answers {
+                    [] => String::from("-"),
+                    answers => answers.iter().join(", "),
+                };
+                println!(
+                    "{} {} (part 1: {}; part 2: {}){}",
+                    status,
+                    saved.input_file,
+                    candidates(&example.part1),
+                    candidates(&example.part2),
+                    if saved.recorded {
+                        format!(", recorded in {}", args.manifest)
+                    } else {
+                        String::new()
+                    }
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate(args: &GenerateArgs) -> ExitCode {
+    let Some(input) = aoc::generate::generate(args.day, args.size, args.seed) else {
+        eprintln!("Day {} has no input generator", args.day);
+        return ExitCode::FAILURE;
+    };
+    match &args.output {
+        Some(output) => {
+            if let Err(why) = std::fs::write(output, input) {
+                eprintln!("Couldn't write {}: {}", output, why);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", input),
+    }
+    ExitCode::SUCCESS
+}
+
+fn dot(args: &DotArgs) -> ExitCode {
+    let input_file = args
+        .input
+        .clone()
+        .unwrap_or_else(|| aoc::default_input_file(args.day));
+    let dot = match aoc::dot(args.day, &input_file) {
+        Ok(dot) => dot,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    match &args.output {
+        Some(output) => {
+            if let Err(why) = std::fs::write(output, dot) {
+                eprintln!("Couldn't write {}: {}", output, why);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", dot),
+    }
+    ExitCode::SUCCESS
+}
+
+fn compare(args: &CompareArgs) -> ExitCode {
+    let mut compared = false;
+    for part in select_parts(args.part) {
+        let implementations = aoc::differential::implementations(args.day, part);
+        if implementations.len() < 2 {
+            continue;
+        }
+        compared = true;
+        let names = implementations
+            .iter()
+            .map(|implementation| implementation.name);
+        match aoc::differential::cross_check(args.day, &implementations, args.size, args.seeds) {
+            Ok(checked) => println!(
+                "Day {} part {}: {} agree on {} inputs",
+                args.day,
+                part,
+                names.format(", "),
+                checked
+            ),
+            Err(mismatch) => {
+                print!("Day {} part {}: {}", args.day, part, mismatch);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if !compared {
+        eprintln!("Day {} has no other implementations to compare", args.day);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn validate(args: &ValidateArgs) -> ExitCode {
+    let Some(solutions) = select_solutions(args.day) else {
+        return ExitCode::FAILURE;
+    };
+
+    let mut failed = false;
+    for (day, solution) in solutions {
+        let input_file = args
+            .input
+            .clone()
+            .unwrap_or_else(|| aoc::default_input_file(day));
+        let (input_name, input) = if input_file == "-" {
+            ("<stdin>", aoc::read_input("<stdin>", io::stdin().lock()))
+        } else {
+            (input_file.as_str(), aoc::read_input_file(&input_file))
+        };
+        let input = match input {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("Day {}: {}", day, error);
+                failed = true;
+                continue;
+            }
+        };
+
+        let errors = solution.validate(&input);
+        if errors.is_empty() {
+            println!("Day {}: {} is valid", day, input_name);
+        }
+        for error in errors {
+            println!("{}", Error::parse(input_name, error));
+            failed = true;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn install_tracer(cli: &Cli) -> Result<(), Error> {
+    let level = match (cli.verbose, &cli.trace_json) {
+        (0, None) => return Ok(()),
+        // Asking for a trace file means wanting something in it
+        (0, Some(_)) => trace::Level::Info,
+        (verbose, _) => trace::Level::from_verbosity(verbose),
+    };
+    let sink = match &cli.trace_json {
+        Some(trace_file) => {
+            let file = File::create(trace_file).map_err(|why| Error::io(trace_file, why))?;
+            trace::Sink::JsonLines(Box::new(BufWriter::new(file)))
+        }
+        None => trace::Sink::Text(Box::new(io::stderr())),
+    };
+    trace::install(trace::Tracer {
+        level,
+        days: cli.trace_days.clone(),
+        sink,
+    });
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    if let Err(error) = install_tracer(&cli) {
+        eprintln!("{}", error);
+        return ExitCode::FAILURE;
+    }
+    if let Some(threads) = cli.threads
+        && let Err(error) = parallel::set_threads(threads)
+    {
+        eprintln!("{}", error);
+        return ExitCode::FAILURE;
+    }
+
+    let exit_code = match &cli.command {
+        Command::Run(args) => run(args),
+        Command::Bench(args) => bench(args),
+        Command::Verify(args) => verify(args),
+        Command::Render(args) => render(args),
+        Command::New(args) => new(args),
+        Command::Extract(args) => extract(args),
+        Command::Generate(args) => generate(args),
+        Command::Compare(args) => compare(args),
+        Command::Validate(args) => validate(args),
+        Command::Dot(args) => dot(args),
+    };
+    trace::uninstall();
+    exit_code
+}
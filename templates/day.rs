@@ -0,0 +1,71 @@
+// https://adventofcode.com/2024/day/{{day}}
+
+//! Day {{day}}: {{title}}.
+
+use std::fmt::Display;
+
+use super::cancel::{Cancel, TimedOut};
+use super::error::{Error, ParseError};
+use super::solution::Solution;
+use super::utils::parse_input_file;
+
+/// The puzzle input
+pub struct Input {
+    /// Each line of the input
+    pub lines: Vec<String>,
+}
+
+/// Reads each line of the puzzle input as it is
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        lines: input.lines().map(String::from).collect(),
+    })
+}
+
+pub fn get_part_one(input_file: &str) -> Result<usize, Error> {
+    Ok(part_one(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part one
+pub fn part_one(input: &Input) -> usize {
+    input.lines.len()
+}
+
+pub fn get_part_two(input_file: &str) -> Result<usize, Error> {
+    Ok(part_two(&parse_input_file(input_file, parse_input)?))
+}
+
+/// Part two
+pub fn part_two(input: &Input) -> usize {
+    input.lines.len()
+}
+
+/// The [`Solution`] for day {{day}}: [`part_one`] and [`part_two`]
+pub struct Day{{dd}};
+
+impl Solution for Day{{dd}} {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(part_one(input))
+    }
+
+    fn part2(&self, input: &Input, _cancel: &Cancel) -> Result<impl Display, TimedOut> {
+        Ok(part_two(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let input = parse_input("first\nsecond\n").unwrap();
+        assert_eq!(vec!["first", "second"], input.lines);
+    }
+}